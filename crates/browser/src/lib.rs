@@ -2,7 +2,7 @@
 #![deny(unsafe_code)]
 
 use futures::stream::TryStreamExt;
-use tower_lsp::{LspService, Server};
+use tower_lsp::Server;
 use wasm_bindgen::{prelude::*, JsCast};
 use wasm_bindgen_futures::{stream::JsStream, JsFuture};
 
@@ -44,15 +44,19 @@ pub async fn serve(config: ServerConfig) -> Result<(), JsValue> {
     #[rustfmt::skip]
     #[cfg(target_arch = "wasm32")]
     let languages = wasm_lsp_server::core::SessionLanguages {
-        wast: wasm_lsp_languages::language::wast().await.unwrap(),
-        wat : wasm_lsp_languages::language::wat ().await.unwrap(),
+        component: wasm_lsp_languages::language::component().await.unwrap(),
+        wast     : wasm_lsp_languages::language::wast     ().await.unwrap(),
+        wat      : wasm_lsp_languages::language::wat      ().await.unwrap(),
+        wit      : wasm_lsp_languages::language::wit      ().await.unwrap(),
     };
 
     #[rustfmt::skip]
     #[cfg(not(target_arch = "wasm32"))]
     let languages = wasm_lsp_server::core::SessionLanguages {
-        wast: wasm_lsp_languages::language::wast(),
-        wat : wasm_lsp_languages::language::wat (),
+        component: wasm_lsp_languages::language::component(),
+        wast     : wasm_lsp_languages::language::wast     (),
+        wat      : wasm_lsp_languages::language::wat      (),
+        wit      : wasm_lsp_languages::language::wit      (),
     };
 
     let stdin = JsStream::from(into_server);
@@ -70,7 +74,10 @@ pub async fn serve(config: ServerConfig) -> Result<(), JsValue> {
     let stdout = wasm_streams::WritableStream::from_raw(stdout);
     let stdout = stdout.try_into_async_write().map_err(|err| err.0)?;
 
-    let (service, socket) = LspService::new(|client| wasm_lsp_server::Server::new(languages, client).unwrap());
+    let spawner: wasm_lsp_server::core::Spawner =
+        std::sync::Arc::new(|task: futures::future::BoxFuture<'static, ()>| wasm_bindgen_futures::spawn_local(task));
+    let (service, socket) = wasm_lsp_server::Server::service(languages, Some(spawner))
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
     Server::new(stdin, stdout, socket).serve(service).await;
 
     Ok(())
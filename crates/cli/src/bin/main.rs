@@ -4,16 +4,27 @@
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 
-use clap::Command;
-use tower_lsp::{LspService, Server};
+use clap::{Arg, ArgMatches, Command};
+use wasm_lsp_cli::transport::Transport;
 
-fn cli() {
+fn cli() -> ArgMatches {
     use wasm_lsp_server::metadata;
-    Command::new(metadata::PKG_NAME)
+    let command = Command::new(metadata::PKG_NAME)
         .author(metadata::PKG_AUTHORS)
         .version(metadata::PKG_VERSION)
         .about(metadata::PKG_DESCRIPTION)
-        .get_matches();
+        .arg(
+            Arg::new("client-process-id")
+                .long("client-process-id")
+                .value_name("PID")
+                .takes_value(true)
+                .validator(|value| value.parse::<u32>())
+                .help("Shut down the server once the client process exits"),
+        )
+        .subcommand(wasm_lsp_cli::check::command())
+        .subcommand(wasm_lsp_cli::format::command())
+        .subcommand(wasm_lsp_cli::index::command());
+    Transport::args(command).get_matches()
 }
 
 fn main() -> anyhow::Result<()> {
@@ -21,27 +32,35 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Run the server with the futures runtime.
+/// Run the subcommand, or the server with the futures runtime.
 fn run() -> anyhow::Result<()> {
     env_logger::try_init()?;
-    cli();
+    let matches = cli();
+    match matches.subcommand() {
+        Some(("check", matches)) => exit(wasm_lsp_cli::check::run(matches)?),
+        Some(("format", matches)) => exit(wasm_lsp_cli::format::run(matches)?),
+        Some(("index", matches)) => exit(wasm_lsp_cli::index::run(matches)?),
+        _ => serve(&matches),
+    }
+}
+
+/// Exit with a non-zero status unless the subcommand `passed`.
+fn exit(passed: bool) -> anyhow::Result<()> {
+    if !passed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run the server with the futures runtime.
+fn serve(matches: &ArgMatches) -> anyhow::Result<()> {
+    if matches.is_present("client-process-id") {
+        let pid = matches.value_of_t("client-process-id")?;
+        wasm_lsp_cli::process::watch(pid);
+    }
+    let transport = Transport::from_matches(matches);
     futures::executor::block_on(async {
-        #[rustfmt::skip]
-        #[cfg(target_arch = "wasm32")]
-        let languages = wasm_lsp_server::core::SessionLanguages {
-            wast: wasm_lsp_languages::language::wast().await?,
-            wat : wasm_lsp_languages::language::wat ().await?,
-        };
-        #[rustfmt::skip]
-        #[cfg(not(target_arch = "wasm32"))]
-        let languages = wasm_lsp_server::core::SessionLanguages {
-            wast: wasm_lsp_languages::language::wast(),
-            wat : wasm_lsp_languages::language::wat (),
-        };
-        let (service, socket) = LspService::new(|client| wasm_lsp_server::Server::new(languages, client).unwrap());
-        let stdin = blocking::Unblock::new(std::io::stdin());
-        let stdout = blocking::Unblock::new(std::io::stdout());
-        Server::new(stdin, stdout, socket).serve(service).await;
-        Ok(())
+        let spawner = wasm_lsp_cli::spawner()?;
+        transport.serve(spawner).await
     })
 }
@@ -0,0 +1,415 @@
+//! The `check` subcommand, which reports the diagnostics of files from the command line.
+
+use anyhow::Context;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use tower_lsp::lsp_types as lsp;
+use wasm_lsp_server::core;
+
+/// The format in which the diagnostics are reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// One line per diagnostic, followed by a summary.
+    Human,
+    /// A JSON array with the diagnostics of each file.
+    Json,
+    /// A SARIF (2.1.0) log, e.g., for code scanning services.
+    Sarif,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            "sarif" => Ok(Format::Sarif),
+            _ => anyhow::bail!("unknown output format :: {}", value),
+        }
+    }
+}
+
+/// The diagnostics of a checked file.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// The path of the file, as given on the command line.
+    pub path: PathBuf,
+    /// The diagnostics of the file.
+    pub diagnostics: Vec<lsp::Diagnostic>,
+    /// The error which prevented checking the file (e.g., it could not be read), if any.
+    pub error: Option<String>,
+}
+
+impl Report {
+    /// Count the diagnostics of the [`Report`] with the given `severity`.
+    pub fn count(&self, severity: lsp::DiagnosticSeverity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|it| it.severity == Some(severity))
+            .count()
+    }
+
+    /// Determine whether the file was checked and has no errors.
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.count(lsp::DiagnosticSeverity::ERROR) == 0
+    }
+}
+
+/// Build the [`Command`] for the `check` subcommand.
+pub fn command() -> Command<'static> {
+    Command::new("check")
+        .about("Report the diagnostics of files, failing if there are any errors")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(["human", "json", "sarif"])
+                .default_value("human")
+                .help("The format in which the diagnostics are reported"),
+        )
+        .arg(
+            Arg::new("paths")
+                .value_name("PATHS")
+                .multiple_values(true)
+                .required(true)
+                .help("The files, directories or glob patterns to check"),
+        )
+}
+
+/// Run the `check` subcommand. Returns whether the checked files are free of errors.
+pub fn run(matches: &ArgMatches) -> anyhow::Result<bool> {
+    let format = matches.value_of_t::<Format>("format")?;
+    let paths = matches.values_of("paths").into_iter().flatten().collect::<Vec<_>>();
+    let files = crate::files::collect(&paths)?;
+    let reports = futures::executor::block_on(check(&files))?;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    match format {
+        Format::Human => human(&mut out, &reports)?,
+        Format::Json => writeln!(out, "{:#}", json(&reports))?,
+        Format::Sarif => writeln!(out, "{:#}", sarif(&reports))?,
+    }
+    Ok(reports.iter().all(Report::passed))
+}
+
+/// Compute the diagnostics of `files`, with the settings of their project configurations.
+///
+/// A file which cannot be checked is reported with its error, and the remaining files are still
+/// checked.
+pub async fn check(files: &[PathBuf]) -> anyhow::Result<Vec<Report>> {
+    let languages = crate::languages().await?;
+    let session = Arc::new(core::Session::new(languages, None, None)?);
+    let mut reports = vec![];
+    for path in files {
+        let (diagnostics, error) = match diagnostics(session.clone(), path)
+            .await
+            .with_context(|| format!("failed to check {}", path.display()))
+        {
+            Ok(diagnostics) => (diagnostics, None),
+            Err(error) => (vec![], Some(format!("{:#}", error))),
+        };
+        let path = path.clone();
+        reports.push(Report {
+            path,
+            diagnostics,
+            error,
+        });
+    }
+    Ok(reports)
+}
+
+/// Compute the syntactic and semantic diagnostics of the file at `path`, like the server does for
+/// an open document.
+async fn diagnostics(session: Arc<core::Session>, path: &Path) -> anyhow::Result<Vec<lsp::Diagnostic>> {
+    let uri = crate::files::uri(path)?;
+    let language = core::Language::try_from(path)?;
+    if let core::Language::Wasm = language {
+        anyhow::bail!("binary modules cannot be checked");
+    }
+    let text_document = {
+        let text = std::fs::read_to_string(path)?;
+        lsp::TextDocumentItem::new(uri.clone(), language.id().into(), 0, text)
+    };
+    let params = lsp::DidOpenTextDocumentParams { text_document };
+    let document =
+        core::Document::open(session.clone(), params)?.ok_or_else(|| anyhow::anyhow!("failed to parse the file"))?;
+    let settings = session.document_settings(&uri).await;
+    let text = document.text();
+    let token = core::CancellationToken::new();
+    let provider = wasm_lsp_server::provider::text_document::diagnostics;
+    let mut diagnostics = provider(&document.tree, &text, &settings, &token)?;
+    let analysis = core::analysis::Analysis::new(language, &document.tree, &text.content, &token)?;
+    let provider = wasm_lsp_server::provider::text_document::semantic_diagnostics;
+    diagnostics.extend(provider(&text, &analysis, &settings, &token)?);
+    diagnostics.sort_by_key(|it| (it.range.start.line, it.range.start.character));
+    Ok(diagnostics)
+}
+
+/// The name of a diagnostic severity, as reported in the output.
+fn severity(diagnostic: &lsp::Diagnostic) -> &'static str {
+    match diagnostic.severity {
+        Some(lsp::DiagnosticSeverity::WARNING) => "warning",
+        Some(lsp::DiagnosticSeverity::INFORMATION) => "info",
+        Some(lsp::DiagnosticSeverity::HINT) => "hint",
+        _ => "error",
+    }
+}
+
+/// The rule code of a diagnostic (e.g., `unknown-index`).
+fn code(diagnostic: &lsp::Diagnostic) -> Option<String> {
+    match &diagnostic.code {
+        Some(lsp::NumberOrString::String(code)) => Some(code.clone()),
+        Some(lsp::NumberOrString::Number(code)) => Some(code.to_string()),
+        None => None,
+    }
+}
+
+/// Write the diagnostics of `reports` as one line per diagnostic (with 1-based positions),
+/// followed by a summary.
+pub fn human(out: &mut impl Write, reports: &[Report]) -> anyhow::Result<()> {
+    for report in reports {
+        if let Some(error) = &report.error {
+            writeln!(out, "{}: error: {}", report.path.display(), error)?;
+        }
+        for diagnostic in &report.diagnostics {
+            let start = diagnostic.range.start;
+            let code = code(diagnostic).map(|it| format!("[{}]", it)).unwrap_or_default();
+            writeln!(
+                out,
+                "{}:{}:{}: {}{}: {}",
+                report.path.display(),
+                start.line + 1,
+                start.character + 1,
+                severity(diagnostic),
+                code,
+                diagnostic.message
+            )?;
+        }
+    }
+    let errors = reports
+        .iter()
+        .map(|it| it.count(lsp::DiagnosticSeverity::ERROR) + usize::from(it.error.is_some()))
+        .sum::<usize>();
+    let warnings = reports
+        .iter()
+        .map(|it| it.count(lsp::DiagnosticSeverity::WARNING))
+        .sum::<usize>();
+    writeln!(
+        out,
+        "checked {} file(s): {} error(s), {} warning(s)",
+        reports.len(),
+        errors,
+        warnings
+    )?;
+    Ok(())
+}
+
+/// Convert the diagnostics of `reports` into a JSON array.
+pub fn json(reports: &[Report]) -> Value {
+    let reports = reports
+        .iter()
+        .map(|report| {
+            json!({
+                "path": path(&report.path),
+                "diagnostics": report.diagnostics,
+                "error": report.error,
+            })
+        })
+        .collect::<Vec<_>>();
+    Value::Array(reports)
+}
+
+/// Convert the diagnostics of `reports` into a SARIF (2.1.0) log.
+pub fn sarif(reports: &[Report]) -> Value {
+    use wasm_lsp_server::metadata;
+    let mut rules = BTreeSet::new();
+    let mut results = vec![];
+    let mut notifications = vec![];
+    for report in reports {
+        if let Some(error) = &report.error {
+            notifications.push(json!({
+                "level": "error",
+                "message": { "text": error },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": path(&report.path) },
+                    },
+                }],
+            }));
+        }
+        for diagnostic in &report.diagnostics {
+            let rule = code(diagnostic).unwrap_or_else(|| String::from("syntax"));
+            let level = match diagnostic.severity {
+                Some(lsp::DiagnosticSeverity::WARNING) => "warning",
+                Some(lsp::DiagnosticSeverity::INFORMATION | lsp::DiagnosticSeverity::HINT) => "note",
+                _ => "error",
+            };
+            let lsp::Range { start, end } = diagnostic.range;
+            results.push(json!({
+                "ruleId": rule,
+                "level": level,
+                "message": { "text": diagnostic.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": path(&report.path) },
+                        "region": {
+                            "startLine": start.line + 1,
+                            "startColumn": start.character + 1,
+                            "endLine": end.line + 1,
+                            "endColumn": end.character + 1,
+                        },
+                    },
+                }],
+            }));
+            rules.insert(rule);
+        }
+    }
+    let rules = rules.into_iter().map(|id| json!({ "id": id })).collect::<Vec<_>>();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": metadata::PKG_NAME,
+                    "version": metadata::PKG_VERSION,
+                    "informationUri": "https://github.com/wasm-lsp/wasm-lsp-server",
+                    "rules": rules,
+                },
+            },
+            "invocations": [{
+                "executionSuccessful": notifications.is_empty(),
+                "toolExecutionNotifications": notifications,
+            }],
+            "results": results,
+        }],
+    })
+}
+
+/// Format `path` with forward slashes, as expected for the (relative) URIs of SARIF artifacts.
+fn path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Report;
+    use std::path::PathBuf;
+    use tower_lsp::lsp_types as lsp;
+
+    fn report() -> Report {
+        let path = PathBuf::from("src/test.wat");
+        let range = lsp::Range::new(lsp::Position::new(1, 10), lsp::Position::new(1, 12));
+        let diagnostic = lsp::Diagnostic {
+            range,
+            severity: Some(lsp::DiagnosticSeverity::ERROR),
+            code: Some(lsp::NumberOrString::String("unknown-index".into())),
+            message: String::from("unknown function `$f`"),
+            ..Default::default()
+        };
+        let diagnostics = vec![diagnostic];
+        let error = None;
+        Report {
+            path,
+            diagnostics,
+            error,
+        }
+    }
+
+    fn failed() -> Report {
+        let path = PathBuf::from("src/missing.wat");
+        let diagnostics = vec![];
+        let error = Some(String::from(
+            "failed to check src/missing.wat: No such file or directory",
+        ));
+        Report {
+            path,
+            diagnostics,
+            error,
+        }
+    }
+
+    #[test]
+    fn check() {
+        let path = std::env::temp_dir().join("wasm-lsp-check.wat");
+        std::fs::write(&path, "(module\n  (func (call $f)))").unwrap();
+        let reports = futures::executor::block_on(super::check(&[path.clone()])).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(1, reports.len());
+        assert_eq!(1, reports[0].count(lsp::DiagnosticSeverity::ERROR));
+        let expected = Some(lsp::NumberOrString::String("unknown-index".into()));
+        assert_eq!(expected, reports[0].diagnostics[0].code);
+    }
+
+    #[test]
+    fn check_failed() {
+        let missing = std::env::temp_dir().join("wasm-lsp-check-missing.wat");
+        let path = std::env::temp_dir().join("wasm-lsp-check-valid.wat");
+        std::fs::write(&path, "(module)").unwrap();
+        let reports = futures::executor::block_on(super::check(&[missing, path.clone()])).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(2, reports.len());
+        assert!(reports[0].error.is_some());
+        assert!(!reports[0].passed());
+        assert!(reports[1].passed());
+    }
+
+    #[test]
+    fn human() {
+        let mut out = vec![];
+        super::human(&mut out, &[report()]).unwrap();
+        let expected = [
+            "src/test.wat:2:11: error[unknown-index]: unknown function `$f`",
+            "checked 1 file(s): 1 error(s), 0 warning(s)",
+            "",
+        ];
+        assert_eq!(expected.join("\n"), String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn human_failed() {
+        let mut out = vec![];
+        super::human(&mut out, &[failed()]).unwrap();
+        let expected = [
+            "src/missing.wat: error: failed to check src/missing.wat: No such file or directory",
+            "checked 1 file(s): 1 error(s), 0 warning(s)",
+            "",
+        ];
+        assert_eq!(expected.join("\n"), String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn sarif() {
+        let sarif = super::sarif(&[report()]);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!("unknown-index", result["ruleId"]);
+        assert_eq!("error", result["level"]);
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!("src/test.wat", location["artifactLocation"]["uri"]);
+        assert_eq!(2, location["region"]["startLine"]);
+        assert_eq!(11, location["region"]["startColumn"]);
+        assert_eq!("unknown-index", sarif["runs"][0]["tool"]["driver"]["rules"][0]["id"]);
+        assert_eq!(true, sarif["runs"][0]["invocations"][0]["executionSuccessful"]);
+    }
+
+    #[test]
+    fn sarif_failed() {
+        let sarif = super::sarif(&[failed(), report()]);
+        let invocation = &sarif["runs"][0]["invocations"][0];
+        assert_eq!(false, invocation["executionSuccessful"]);
+        let notification = &invocation["toolExecutionNotifications"][0];
+        assert_eq!("error", notification["level"]);
+        let location = &notification["locations"][0]["physicalLocation"];
+        assert_eq!("src/missing.wat", location["artifactLocation"]["uri"]);
+        assert_eq!(1, sarif["runs"][0]["results"].as_array().unwrap().len());
+    }
+}
@@ -0,0 +1,51 @@
+//! Functionality for collecting the files named on the command line.
+
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types as lsp;
+
+/// Collect the files named by `args`, each of which is a file, a directory (which is searched for
+/// `.wast`, `.wat` and `.wit` files), or a glob pattern.
+///
+/// Fails if an argument names neither an existing file or directory nor matches any file.
+pub fn collect<S: AsRef<str>>(args: &[S]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for arg in args {
+        let arg = arg.as_ref();
+        let path = Path::new(arg);
+        if path.is_dir() {
+            let folder = lsp::Url::from_directory_path(path.canonicalize()?)
+                .map_err(|()| anyhow::anyhow!("directory is not a local path :: {}", arg))?;
+            let uris = wasm_lsp_server::core::workspace::index(&[folder]);
+            files.extend(uris.iter().filter_map(|uri| uri.to_file_path().ok()));
+        } else if path.is_file() {
+            files.push(path.to_path_buf());
+        } else {
+            let matched = glob::glob(arg)?
+                .filter_map(Result::ok)
+                .filter(|path| path.is_file() && is_source(path))
+                .collect::<Vec<_>>();
+            if matched.is_empty() {
+                anyhow::bail!("no files found :: {}", arg);
+            }
+            files.extend(matched);
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Determine whether the file at `path` is a textual WebAssembly source file.
+pub fn is_source(path: &Path) -> bool {
+    use wasm_lsp_server::core::Language;
+    matches!(
+        Language::try_from(path),
+        Ok(Language::Wast | Language::Wat | Language::Wit)
+    )
+}
+
+/// Convert `path` into the URL of the file it refers to.
+pub fn uri(path: &Path) -> anyhow::Result<lsp::Url> {
+    let path = path.canonicalize()?;
+    lsp::Url::from_file_path(&path).map_err(|()| anyhow::anyhow!("file is not a local path :: {:?}", path))
+}
@@ -0,0 +1,123 @@
+//! The `format` subcommand, which formats `.wat` and `.wast` files from the command line.
+
+use clap::{Arg, ArgMatches, Command};
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+use tower_lsp::lsp_types as lsp;
+use wasm_lsp_server::core;
+
+/// Build the [`Command`] for the `format` subcommand.
+pub fn command() -> Command<'static> {
+    Command::new("format")
+        .about("Format .wat and .wast files in place")
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Print the changes as a diff instead of applying them, failing if there are any"),
+        )
+        .arg(
+            Arg::new("stdin")
+                .long("stdin")
+                .help("Format stdin to stdout (a given path is only used to find the project configuration)"),
+        )
+        .arg(
+            Arg::new("paths")
+                .value_name("PATHS")
+                .multiple_values(true)
+                .required_unless_present("stdin")
+                .help("The files, directories or glob patterns to format"),
+        )
+}
+
+/// Run the `format` subcommand. Returns whether the files were already formatted, in check mode.
+pub fn run(matches: &ArgMatches) -> anyhow::Result<bool> {
+    let check = matches.is_present("check");
+    let paths = matches.values_of("paths").into_iter().flatten().collect::<Vec<_>>();
+    futures::executor::block_on(async {
+        let languages = crate::languages().await?;
+        let session = core::Session::new(languages, None, None)?;
+        if matches.is_present("stdin") {
+            let path = Path::new(paths.first().copied().unwrap_or("stdin.wat"));
+            return stdin(&session, path, check).await;
+        }
+        let mut formatted = true;
+        for path in crate::files::collect(&paths)? {
+            if !is_formattable(&path) {
+                log::warn!("skipping file which cannot be formatted :: path: {}", path.display());
+                continue;
+            }
+            let settings = settings(&session, &path).await?;
+            let text = std::fs::read_to_string(&path)?;
+            let result = core::format::format(&text, &settings);
+            if result == text {
+                continue;
+            }
+            if check {
+                print!("{}", diff(&path, &text, &result));
+                formatted = false;
+            } else {
+                std::fs::write(&path, result)?;
+                eprintln!("formatted {}", path.display());
+            }
+        }
+        Ok(formatted)
+    })
+}
+
+/// Format stdin to stdout (or, in check mode, print the changes as a diff), with the settings of
+/// the project configuration governing `path`.
+async fn stdin(session: &core::Session, path: &Path, check: bool) -> anyhow::Result<bool> {
+    let settings = settings(session, path).await?;
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    let result = core::format::format(&text, &settings);
+    if check {
+        if result != text {
+            print!("{}", diff(Path::new("<stdin>"), &text, &result));
+        }
+        return Ok(result == text);
+    }
+    std::io::stdout().write_all(result.as_bytes())?;
+    Ok(true)
+}
+
+/// Determine whether the file at `path` is a `.wat` or `.wast` file.
+fn is_formattable(path: &Path) -> bool {
+    matches!(
+        core::Language::try_from(path),
+        Ok(core::Language::Wast | core::Language::Wat)
+    )
+}
+
+/// Compute the format settings for the file at `path` (which need not exist), honoring the
+/// project configuration governing it.
+async fn settings(session: &core::Session, path: &Path) -> anyhow::Result<core::FormatSettings> {
+    let path = std::env::current_dir()?.join(path);
+    let uri =
+        lsp::Url::from_file_path(&path).map_err(|()| anyhow::anyhow!("file is not a local path :: {:?}", path))?;
+    Ok(session.document_settings(&uri).await.format)
+}
+
+/// Compute the unified diff between the `text` of the file at `path` and its formatted `result`.
+fn diff(path: &Path, text: &str, result: &str) -> String {
+    let path = path.display().to_string();
+    similar::TextDiff::from_lines(text, result)
+        .unified_diff()
+        .header(&path, &path)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    #[test]
+    fn diff() {
+        let text = "(module\n(func))\n";
+        let result = "(module\n  (func))\n";
+        let expected = "--- test.wat\n+++ test.wat\n@@ -1,2 +1,2 @@\n (module\n-(func))\n+  (func))\n";
+        assert_eq!(expected, super::diff(Path::new("test.wat"), text, result));
+    }
+}
@@ -0,0 +1,422 @@
+//! The `index` subcommand, which exports an LSIF index of a directory for code navigation outside
+//! of the editor.
+
+use clap::{Arg, ArgMatches, Command};
+use lsp_text::RopeExt;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tower_lsp::lsp_types as lsp;
+use wasm_lsp_server::core::{self, analysis::Analysis};
+
+/// The version of the LSIF specification the index conforms to.
+pub const LSIF_VERSION: &str = "0.4.3";
+
+/// The scheme of the monikers of imported and exported entities.
+///
+/// Monikers are identified by the module name and the entity name (e.g., `env:log`), where the
+/// module name of an exporting file is the name it is mapped to in the project configuration
+/// (`[modules]` of `.wasm-lsp.toml`), or otherwise its path relative to the indexed directory.
+pub const MONIKER_SCHEME: &str = "wasm";
+
+/// Build the [`Command`] for the `index` subcommand.
+pub fn command() -> Command<'static> {
+    Command::new("index")
+        .about("Export an LSIF index of the .wat and .wast files in a directory")
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .value_name("FILE")
+                .takes_value(true)
+                .allow_invalid_utf8(true)
+                .default_value("dump.lsif")
+                .help("The file to write the index to (`-` for stdout)"),
+        )
+        .arg(
+            Arg::new("root")
+                .value_name("DIR")
+                .allow_invalid_utf8(true)
+                .default_value(".")
+                .help("The directory to index"),
+        )
+}
+
+/// Run the `index` subcommand.
+pub fn run(matches: &ArgMatches) -> anyhow::Result<bool> {
+    let root = PathBuf::from(matches.value_of_os("root").unwrap_or_default());
+    let output = matches.value_of_os("output").unwrap_or_default();
+    let elements = futures::executor::block_on(index(&root))?;
+    let mut dump = String::new();
+    for element in &elements {
+        dump.push_str(&element.to_string());
+        dump.push('\n');
+    }
+    if "-" == output {
+        std::io::stdout().write_all(dump.as_bytes())?;
+    } else {
+        std::fs::write(output, dump)?;
+        eprintln!(
+            "wrote {} LSIF elements to {}",
+            elements.len(),
+            Path::new(output).display()
+        );
+    }
+    Ok(true)
+}
+
+/// A file of the indexed directory.
+struct File {
+    uri: lsp::Url,
+    content: ropey::Rope,
+    analysis: Analysis,
+    /// The id of the `document` vertex.
+    document: usize,
+}
+
+/// The `resultSet` of a definition, with the ranges which define or refer to it.
+#[derive(Default)]
+struct Symbol {
+    set: usize,
+    definitions: Vec<(usize, usize)>,
+    references: Vec<(usize, usize)>,
+}
+
+/// Emitter assigning ids to the vertices and edges of an LSIF index.
+#[derive(Default)]
+struct Emitter {
+    elements: Vec<Value>,
+}
+
+impl Emitter {
+    fn element(&mut self, kind: &str, label: &str, properties: Value) -> usize {
+        let id = self.elements.len() + 1;
+        let mut element = json!({ "id": id, "type": kind, "label": label });
+        if let (Value::Object(element), Value::Object(properties)) = (&mut element, properties) {
+            element.extend(properties);
+        }
+        self.elements.push(element);
+        id
+    }
+
+    fn vertex(&mut self, label: &str, properties: Value) -> usize {
+        self.element("vertex", label, properties)
+    }
+
+    fn range(&mut self, range: lsp::Range) -> usize {
+        self.vertex("range", json!({ "start": range.start, "end": range.end }))
+    }
+
+    fn edge(&mut self, label: &str, out_v: usize, in_v: usize) -> usize {
+        self.element("edge", label, json!({ "outV": out_v, "inV": in_v }))
+    }
+
+    fn edges(&mut self, label: &str, out_v: usize, in_vs: Vec<usize>, mut properties: Value) -> usize {
+        properties["outV"] = json!(out_v);
+        properties["inVs"] = json!(in_vs);
+        self.element("edge", label, properties)
+    }
+
+    /// Emit the `item` edges from a result to ranges, one edge per document.
+    fn items(&mut self, result: usize, ranges: &[(usize, usize)], property: Option<&str>) {
+        let mut documents = BTreeMap::<usize, Vec<usize>>::new();
+        for &(document, range) in ranges {
+            documents.entry(document).or_default().push(range);
+        }
+        for (document, ranges) in documents {
+            let mut properties = json!({ "document": document });
+            if let Some(property) = property {
+                properties["property"] = json!(property);
+            }
+            self.edges("item", result, ranges, properties);
+        }
+    }
+}
+
+/// Index the `.wat` and `.wast` files in the directory at `root`, returning the vertices and
+/// edges of the LSIF index.
+///
+/// The definitions and references of each file are resolved by the semantic analysis of the
+/// server, and the imports of a file are resolved to the exports of other files through the
+/// module mappings of the project configuration (like go-to-definition does). Imports and exports
+/// additionally carry monikers (see [`MONIKER_SCHEME`]), so that they can be linked across
+/// separately indexed directories.
+pub async fn index(root: &Path) -> anyhow::Result<Vec<Value>> {
+    let root = root.canonicalize()?;
+    let root_uri = lsp::Url::from_directory_path(&root)
+        .map_err(|()| anyhow::anyhow!("directory is not a local path :: {:?}", root))?;
+    let session = Arc::new(core::Session::new(crate::languages().await?, None, None)?);
+    let token = core::CancellationToken::new();
+
+    let mut emitter = Emitter::default();
+    emitter.vertex(
+        "metaData",
+        json!({
+            "version": LSIF_VERSION,
+            "projectRoot": root_uri,
+            "positionEncoding": "utf-16",
+            "toolInfo": {
+                "name": wasm_lsp_server::metadata::PKG_NAME,
+                "version": wasm_lsp_server::metadata::PKG_VERSION,
+            },
+        }),
+    );
+    let project = emitter.vertex("project", json!({ "kind": "wasm" }));
+
+    // parse and analyze the files
+    let mut files = vec![];
+    for uri in core::workspace::index(&[root_uri.clone()]) {
+        // a single unreadable (or unparsable) file must not fail the whole export
+        let (language, content, analysis) = match analyze(&session, &uri, &token) {
+            Ok(Some(analyzed)) => analyzed,
+            Ok(None) => continue,
+            Err(error) => {
+                log::warn!(
+                    "skipping file which cannot be indexed :: uri: {}, error: {:#}",
+                    uri,
+                    error
+                );
+                continue;
+            },
+        };
+        let document = emitter.vertex("document", json!({ "uri": uri, "languageId": language.id() }));
+        files.push(File {
+            uri,
+            content,
+            analysis,
+            document,
+        });
+    }
+
+    // emit the definitions, references and exports of each file
+    let mut symbols = HashMap::<(usize, usize), Symbol>::new();
+    let mut exports = HashMap::<(lsp::Url, String), (usize, (usize, usize))>::new();
+    let mut contains = BTreeMap::<usize, Vec<usize>>::new();
+    for (index, file) in files.iter().enumerate() {
+        let ranges = contains.entry(file.document).or_default();
+        for (id, definition) in file.analysis.definitions.iter().enumerate() {
+            let range = file.content.tree_sitter_range_to_lsp_range(
+                definition
+                    .identifier
+                    .clone()
+                    .unwrap_or_else(|| definition.range.clone()),
+            );
+            let range = emitter.range(range);
+            let set = emitter.vertex("resultSet", json!({}));
+            emitter.edge("next", range, set);
+            let hover = json!({
+                "result": {
+                    "contents": { "kind": "markdown", "value": describe(&file.analysis, id) },
+                },
+            });
+            let hover = emitter.vertex("hoverResult", hover);
+            emitter.edge("textDocument/hover", set, hover);
+            ranges.push(range);
+            let definitions = vec![(file.document, range)];
+            symbols.insert((index, id), Symbol {
+                set,
+                definitions,
+                ..Default::default()
+            });
+        }
+        for reference in &file.analysis.references {
+            if let Some(id) = reference.definition {
+                let range = emitter.range(file.content.tree_sitter_range_to_lsp_range(reference.range.clone()));
+                let symbol = symbols.entry((index, id)).or_default();
+                emitter.edge("next", range, symbol.set);
+                symbol.references.push((file.document, range));
+                ranges.push(range);
+            }
+        }
+        let module = module_name(&session, &root, &file.uri).await;
+        for export in &file.analysis.exports {
+            let range = emitter.range(file.content.tree_sitter_range_to_lsp_range(export.name_range.clone()));
+            let set = emitter.vertex("resultSet", json!({}));
+            emitter.edge("next", range, set);
+            let identifier = format!("{}:{}", module, export.name);
+            let moniker = moniker(&mut emitter, identifier, "export");
+            emitter.edge("moniker", set, moniker);
+            let symbol = symbols.entry((index, export.definition)).or_default();
+            emitter.edge("next", set, symbol.set);
+            symbol.references.push((file.document, range));
+            ranges.push(range);
+            let key = (file.uri.clone(), export.name.clone());
+            exports.insert(key, (set, (index, export.definition)));
+        }
+    }
+
+    // emit the imports of each file, resolved to the exports of the files they are mapped to
+    for (index, file) in files.iter().enumerate() {
+        let project = session.project(&file.uri).await;
+        for import in &file.analysis.imports {
+            let range = emitter.range(file.content.tree_sitter_range_to_lsp_range(import.name_range.clone()));
+            let set = emitter.vertex("resultSet", json!({}));
+            emitter.edge("next", range, set);
+            let identifier = format!("{}:{}", import.module, import.name);
+            let moniker = moniker(&mut emitter, identifier, "import");
+            emitter.edge("moniker", set, moniker);
+            let export = project
+                .as_ref()
+                .and_then(|project| project.modules.get(&import.module))
+                .and_then(|target| exports.get(&(target.clone(), import.name.clone())));
+            let key = match export {
+                Some(&(export, key)) => {
+                    emitter.edge("next", set, export);
+                    key
+                },
+                None => {
+                    let key = (index, import.definition);
+                    emitter.edge("next", set, symbols.entry(key).or_default().set);
+                    key
+                },
+            };
+            symbols.entry(key).or_default().references.push((file.document, range));
+            contains.entry(file.document).or_default().push(range);
+        }
+    }
+
+    // emit the results of the definitions
+    let mut keys = symbols.keys().copied().collect::<Vec<_>>();
+    keys.sort_unstable();
+    for key in keys {
+        let symbol = &symbols[&key];
+        let result = emitter.vertex("definitionResult", json!({}));
+        emitter.edge("textDocument/definition", symbol.set, result);
+        emitter.items(result, &symbol.definitions, None);
+        let result = emitter.vertex("referenceResult", json!({}));
+        emitter.edge("textDocument/references", symbol.set, result);
+        emitter.items(result, &symbol.definitions, Some("definitions"));
+        emitter.items(result, &symbol.references, Some("references"));
+    }
+
+    let documents = files.iter().map(|file| file.document).collect::<Vec<_>>();
+    for (document, ranges) in contains {
+        if !ranges.is_empty() {
+            emitter.edges("contains", document, ranges, json!({}));
+        }
+    }
+    if !documents.is_empty() {
+        emitter.edges("contains", project, documents, json!({}));
+    }
+
+    Ok(emitter.elements)
+}
+
+/// Read, parse and analyze the file at `uri`. Returns `None` for files which are not indexed
+/// (`.wit` documents).
+fn analyze(
+    session: &Arc<core::Session>,
+    uri: &lsp::Url,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<(core::Language, ropey::Rope, Analysis)>> {
+    let language = core::workspace::language(uri)?;
+    if let core::Language::Wit = language {
+        return Ok(None);
+    }
+    let text_document = {
+        let text = core::workspace::read(uri)?;
+        lsp::TextDocumentItem::new(uri.clone(), language.id().into(), 0, text)
+    };
+    let params = lsp::DidOpenTextDocumentParams { text_document };
+    match core::Document::open(session.clone(), params)? {
+        Some(document) => {
+            let analysis = Analysis::new(language, &document.tree, &document.content, token)?;
+            Ok(Some((language, document.content, analysis)))
+        },
+        None => Ok(None),
+    }
+}
+
+/// Emit a moniker for an imported or exported entity.
+fn moniker(emitter: &mut Emitter, identifier: String, kind: &str) -> usize {
+    let moniker = json!({
+        "scheme": MONIKER_SCHEME,
+        "identifier": identifier,
+        "unique": "scheme",
+        "kind": kind,
+    });
+    emitter.vertex("moniker", moniker)
+}
+
+/// Compute the module name of the file at `uri` for the monikers of its exports: the name it is
+/// mapped to in the project configuration, or otherwise its path relative to `root`.
+async fn module_name(session: &core::Session, root: &Path, uri: &lsp::Url) -> String {
+    if let Some(project) = session.project(uri).await {
+        let mut names = project
+            .modules
+            .iter()
+            .filter(|(_, target)| *target == uri)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        names.sort();
+        if let Some(name) = names.first() {
+            return String::from(*name);
+        }
+    }
+    let path = uri.to_file_path().unwrap_or_default();
+    let path = path.strip_prefix(root).unwrap_or(&path);
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Describe the definition `id` of `analysis` for its hover.
+fn describe(analysis: &Analysis, id: usize) -> String {
+    let definition = &analysis.definitions[id];
+    let name = definition.name.clone().unwrap_or_else(|| definition.index.to_string());
+    let mut value = format!("{} `{}` (index {})", definition.space, name, definition.index);
+    if let Some(import) = analysis.imports.iter().find(|it| it.definition == id) {
+        value.push_str(&format!("\n\nImported as `{}` `{}`", import.module, import.name));
+    }
+    for export in analysis.exports.iter().filter(|it| it.definition == id) {
+        value.push_str(&format!("\n\nExported as `{}`", export.name));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    fn find<'a>(elements: &'a [Value], label: &str, predicate: impl Fn(&Value) -> bool) -> Option<&'a Value> {
+        elements.iter().find(|it| it["label"] == label && predicate(it))
+    }
+
+    #[test]
+    fn index() {
+        let root = std::env::temp_dir().join(format!("wasm-lsp-index-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join(".wasm-lsp.toml"), "[modules]\nenv = \"env.wat\"\n").unwrap();
+        std::fs::write(root.join("env.wat"), "(module\n  (func $log (export \"log\")))\n").unwrap();
+        let main = "(module\n  (import \"env\" \"log\" (func $log))\n  (func (call $log)))\n";
+        std::fs::write(root.join("main.wat"), main).unwrap();
+        // files which cannot be read are skipped
+        std::fs::write(root.join("broken.wat"), b"(module \xff)").unwrap();
+        let elements = futures::executor::block_on(super::index(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+        let elements = elements.unwrap();
+
+        assert_eq!("metaData", elements[0]["label"]);
+        let documents = elements.iter().filter(|it| it["label"] == "document").count();
+        assert_eq!(2, documents);
+
+        // the export and the import share a moniker
+        let export = find(&elements, "moniker", |it| it["kind"] == "export").unwrap();
+        let import = find(&elements, "moniker", |it| it["kind"] == "import").unwrap();
+        assert_eq!("env:log", export["identifier"]);
+        assert_eq!("env:log", import["identifier"]);
+
+        // the import resolves to the export in the other file
+        let set = |moniker: &Value| {
+            find(&elements, "moniker", |it| {
+                it["type"] == "edge" && it["inV"] == moniker["id"]
+            })
+        };
+        let export = set(export).unwrap()["outV"].clone();
+        let import = set(import).unwrap()["outV"].clone();
+        let next = find(&elements, "next", |it| it["outV"] == import).unwrap();
+        assert_eq!(export, next["inV"]);
+    }
+}
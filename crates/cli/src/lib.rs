@@ -0,0 +1,52 @@
+//! Functionality for the command-line interface of the WebAssembly Language Server.
+
+#![deny(clippy::all)]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+/// The `check` subcommand, which reports the diagnostics of files from the command line.
+pub mod check;
+
+/// Functionality for collecting the files named on the command line.
+pub mod files;
+
+/// The `format` subcommand, which formats `.wat` and `.wast` files from the command line.
+pub mod format;
+
+/// The `index` subcommand, which exports an LSIF index of a directory for code navigation.
+pub mod index;
+
+/// Functionality for watching the process of the client.
+pub mod process;
+
+/// Transports over which the server communicates with the client.
+pub mod transport;
+
+/// Load the tree-sitter languages for a new session.
+pub async fn languages() -> anyhow::Result<wasm_lsp_server::core::SessionLanguages> {
+    #[rustfmt::skip]
+    #[cfg(target_arch = "wasm32")]
+    let languages = wasm_lsp_server::core::SessionLanguages {
+        component: wasm_lsp_languages::language::component().await?,
+        wast     : wasm_lsp_languages::language::wast     ().await?,
+        wat      : wasm_lsp_languages::language::wat      ().await?,
+        wit      : wasm_lsp_languages::language::wit      ().await?,
+    };
+    #[rustfmt::skip]
+    #[cfg(not(target_arch = "wasm32"))]
+    let languages = wasm_lsp_server::core::SessionLanguages {
+        component: wasm_lsp_languages::language::component(),
+        wast     : wasm_lsp_languages::language::wast     (),
+        wat      : wasm_lsp_languages::language::wat      (),
+        wit      : wasm_lsp_languages::language::wit      (),
+    };
+    Ok(languages)
+}
+
+/// Create a [`wasm_lsp_server::core::Spawner`] which runs background tasks on a thread pool.
+pub fn spawner() -> anyhow::Result<wasm_lsp_server::core::Spawner> {
+    let pool = futures::executor::ThreadPool::new()?;
+    Ok(std::sync::Arc::new(
+        move |task: futures::future::BoxFuture<'static, ()>| pool.spawn_ok(task),
+    ))
+}
@@ -0,0 +1,95 @@
+//! Functionality for watching the process of the client.
+
+use std::time::Duration;
+
+/// The interval between two checks of whether the process of the client is still alive.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Exit the server once the process of the client with the given `pid` has exited, so that the
+/// server does not outlive the editor which started it.
+pub fn watch(pid: u32) {
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::spawn(move || loop {
+        if !is_alive(pid) {
+            log::info!("client process exited; shutting down :: pid: {}", pid);
+            std::process::exit(0);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+    #[cfg(target_arch = "wasm32")]
+    log::warn!(
+        "watching the client process is not supported on this platform :: pid: {}",
+        pid
+    );
+}
+
+/// Determine whether the process with the given `pid` is still alive.
+///
+/// When this cannot be determined, the process is assumed to be alive.
+#[cfg(target_os = "linux")]
+pub fn is_alive(pid: u32) -> bool {
+    std::path::Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Determine whether the process with the given `pid` is still alive.
+///
+/// When this cannot be determined, the process is assumed to be alive.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn is_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_or(true, |status| status.success())
+}
+
+/// Determine whether the process with the given `pid` is still alive.
+///
+/// When this cannot be determined, the process is assumed to be alive.
+#[cfg(windows)]
+pub fn is_alive(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .map_or(true, |output| is_listed(&String::from_utf8_lossy(&output.stdout), pid))
+}
+
+/// Determine whether the CSV output of `tasklist` lists a process with the given `pid`.
+///
+/// The PID (second column) must match exactly, since a substring match would also accept the
+/// PIDs of other processes (or the numbers in other columns) which contain it.
+#[cfg(any(windows, test))]
+fn is_listed(output: &str, pid: u32) -> bool {
+    let pid = pid.to_string();
+    output
+        .lines()
+        .filter_map(|line| line.split(',').nth(1))
+        .any(|column| column.trim().trim_matches('"') == pid)
+}
+
+/// Determine whether the process with the given `pid` is still alive.
+///
+/// When this cannot be determined, the process is assumed to be alive.
+#[cfg(not(any(unix, windows)))]
+pub fn is_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn is_alive() {
+        assert!(super::is_alive(std::process::id()));
+    }
+
+    #[test]
+    fn is_listed() {
+        let output = "\"code.exe\",\"12345\",\"Console\",\"1\",\"1,234 K\"\r\n";
+        assert!(super::is_listed(output, 12345));
+        assert!(!super::is_listed(output, 1234));
+        assert!(!super::is_listed(output, 1));
+        let output = "INFO: No tasks are running which match the specified criteria.\r\n";
+        assert!(!super::is_listed(output, 12345));
+    }
+}
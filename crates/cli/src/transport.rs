@@ -0,0 +1,163 @@
+//! Transports over which the server communicates with the client.
+
+use clap::{Arg, ArgGroup, ArgMatches, Command};
+use futures::io::{AsyncRead, AsyncWrite};
+use std::path::PathBuf;
+
+/// A transport over which the server communicates with the client.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Transport {
+    /// Communicate over standard input and output (the default).
+    Stdio,
+    /// Listen on a TCP socket at the given address and serve the clients which connect to it, one
+    /// at a time.
+    Listen(String),
+    /// Connect to a client listening on a TCP socket at the given address.
+    Connect(String),
+    /// Connect to a client listening on a Unix domain socket at the given path.
+    Pipe(PathBuf),
+}
+
+impl Transport {
+    /// Add the arguments for selecting a [`Transport`] to `command`.
+    pub fn args<'a>(command: Command<'a>) -> Command<'a> {
+        command
+            .arg(
+                Arg::new("stdio")
+                    .long("stdio")
+                    .help("Communicate over stdin and stdout (default)"),
+            )
+            .arg(
+                Arg::new("listen")
+                    .long("listen")
+                    .value_name("ADDR")
+                    .takes_value(true)
+                    .help("Listen for a client on a TCP socket (e.g., 0.0.0.0:9257)"),
+            )
+            .arg(
+                Arg::new("connect")
+                    .long("connect")
+                    .value_name("ADDR")
+                    .takes_value(true)
+                    .help("Connect to a client listening on a TCP socket (e.g., 127.0.0.1:9257)"),
+            )
+            .arg(
+                Arg::new("pipe")
+                    .long("pipe")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .allow_invalid_utf8(true)
+                    .help("Connect to a client listening on a Unix domain socket"),
+            )
+            .group(ArgGroup::new("transport").args(&["stdio", "listen", "connect", "pipe"]))
+    }
+
+    /// Determine the [`Transport`] selected by the arguments in `matches`.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        if let Some(addr) = matches.value_of("listen") {
+            Transport::Listen(addr.into())
+        } else if let Some(addr) = matches.value_of("connect") {
+            Transport::Connect(addr.into())
+        } else if let Some(path) = matches.value_of_os("pipe") {
+            Transport::Pipe(path.into())
+        } else {
+            Transport::Stdio
+        }
+    }
+
+    /// Serve the language server over the [`Transport`].
+    pub async fn serve(&self, spawner: wasm_lsp_server::core::Spawner) -> anyhow::Result<()> {
+        match self {
+            Transport::Stdio => {
+                let stdin = blocking::Unblock::new(std::io::stdin());
+                let stdout = blocking::Unblock::new(std::io::stdout());
+                serve(stdin, stdout, spawner).await
+            },
+            #[cfg(not(target_arch = "wasm32"))]
+            Transport::Listen(addr) => {
+                let listener = async_net::TcpListener::bind(addr.as_str()).await?;
+                log::info!("listening for clients :: addr: {}", listener.local_addr()?);
+                loop {
+                    // a failed connection must not stop the server from accepting further clients
+                    let (stream, peer) = match listener.accept().await {
+                        Ok(accepted) => accepted,
+                        Err(error) => {
+                            log::warn!("failed to accept client :: error: {}", error);
+                            continue;
+                        },
+                    };
+                    log::info!("client connected :: peer: {}", peer);
+                    serve(stream.clone(), stream, spawner.clone()).await?;
+                    log::info!("client disconnected :: peer: {}", peer);
+                }
+            },
+            #[cfg(not(target_arch = "wasm32"))]
+            Transport::Connect(addr) => {
+                let stream = async_net::TcpStream::connect(addr.as_str()).await?;
+                serve(stream.clone(), stream, spawner).await
+            },
+            #[cfg(unix)]
+            Transport::Pipe(path) => {
+                let stream = async_net::unix::UnixStream::connect(path).await?;
+                serve(stream.clone(), stream, spawner).await
+            },
+            #[allow(unreachable_patterns)]
+            transport => anyhow::bail!("transport is not supported on this platform :: {:?}", transport),
+        }
+    }
+}
+
+/// Serve a new session of the language server over the `input` and `output` streams until the
+/// client exits (or disconnects).
+async fn serve<I, O>(input: I, output: O, spawner: wasm_lsp_server::core::Spawner) -> anyhow::Result<()>
+where
+    I: AsyncRead + Unpin,
+    O: AsyncWrite,
+{
+    let languages = crate::languages().await?;
+    let (service, socket) = wasm_lsp_server::Server::service(languages, Some(spawner))?;
+    tower_lsp::Server::new(input, output, socket).serve(service).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transport;
+    use clap::Command;
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> clap::Result<Transport> {
+        let command = Transport::args(Command::new("wasm-lsp"));
+        let matches = command.try_get_matches_from(std::iter::once("wasm-lsp").chain(args.iter().copied()))?;
+        Ok(Transport::from_matches(&matches))
+    }
+
+    #[test]
+    fn stdio() {
+        assert_eq!(Transport::Stdio, parse(&[]).unwrap());
+        assert_eq!(Transport::Stdio, parse(&["--stdio"]).unwrap());
+    }
+
+    #[test]
+    fn listen() {
+        let transport = parse(&["--listen", "0.0.0.0:9257"]).unwrap();
+        assert_eq!(Transport::Listen(String::from("0.0.0.0:9257")), transport);
+    }
+
+    #[test]
+    fn connect() {
+        let transport = parse(&["--connect", "127.0.0.1:9257"]).unwrap();
+        assert_eq!(Transport::Connect(String::from("127.0.0.1:9257")), transport);
+    }
+
+    #[test]
+    fn pipe() {
+        let transport = parse(&["--pipe", "/tmp/wasm-lsp.sock"]).unwrap();
+        assert_eq!(Transport::Pipe(PathBuf::from("/tmp/wasm-lsp.sock")), transport);
+    }
+
+    #[test]
+    fn conflicting() {
+        assert!(parse(&["--stdio", "--listen", "0.0.0.0:9257"]).is_err());
+    }
+}
@@ -1,6 +1,85 @@
-use std::path::Path;
+use anyhow::Context;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The tree-sitter CLI used for generating the grammars maintained in this crate, preferring the
+/// one installed (through `npm ci`) for the `vendor/tree-sitter-wasm` grammars.
+fn tree_sitter_cli() -> PathBuf {
+    let path = Path::new("../../vendor/tree-sitter-wasm/node_modules/.bin/tree-sitter");
+    if path.exists() {
+        return path.canonicalize().unwrap_or_else(|_| path.into());
+    }
+    PathBuf::from("tree-sitter")
+}
+
+/// Run the tree-sitter CLI with `args` in the directory of the grammar `dir`.
+fn tree_sitter(dir: &Path, args: &[&str]) -> anyhow::Result<()> {
+    let cli = tree_sitter_cli();
+    let status = Command::new(&cli)
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .with_context(|| format!("failed to run the tree-sitter CLI :: {}", cli.display()))?;
+    if !status.success() {
+        anyhow::bail!("`tree-sitter {}` failed :: grammar: {}", args.join(" "), dir.display());
+    }
+    Ok(())
+}
+
+/// Determine whether the file at `output` is missing or older than the `grammar.js` in `dir`.
+fn is_stale(dir: &Path, output: &Path) -> bool {
+    let modified = |path: &Path| path.metadata().and_then(|metadata| metadata.modified()).ok();
+    match (modified(&dir.join("grammar.js")), modified(output)) {
+        (Some(grammar), Some(output)) => output < grammar,
+        _ => !output.exists(),
+    }
+}
+
+/// Generate the parser (`src/parser.c`) of the grammar maintained in this crate under `dir`, when
+/// it is missing or outdated.
+///
+/// The generated sources are not committed, so a fresh checkout needs the tree-sitter CLI (see
+/// `cargo xtask build --rebuild-parsers`).
+fn generate_parser(dir: &Path) -> anyhow::Result<()> {
+    println!("cargo:rerun-if-changed={}", dir.join("grammar.js").display());
+    if is_stale(dir, &dir.join("src/parser.c")) {
+        tree_sitter(dir, &["generate", "--no-bindings"])
+            .context("generating the parser requires the tree-sitter CLI (`npm ci` in vendor/tree-sitter-wasm)")?;
+    }
+    Ok(())
+}
+
+/// Build the `.wasm` module (loaded by the wasm32 build) of the grammar maintained in this crate
+/// under `dir`, when it is missing or outdated.
+fn generate_wasm(dir: &Path, name: &str) -> anyhow::Result<()> {
+    println!("cargo:rerun-if-changed={}", dir.join("grammar.js").display());
+    if is_stale(dir, &dir.join(format!("tree-sitter-{}.wasm", name))) {
+        generate_parser(dir)?;
+        tree_sitter(dir, &["build-wasm"])
+            .context("building the grammar module requires the tree-sitter CLI and emscripten (or docker)")?;
+    }
+    Ok(())
+}
+
+fn compile_tree_sitter_grammars() -> anyhow::Result<()> {
+    let dir = Path::new("grammars");
+
+    generate_parser(&dir.join("component"))?;
+    println!("cargo:rerun-if-changed={:?}", dir.join("component/src/parser.c"));
+    let mut cc = cc::Build::new();
+    cc.include(dir.join("component/src"));
+    cc.file(dir.join("component/src/parser.c"));
+    cc.compile("tree-sitter-component");
+
+    generate_parser(&dir.join("wit"))?;
+    println!("cargo:rerun-if-changed={:?}", dir.join("wit/src/parser.c"));
+    let mut cc = cc::Build::new();
+    cc.include(dir.join("wit/src"));
+    cc.file(dir.join("wit/src/parser.c"));
+    cc.compile("tree-sitter-wit");
 
-fn compile_tree_sitter_grammars() {
     let dir = Path::new("../../vendor/tree-sitter-wasm");
 
     println!("cargo:rerun-if-changed={:?}", dir.join("wast/src/parser.c"));
@@ -14,11 +93,22 @@ fn compile_tree_sitter_grammars() {
     cc.include(dir.join("wat/src"));
     cc.file(dir.join("wat/src/parser.c"));
     cc.compile("tree-sitter-wat");
+
+    Ok(())
+}
+
+fn build_tree_sitter_modules() -> anyhow::Result<()> {
+    let dir = Path::new("grammars");
+    generate_wasm(&dir.join("component"), "component")?;
+    generate_wasm(&dir.join("wit"), "wit")?;
+    Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
     if std::env::var("CARGO_CFG_TARGET_ARCH")? != "wasm32" {
-        compile_tree_sitter_grammars();
+        compile_tree_sitter_grammars()?;
+    } else {
+        build_tree_sitter_modules()?;
     }
     Ok(())
 }
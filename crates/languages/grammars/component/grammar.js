@@ -0,0 +1,284 @@
+// Tree-sitter grammar for the WebAssembly component model text format.
+//
+// The grammar extends the `.wat` grammar, so that core modules nested within components (and the
+// identifiers, indices, strings and comments shared by both formats) are parsed with the same
+// rules and node kinds as in `.wat` documents.
+//
+// Regenerate the parser (`src/parser.c`) with `cargo xtask build --rebuild-parsers`.
+
+const wat = require("../../../../vendor/tree-sitter-wasm/wat/grammar");
+
+const PRIMITIVE_VAL_TYPES = [
+  "bool",
+  "s8",
+  "u8",
+  "s16",
+  "u16",
+  "s32",
+  "u32",
+  "s64",
+  "u64",
+  "f32",
+  "f64",
+  "char",
+  "string",
+  "error-context",
+];
+
+const CORE_SORTS = ["func", "table", "memory", "global", "type", "module", "instance"];
+
+const SORTS = ["func", "value", "type", "component", "instance"];
+
+module.exports = grammar(wat, {
+  name: "component",
+
+  conflicts: ($, original) =>
+    original.concat([
+      [$.sort_index, $.extern_desc],
+      [$.type_definition, $.component_type_use],
+      [$.val_type, $.component_type_use],
+    ]),
+
+  rules: {
+    ROOT: $ => repeat($._definition),
+
+    component: $ => seq("(", "component", optional(field("identifier", $.identifier)), repeat($._definition), ")"),
+
+    _definition: $ =>
+      choice(
+        $.alias,
+        $.canon,
+        $.component,
+        $.component_export,
+        $.component_func,
+        $.component_import,
+        $.component_start,
+        $.core_func,
+        $.core_instance,
+        $.core_module,
+        $.core_type,
+        $.instance,
+        $.type_definition,
+      ),
+
+    // core definitions
+
+    core_module: $ =>
+      seq("(", "core", "module", optional(field("identifier", $.identifier)), repeat($.module_field), ")"),
+
+    core_instance: $ =>
+      seq(
+        "(",
+        "core",
+        "instance",
+        optional(field("identifier", $.identifier)),
+        choice($.core_instantiate, repeat($.core_inline_export)),
+        ")",
+      ),
+
+    core_instantiate: $ => seq("(", "instantiate", $.index, repeat($.core_instantiate_arg), ")"),
+
+    core_instantiate_arg: $ =>
+      seq(
+        "(",
+        "with",
+        $.string,
+        choice($.core_sort_index, seq("(", "instance", repeat($.core_inline_export), ")")),
+        ")",
+      ),
+
+    core_inline_export: $ => seq("(", "export", $.string, $.core_sort_index, ")"),
+
+    core_sort: $ => choice(...CORE_SORTS),
+
+    core_sort_index: $ => seq("(", optional("core"), $.core_sort, $.index, optional($.string), ")"),
+
+    core_type: $ =>
+      seq("(", "core", "type", optional(field("identifier", $.identifier)), choice($.func_type, $.core_module_type), ")"),
+
+    core_module_type: $ =>
+      seq("(", "module", repeat(choice($.module_field_import, $.module_field_type, $.core_export_decl)), ")"),
+
+    core_export_decl: $ => seq("(", "export", $.string, $.import_desc, ")"),
+
+    core_func: $ =>
+      seq("(", "core", "func", optional(field("identifier", $.identifier)), choice($.canon, $.alias_target_inline), ")"),
+
+    // component definitions
+
+    instance: $ =>
+      seq(
+        "(",
+        "instance",
+        optional(field("identifier", $.identifier)),
+        choice($.instantiate, repeat($.inline_export)),
+        ")",
+      ),
+
+    instantiate: $ => seq("(", "instantiate", $.index, repeat($.instantiate_arg), ")"),
+
+    instantiate_arg: $ =>
+      seq("(", "with", $.string, choice($.sort_index, seq("(", "instance", repeat($.inline_export), ")")), ")"),
+
+    inline_export: $ => seq("(", "export", $.export_name, $.sort_index, ")"),
+
+    sort: $ => choice(seq("core", $.core_sort), ...SORTS),
+
+    sort_index: $ => seq("(", $.sort, $.index, optional($.string), ")"),
+
+    alias: $ => seq("(", "alias", $.alias_target, "(", $.sort, optional(field("identifier", $.identifier)), ")", ")"),
+
+    alias_target: $ =>
+      choice(seq("export", $.index, $.string), seq("core", "export", $.index, $.string), seq("outer", $.index, $.index)),
+
+    alias_target_inline: $ => seq("(", "alias", $.alias_target, ")"),
+
+    canon: $ =>
+      seq(
+        "(",
+        "canon",
+        choice(
+          seq("lift", $.core_sort_index, repeat($.canon_opt), optional($.extern_desc)),
+          seq("lower", $.sort_index, repeat($.canon_opt), optional($.core_sort_index)),
+          seq(choice("resource.new", "resource.drop", "resource.rep"), $.index, optional($.core_sort_index)),
+        ),
+        ")",
+      ),
+
+    canon_opt: $ =>
+      choice(
+        "string-encoding=utf8",
+        "string-encoding=utf16",
+        "string-encoding=latin1+utf16",
+        "async",
+        seq("(", choice("memory", "realloc", "post-return", "callback"), choice($.index, $.core_sort_index), ")"),
+      ),
+
+    component_func: $ =>
+      seq(
+        "(",
+        "func",
+        optional(field("identifier", $.identifier)),
+        repeat($.export),
+        choice($.canon, $.alias_target_inline, seq(optional($.component_type_use), $.import)),
+        ")",
+      ),
+
+    component_start: $ =>
+      seq(
+        "(",
+        "start",
+        $.index,
+        repeat(seq("(", "value", $.index, ")")),
+        repeat(seq("(", "result", "(", "value", optional(field("identifier", $.identifier)), ")", ")")),
+        ")",
+      ),
+
+    component_import: $ => seq("(", "import", $.import_name, $.extern_desc, ")"),
+
+    component_export: $ =>
+      seq(
+        "(",
+        "export",
+        optional(field("identifier", $.identifier)),
+        $.export_name,
+        $.sort_index,
+        optional($.extern_desc),
+        ")",
+      ),
+
+    import_name: $ => choice($.string, $.interface_name),
+
+    export_name: $ => choice($.string, $.interface_name),
+
+    interface_name: $ => seq("(", "interface", $.string, ")"),
+
+    extern_desc: $ =>
+      seq(
+        "(",
+        choice(seq("core", "module"), ...SORTS),
+        optional(field("identifier", $.identifier)),
+        repeat(
+          choice(
+            $.component_type_use,
+            $.component_param,
+            $.component_result,
+            $.component_import,
+            $.core_export_decl,
+            $.export_decl,
+            $.module_field_import,
+            $.type_bound,
+            $.type_definition,
+            $.val_type,
+          ),
+        ),
+        ")",
+      ),
+
+    type_bound: $ => seq("(", choice(seq("eq", $.index), seq("sub", "resource")), ")"),
+
+    // component types
+
+    type_definition: $ =>
+      seq(
+        "(",
+        "type",
+        optional(field("identifier", $.identifier)),
+        choice($.val_type, $.component_func_type, $.component_type, $.instance_type, $.resource_type),
+        ")",
+      ),
+
+    component_type_use: $ => seq("(", "type", $.index, ")"),
+
+    val_type: $ =>
+      choice(
+        $.primitive_val_type,
+        $.index,
+        seq(
+          "(",
+          choice(
+            seq("record", repeat($.record_field)),
+            seq("variant", repeat($.variant_case)),
+            seq("list", $.val_type),
+            seq("tuple", repeat($.val_type)),
+            seq("flags", repeat($.string)),
+            seq("enum", repeat($.string)),
+            seq("option", $.val_type),
+            seq("result", optional($.val_type), optional(seq("(", "error", $.val_type, ")"))),
+            seq("own", $.index),
+            seq("borrow", $.index),
+            seq("stream", optional($.val_type)),
+            seq("future", optional($.val_type)),
+          ),
+          ")",
+        ),
+      ),
+
+    primitive_val_type: $ => choice(...PRIMITIVE_VAL_TYPES),
+
+    record_field: $ => seq("(", "field", $.string, $.val_type, ")"),
+
+    variant_case: $ => seq("(", "case", $.string, optional($.val_type), ")"),
+
+    component_func_type: $ => seq("(", "func", repeat($.component_param), optional($.component_result), ")"),
+
+    component_param: $ => seq("(", "param", $.string, $.val_type, ")"),
+
+    component_result: $ => seq("(", "result", $.val_type, ")"),
+
+    component_type: $ =>
+      seq(
+        "(",
+        "component",
+        repeat(choice($.alias, $.component_import, $.core_type, $.export_decl, $.type_definition)),
+        ")",
+      ),
+
+    instance_type: $ => seq("(", "instance", repeat(choice($.alias, $.core_type, $.export_decl, $.type_definition)), ")"),
+
+    export_decl: $ => seq("(", "export", $.export_name, $.extern_desc, ")"),
+
+    resource_type: $ =>
+      seq("(", "resource", "(", "rep", "i32", ")", optional(seq("(", "dtor", $.core_sort_index, ")")), ")"),
+  },
+});
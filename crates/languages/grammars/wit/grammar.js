@@ -0,0 +1,184 @@
+// Tree-sitter grammar for the WebAssembly Interface Type (WIT) format.
+//
+// Regenerate the parser (`src/parser.c`) with `cargo xtask build --rebuild-parsers`.
+
+const PRIMITIVE_TYPES = [
+  "bool",
+  "s8",
+  "u8",
+  "s16",
+  "u16",
+  "s32",
+  "u32",
+  "s64",
+  "u64",
+  "f32",
+  "f64",
+  "char",
+  "string",
+  "error-context",
+];
+
+const commaSep = rule => optional(commaSep1(rule));
+
+const commaSep1 = rule => seq(rule, repeat(seq(",", rule)), optional(","));
+
+module.exports = grammar({
+  name: "wit",
+
+  extras: $ => [/\s/, $.comment_block, $.comment_line],
+
+  word: $ => $.identifier,
+
+  rules: {
+    ROOT: $ => seq(optional($.package_decl), repeat($._item)),
+
+    _item: $ => choice($.interface_item, $.toplevel_use, $.world_item),
+
+    // packages and paths
+
+    package_decl: $ => seq("package", field("name", $.package_name), ";"),
+
+    package_name: $ =>
+      seq(
+        field("namespace", $.identifier),
+        ":",
+        field("package", $.identifier),
+        optional(seq("@", field("version", $.version))),
+      ),
+
+    use_path: $ =>
+      choice(
+        field("interface", $.identifier),
+        seq(
+          field("namespace", $.identifier),
+          ":",
+          field("package", $.identifier),
+          "/",
+          field("interface", $.identifier),
+          optional(seq("@", field("version", $.version))),
+        ),
+      ),
+
+    toplevel_use: $ =>
+      seq("use", $.use_path, optional(seq("as", field("alias", $.identifier))), ";"),
+
+    use_item: $ => seq("use", $.use_path, ".", "{", commaSep1($.use_name), "}", ";"),
+
+    use_name: $ => seq(field("name", $.identifier), optional(seq("as", field("alias", $.identifier)))),
+
+    // interfaces and worlds
+
+    interface_item: $ =>
+      seq("interface", field("identifier", $.identifier), "{", repeat($._interface_item), "}"),
+
+    _interface_item: $ => choice($.func_item, $.use_item, $._type_item),
+
+    world_item: $ => seq("world", field("identifier", $.identifier), "{", repeat($._world_item), "}"),
+
+    _world_item: $ => choice($.export_item, $.import_item, $.include_item, $.use_item, $._type_item),
+
+    export_item: $ => seq("export", $._extern_type),
+
+    import_item: $ => seq("import", $._extern_type),
+
+    _extern_type: $ =>
+      choice(
+        seq(field("identifier", $.identifier), ":", $.func_type, ";"),
+        seq(field("identifier", $.identifier), ":", "interface", "{", repeat($._interface_item), "}"),
+        seq($.use_path, ";"),
+      ),
+
+    include_item: $ => seq("include", $.use_path, ";"),
+
+    // functions
+
+    func_item: $ => seq(field("identifier", $.identifier), ":", $.func_type, ";"),
+
+    func_type: $ => seq(optional("async"), "func", $.param_list, optional($.result_list)),
+
+    param_list: $ => seq("(", commaSep($.param), ")"),
+
+    param: $ => seq(field("identifier", $.identifier), ":", $._type),
+
+    result_list: $ => seq("->", $._type),
+
+    // type definitions
+
+    _type_item: $ =>
+      choice($.enum_item, $.flags_item, $.record_item, $.resource_item, $.type_alias_item, $.variant_item),
+
+    enum_item: $ => seq("enum", field("identifier", $.identifier), "{", commaSep1($.enum_case), "}"),
+
+    enum_case: $ => field("identifier", $.identifier),
+
+    flags_item: $ => seq("flags", field("identifier", $.identifier), "{", commaSep($.flags_field), "}"),
+
+    flags_field: $ => field("identifier", $.identifier),
+
+    record_item: $ => seq("record", field("identifier", $.identifier), "{", commaSep($.record_field), "}"),
+
+    record_field: $ => seq(field("identifier", $.identifier), ":", $._type),
+
+    resource_item: $ =>
+      seq("resource", field("identifier", $.identifier), choice(";", seq("{", repeat($.resource_method), "}"))),
+
+    resource_method: $ =>
+      choice(
+        seq(field("identifier", $.identifier), ":", optional("static"), $.func_type, ";"),
+        seq(field("identifier", "constructor"), $.param_list, optional($.result_list), ";"),
+      ),
+
+    type_alias_item: $ => seq("type", field("identifier", $.identifier), "=", $._type, ";"),
+
+    variant_item: $ => seq("variant", field("identifier", $.identifier), "{", commaSep1($.variant_case), "}"),
+
+    variant_case: $ => seq(field("identifier", $.identifier), optional(seq("(", $._type, ")"))),
+
+    // types
+
+    _type: $ =>
+      choice(
+        $.handle_type,
+        $.list_type,
+        $.option_type,
+        $.primitive_type,
+        $.result_type,
+        $.tuple_type,
+        $.future_type,
+        $.stream_type,
+        $.type_ref,
+      ),
+
+    primitive_type: $ => choice(...PRIMITIVE_TYPES),
+
+    handle_type: $ => seq(choice("own", "borrow"), "<", $.type_ref, ">"),
+
+    list_type: $ => seq("list", "<", $._type, optional(seq(",", $.integer)), ">"),
+
+    option_type: $ => seq("option", "<", $._type, ">"),
+
+    result_type: $ =>
+      prec.right(seq("result", optional(seq("<", choice($._type, "_"), optional(seq(",", $._type)), ">")))),
+
+    tuple_type: $ => seq("tuple", "<", commaSep1($._type), ">"),
+
+    future_type: $ => prec.right(seq("future", optional(seq("<", $._type, ">")))),
+
+    stream_type: $ => prec.right(seq("stream", optional(seq("<", $._type, ">")))),
+
+    type_ref: $ => field("identifier", $.identifier),
+
+    // lexical
+
+    comment_block: $ => token(seq("/*", /[^*]*\*+([^/*][^*]*\*+)*/, "/")),
+
+    comment_line: $ => token(seq("//", /.*/)),
+
+    identifier: $ => /%?[a-zA-Z][a-zA-Z0-9]*(-[a-zA-Z0-9]+)*/,
+
+    integer: $ => /[0-9]+/,
+
+    version: $ => /[0-9]+\.[0-9]+\.[0-9]+(-[0-9A-Za-z.-]+)?(\+[0-9A-Za-z.-]+)?/,
+  },
+});
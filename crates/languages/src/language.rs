@@ -1,5 +1,30 @@
 use std::path::Path;
 
+/// Tree-sitter language for the component model grammar.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn component() -> tree_sitter::Language {
+    #[allow(unsafe_code)]
+    let inner = unsafe { crate::tree_sitter_component() };
+    inner.into()
+}
+
+/// Tree-sitter language for the component model grammar.
+#[cfg(target_arch = "wasm32")]
+pub async fn component() -> anyhow::Result<tree_sitter::Language> {
+    use anyhow::anyhow;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+    let bytes: &[u8] = include_bytes!("../grammars/component/tree-sitter-component.wasm");
+    let promise = web_tree_sitter_sys::Language::load_bytes(&bytes.into());
+    let future = JsFuture::from(promise);
+    let value = future
+        .await
+        .map_err(|_| anyhow!("failed to load tree-sitter-component.wasm"))?;
+    let inner = value.unchecked_into::<web_tree_sitter_sys::Language>();
+    let result = inner.into();
+    Ok(result)
+}
+
 /// Tree-sitter language for the `.wast` grammar.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn wast() -> tree_sitter::Language {
@@ -50,21 +75,55 @@ pub async fn wat() -> anyhow::Result<tree_sitter::Language> {
     Ok(result)
 }
 
+/// Tree-sitter language for the `.wit` grammar.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn wit() -> tree_sitter::Language {
+    #[allow(unsafe_code)]
+    let inner = unsafe { crate::tree_sitter_wit() };
+    inner.into()
+}
+
+/// Tree-sitter language for the `.wit` grammar.
+#[cfg(target_arch = "wasm32")]
+pub async fn wit() -> anyhow::Result<tree_sitter::Language> {
+    use anyhow::anyhow;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+    let bytes: &[u8] = include_bytes!("../grammars/wit/tree-sitter-wit.wasm");
+    let promise = web_tree_sitter_sys::Language::load_bytes(&bytes.into());
+    let future = JsFuture::from(promise);
+    let value = future
+        .await
+        .map_err(|_| anyhow!("failed to load tree-sitter-wit.wasm"))?;
+    let inner = value.unchecked_into::<web_tree_sitter_sys::Language>();
+    let result = inner.into();
+    Ok(result)
+}
+
 /// Languages supported by the server.
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Language {
+    /// The component model text format.
+    Component,
+    /// The binary `.wasm` language (presented as disassembled `.wat`).
+    Wasm,
     /// The `.wast` language.
     Wast,
     /// The `.wat` language.
     Wat,
+    /// The `.wit` language.
+    Wit,
 }
 
 impl Language {
     /// Compute the language id string for the given language.
     pub fn id(&self) -> &str {
         match self {
+            Language::Component => "wasm.component",
+            Language::Wasm => "wasm.wasm",
             Language::Wast => "wasm.wast",
             Language::Wat => "wasm.wat",
+            Language::Wit => "wasm.wit",
         }
     }
 }
@@ -75,8 +134,11 @@ impl TryFrom<&str> for Language {
     fn try_from(language_id: &str) -> anyhow::Result<Self> {
         use crate::error::Error;
         match language_id {
+            "wasm.component" => Ok(Language::Component),
+            "wasm.wasm" => Ok(Language::Wasm),
             "wasm.wast" => Ok(Language::Wast),
             "wasm.wat" => Ok(Language::Wat),
+            "wasm.wit" => Ok(Language::Wit),
             _ => Err(Error::InvalidLanguageId(language_id.into()).into()),
         }
     }
@@ -6,6 +6,10 @@
 
 #[cfg(not(target_arch = "wasm32"))]
 extern {
+    #[allow(dead_code)]
+    #[doc(hidden)]
+    fn tree_sitter_component() -> tree_sitter_sys::Language;
+
     #[allow(dead_code)]
     #[doc(hidden)]
     fn tree_sitter_wast() -> tree_sitter_sys::Language;
@@ -13,9 +17,14 @@ extern {
     #[allow(dead_code)]
     #[doc(hidden)]
     fn tree_sitter_wat() -> tree_sitter_sys::Language;
+
+    #[allow(dead_code)]
+    #[doc(hidden)]
+    fn tree_sitter_wit() -> tree_sitter_sys::Language;
 }
 
-mod error;
+/// Functionality related to runtime errors.
+pub mod error;
 
 /// Functions for creating [`tree-sitter::Language`].
 pub mod language;
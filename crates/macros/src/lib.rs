@@ -216,8 +216,10 @@ pub fn field_ids(input: TokenStream) -> TokenStream {
 
     #[allow(unsafe_code)]
     let language = match macro_input.language.0 {
+        language::Language::Component => language::component(),
         language::Language::Wast => language::wast(),
-        language::Language::Wat => language::wat(),
+        language::Language::Wat | language::Language::Wasm => language::wat(),
+        language::Language::Wit => language::wit(),
     };
 
     let mut content = vec![];
@@ -304,8 +306,10 @@ pub fn node_kind_ids(input: TokenStream) -> TokenStream {
 
     #[allow(unsafe_code)]
     let language = match macro_input.language.0 {
+        language::Language::Component => language::component(),
         language::Language::Wast => language::wast(),
-        language::Language::Wat => language::wat(),
+        language::Language::Wat | language::Language::Wasm => language::wat(),
+        language::Language::Wit => language::wit(),
     };
 
     let mut content = vec![];
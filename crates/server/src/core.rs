@@ -1,32 +1,54 @@
 //! Core definitions for server functionality.
 
+/// Definitions related to the semantic analysis of documents.
+pub mod analysis;
+
+/// Definitions related to the cooperative cancellation of long-running work.
+pub mod cancellation;
+
+/// Definitions related to disassembled binary `.wasm` modules.
+pub mod disassembly;
+
 /// Definitions related to LSP documents.
 pub mod document;
 
+/// Definitions related to encoding `.wat` documents as binary `.wasm` modules.
+pub mod encoding;
+
 /// Definitions related to runtime errors.
 pub mod error;
 
-/// Definitions related to session hashmaps.
-pub mod map;
+/// Definitions related to formatting `.wat` and `.wast` documents.
+pub mod format;
 
-/// Definitions related to lock structures for session hashmaps.
-pub mod lock;
+/// Definitions related to the project configuration files (`.wasm-lsp.toml`).
+pub mod project;
 
-/// Definitions related to references for session hashmaps.
-pub mod reference;
+/// Definitions related to scheduling the computation of diagnostics.
+pub mod scheduler;
 
 /// Definitions related to the LSP session.
 pub mod session;
 
+/// Definitions related to the configurable settings of the server.
+pub mod settings;
+
+/// Definitions related to immutable snapshots of documents.
+pub mod snapshot;
+
 /// Definitions related to working with textual content.
 pub mod text;
 
+/// Definitions related to the files of the workspace.
+pub mod workspace;
+
+pub use cancellation::*;
 pub use document::*;
 pub use error::*;
-pub use lock::*;
-pub use map::*;
-pub use reference::*;
+pub use scheduler::*;
 pub use session::*;
+pub use settings::*;
+pub use snapshot::*;
 pub use text::*;
 pub use wasm_lsp_languages::language::Language;
 pub use wasm_lsp_syntax::{language, node, range};
@@ -0,0 +1,1343 @@
+//! Definitions related to the semantic analysis of documents.
+
+use crate::core;
+use lsp_text::RopeExt;
+
+/// Node kinds for semantic analysis of `.wast` documents.
+pub mod wast;
+
+/// Node kinds for semantic analysis of `.wat` documents.
+pub mod wat;
+
+/// The index spaces of a WebAssembly module.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IndexSpace {
+    /// The data segment index space.
+    Data,
+    /// The element segment index space.
+    Elem,
+    /// The field index space of a struct type.
+    Field,
+    /// The function index space.
+    Func,
+    /// The global index space.
+    Global,
+    /// The local (and parameter) index space of a function.
+    Local,
+    /// The memory index space.
+    Memory,
+    /// The table index space.
+    Table,
+    /// The (exception) tag index space.
+    Tag,
+    /// The type index space.
+    Type,
+}
+
+impl IndexSpace {
+    /// Compute the index space referenced by the index immediates of an instruction.
+    pub fn for_instr(op: &str) -> Option<Self> {
+        match op {
+            "call" | "return_call" | "ref.func" => Some(IndexSpace::Func),
+            "call_ref" | "return_call_ref" => Some(IndexSpace::Type),
+            "global.get" | "global.set" => Some(IndexSpace::Global),
+            "local.get" | "local.set" | "local.tee" => Some(IndexSpace::Local),
+            "call_indirect" | "return_call_indirect" => Some(IndexSpace::Table),
+            "data.drop" | "memory.init" => Some(IndexSpace::Data),
+            "elem.drop" => Some(IndexSpace::Elem),
+            "throw" => Some(IndexSpace::Tag),
+            _ if op.starts_with("struct.") || op.starts_with("array.") => Some(IndexSpace::Type),
+            _ if op.starts_with("table.") => Some(IndexSpace::Table),
+            _ if op.starts_with("memory.") => Some(IndexSpace::Memory),
+            _ if op.contains(".load") || op.contains(".store") || op.contains(".atomic.") => Some(IndexSpace::Memory),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for IndexSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            IndexSpace::Data => "data",
+            IndexSpace::Elem => "elem",
+            IndexSpace::Field => "field",
+            IndexSpace::Func => "func",
+            IndexSpace::Global => "global",
+            IndexSpace::Local => "local",
+            IndexSpace::Memory => "memory",
+            IndexSpace::Table => "table",
+            IndexSpace::Tag => "tag",
+            IndexSpace::Type => "type",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The address type of a memory or table (`i64` for memory64).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum AddressType {
+    /// 32-bit addresses (the default).
+    #[default]
+    I32,
+    /// 64-bit addresses.
+    I64,
+}
+
+impl std::fmt::Display for AddressType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AddressType::I32 => "i32",
+            AddressType::I64 => "i64",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The WebAssembly proposals (beyond the MVP) which a document can make use of.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Proposal {
+    /// The exception handling proposal.
+    Exceptions,
+    /// The garbage collection proposal.
+    Gc,
+    /// The 64-bit memory proposal.
+    Memory64,
+    /// The multiple memories proposal.
+    MultiMemory,
+    /// The fixed-width SIMD proposal.
+    Simd,
+    /// The tail call proposal.
+    TailCall,
+    /// The threads (shared memory and atomics) proposal.
+    Threads,
+}
+
+impl Proposal {
+    /// Compute the proposal which introduces an instruction, if any.
+    pub fn for_instr(op: &str) -> Option<Self> {
+        const SIMD_PREFIXES: &[&str] = &["v128.", "i8x16.", "i16x8.", "i32x4.", "i64x2.", "f32x4.", "f64x2."];
+        const GC_PREFIXES: &[&str] = &[
+            "struct.",
+            "array.",
+            "i31.",
+            "ref.i31",
+            "ref.test",
+            "ref.cast",
+            "br_on_cast",
+        ];
+        match op {
+            "throw" | "throw_ref" => Some(Proposal::Exceptions),
+            "any.convert_extern" | "extern.convert_any" => Some(Proposal::Gc),
+            "return_call" | "return_call_indirect" | "return_call_ref" => Some(Proposal::TailCall),
+            "atomic.fence" => Some(Proposal::Threads),
+            _ if op.contains(".atomic.") => Some(Proposal::Threads),
+            _ if SIMD_PREFIXES.iter().any(|prefix| op.starts_with(prefix)) => Some(Proposal::Simd),
+            _ if GC_PREFIXES.iter().any(|prefix| op.starts_with(prefix)) => Some(Proposal::Gc),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Proposal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Proposal::Exceptions => "exception handling",
+            Proposal::Gc => "garbage collection",
+            Proposal::Memory64 => "memory64",
+            Proposal::MultiMemory => "multi-memory",
+            Proposal::Simd => "SIMD",
+            Proposal::TailCall => "tail call",
+            Proposal::Threads => "threads",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Node kinds of a particular grammar which are relevant for semantic analysis.
+pub(crate) struct Kinds {
+    pub module: u16,
+    pub module_field: u16,
+    pub module_field_data: u16,
+    pub module_field_elem: u16,
+    pub module_field_export: u16,
+    pub module_field_func: u16,
+    pub module_field_global: u16,
+    pub module_field_import: u16,
+    pub module_field_memory: u16,
+    pub module_field_rec: u16,
+    pub module_field_start: u16,
+    pub module_field_table: u16,
+    pub module_field_tag: u16,
+    pub module_field_type: u16,
+    pub identifier: u16,
+    pub index: u16,
+    pub import: u16,
+    pub name: u16,
+    pub export: u16,
+    pub export_desc_func: u16,
+    pub export_desc_global: u16,
+    pub export_desc_memory: u16,
+    pub export_desc_table: u16,
+    pub export_desc_tag: u16,
+    pub import_desc_func_type: u16,
+    pub import_desc_global_type: u16,
+    pub import_desc_memory_type: u16,
+    pub import_desc_table_type: u16,
+    pub import_desc_tag: u16,
+    pub import_desc_type_use: u16,
+    pub func_type_params_one: u16,
+    pub func_type_params_many: u16,
+    pub func_locals_one: u16,
+    pub func_locals_many: u16,
+    pub struct_field_one: u16,
+    pub struct_field_many: u16,
+    pub field_type: u16,
+    pub sub_type: u16,
+    pub heap_type: u16,
+    pub value_type: u16,
+    pub global_type_mut: u16,
+    pub memory_type: u16,
+    pub table_type: u16,
+    pub address_type: u16,
+    pub share: u16,
+    pub align_value: u16,
+    pub type_use: u16,
+    pub memory_use: u16,
+    pub table_use: u16,
+    pub elem_list: u16,
+    pub catch_clause: u16,
+    pub instr: u16,
+    pub instr_plain: u16,
+    pub expr: u16,
+    pub expr1: u16,
+    pub expr1_plain: u16,
+    pub op_table_copy: u16,
+    pub op_table_init: u16,
+    pub op_struct_field: u16,
+    pub op_array_copy: u16,
+    pub op_array_segment: u16,
+    pub token_else: u16,
+    pub token_mut: u16,
+    pub field_identifier: u16,
+    /// The `.wast` commands whose modules are expected to be invalid (or malformed), and which are
+    /// therefore not analyzed.
+    pub invalid: &'static [u16],
+}
+
+/// An entity defined in one of the index spaces of a module.
+#[derive(Clone, Debug)]
+pub struct Definition {
+    /// The index space of the entity.
+    pub space: IndexSpace,
+    /// The index of the entity in its index space.
+    pub index: u32,
+    /// The (module) scope of the entity.
+    pub module: usize,
+    /// The (function) scope of the entity, for locals and parameters.
+    pub scope: Option<usize>,
+    /// The symbolic name (`$identifier`) of the entity, if any.
+    pub name: Option<String>,
+    /// The range of the node defining the entity.
+    pub range: tree_sitter::Range,
+    /// The range of the identifier of the entity, if any.
+    pub identifier: Option<tree_sitter::Range>,
+    /// Whether the entity is imported.
+    pub imported: bool,
+    /// Whether the entity is exported.
+    pub exported: bool,
+    /// Whether the entity is mutable (for globals and struct fields).
+    pub mutable: bool,
+    /// Whether the entity is shared (for memories).
+    pub shared: bool,
+    /// The address type of the entity (for memories and tables).
+    pub address_type: Option<AddressType>,
+}
+
+/// An import of an entity from another module.
+#[derive(Clone, Debug)]
+pub struct Import {
+    /// The name of the module the entity is imported from.
+    pub module: String,
+    /// The name of the imported entity.
+    pub name: String,
+    /// The range of the module name (including quotes).
+    pub module_range: tree_sitter::Range,
+    /// The range of the entity name (including quotes).
+    pub name_range: tree_sitter::Range,
+    /// The index of the [`Definition`] of the imported entity.
+    pub definition: usize,
+}
+
+/// An export of an entity from a module.
+#[derive(Clone, Debug)]
+pub struct Export {
+    /// The name under which the entity is exported.
+    pub name: String,
+    /// The range of the name (including quotes).
+    pub name_range: tree_sitter::Range,
+    /// The index of the [`Definition`] of the exported entity.
+    pub definition: usize,
+}
+
+/// A use of an index which refers to some entity.
+#[derive(Clone, Debug)]
+pub struct Reference {
+    /// The index space the reference refers into.
+    pub space: IndexSpace,
+    /// The textual content of the index (a `$identifier` or a numeric index).
+    pub text: String,
+    /// The range of the index node.
+    pub range: tree_sitter::Range,
+    /// The (position in [`Analysis::definitions`] of the) referenced definition, if resolved.
+    pub definition: Option<usize>,
+}
+
+/// An instruction which addresses a memory or table.
+#[derive(Clone, Debug)]
+pub struct Access {
+    /// The mnemonic of the instruction.
+    pub op: String,
+    /// The range of the instruction.
+    pub range: tree_sitter::Range,
+    /// The index space of the addressed entity.
+    pub space: IndexSpace,
+    /// The (position in [`Analysis::definitions`] of the) addressed entity, if resolved.
+    pub target: Option<usize>,
+    /// The type of the address operands of the instruction.
+    pub address_type: AddressType,
+    /// The explicit alignment (in bytes) of the instruction and the range of its immediate, if any.
+    pub align: Option<(u32, tree_sitter::Range)>,
+}
+
+/// A construct which makes use of a (post-MVP) proposal.
+#[derive(Clone, Debug)]
+pub struct ProposalUse {
+    /// The proposal which introduces the construct.
+    pub proposal: Proposal,
+    /// The range of the construct.
+    pub range: tree_sitter::Range,
+}
+
+/// The result of semantic analysis for a document.
+#[derive(Clone, Debug, Default)]
+pub struct Analysis {
+    /// The entities defined in the document, in order of definition.
+    pub definitions: Vec<Definition>,
+    /// The index references made in the document.
+    pub references: Vec<Reference>,
+    /// The ranges of instructions which can never be executed.
+    pub unreachable: Vec<tree_sitter::Range>,
+    /// The instructions which address memories or tables.
+    pub accesses: Vec<Access>,
+    /// The constructs which make use of (post-MVP) proposals.
+    pub proposals: Vec<ProposalUse>,
+    /// The imports of the modules of the document.
+    pub imports: Vec<Import>,
+    /// The exports of the modules of the document.
+    pub exports: Vec<Export>,
+    lookup: Lookup,
+}
+
+/// The key of a definition in the (module, index space, and scope) it is defined in.
+type Scoped<T> = (usize, IndexSpace, Option<usize>, T);
+
+/// Lookup tables for the definitions of an [`Analysis`], by position in
+/// [`Analysis::definitions`].
+#[derive(Clone, Debug, Default)]
+struct Lookup {
+    /// The definitions by symbolic name.
+    names: std::collections::HashMap<Scoped<String>, usize>,
+    /// The definitions by numeric index.
+    indices: std::collections::HashMap<Scoped<u32>, usize>,
+    /// The definitions by the (start and end byte) range of their identifier, ordered by position.
+    identifiers: std::collections::BTreeMap<(u32, u32), usize>,
+    /// The (positions in [`Analysis::references`] of the) references to each definition.
+    references: std::collections::HashMap<usize, Vec<usize>>,
+}
+
+impl Analysis {
+    /// Analyze the syntax tree for a document.
+    ///
+    /// The traversal checks `token` periodically, failing with
+    /// [`core::Error::RequestCancelled`] once it has been cancelled.
+    pub fn new(
+        language: core::Language,
+        tree: &tree_sitter::Tree,
+        content: &ropey::Rope,
+        token: &core::CancellationToken,
+    ) -> anyhow::Result<Self> {
+        let kinds = match language {
+            // NOTE: index spaces of components are not (yet) analyzed
+            core::Language::Component => return Ok(Analysis::default()),
+            // NOTE: interface types have no index spaces
+            core::Language::Wit => return Ok(Analysis::default()),
+            core::Language::Wast => &wast::KINDS,
+            core::Language::Wat | core::Language::Wasm => &wat::KINDS,
+        };
+        let mut analyzer = Analyzer {
+            kinds,
+            content,
+            token,
+            analysis: Analysis::default(),
+        };
+        analyzer.root(tree.root_node())?;
+        let mut analysis = analyzer.analysis;
+        // references and unreachable ranges are searched by position
+        analysis.references.sort_by_key(|it| it.range.start_byte());
+        analysis.unreachable.sort_by_key(|it| it.start_byte());
+        for (position, reference) in analysis.references.iter().enumerate() {
+            if let Some(id) = reference.definition {
+                analysis.lookup.references.entry(id).or_default().push(position);
+            }
+        }
+        Ok(analysis)
+    }
+
+    /// Find the definition whose identifier is the given node.
+    pub fn definition_at(&self, node: &tree_sitter::Node) -> Option<&Definition> {
+        let key = (node.start_byte(), node.end_byte());
+        self.lookup.identifiers.get(&key).map(|id| &self.definitions[*id])
+    }
+
+    /// Find the reference made by the given index node.
+    pub fn reference_at(&self, node: &tree_sitter::Node) -> Option<&Reference> {
+        let position = self
+            .references
+            .binary_search_by_key(&node.start_byte(), |it| it.range.start_byte())
+            .ok()?;
+        let reference = &self.references[position];
+        if reference.range.end_byte() == node.end_byte() {
+            Some(reference)
+        } else {
+            None
+        }
+    }
+
+    /// Find the (position in [`Analysis::definitions`] of the) definition with the given symbolic
+    /// or numeric index in a module, scope and index space.
+    pub fn resolve(&self, module: usize, scope: Option<usize>, space: IndexSpace, text: &str) -> Option<usize> {
+        let scope = match space {
+            IndexSpace::Field | IndexSpace::Local => scope,
+            _ => None,
+        };
+        if text.starts_with('$') {
+            self.lookup
+                .names
+                .get(&(module, space, scope, text.to_string()))
+                .copied()
+        } else {
+            let index = parse_u32(text)?;
+            self.lookup.indices.get(&(module, space, scope, index)).copied()
+        }
+    }
+
+    /// Find the (position in [`Analysis::definitions`] of the) definition which is either defined
+    /// by the identifier or referenced by the index at the given byte offset.
+    ///
+    /// The end of an identifier or index is included, so that the definition is also found for a
+    /// position directly after it.
+    pub fn target_at(&self, byte: u32) -> Option<usize> {
+        let contains = |range: &tree_sitter::Range| range.start_byte() <= byte && byte <= range.end_byte();
+        // the references are sorted by position, so only the last one starting before `byte` can
+        // contain it
+        let position = self.references.partition_point(|it| it.range.start_byte() <= byte);
+        let reference = position
+            .checked_sub(1)
+            .map(|position| &self.references[position])
+            .filter(|it| contains(&it.range));
+        if let Some(reference) = reference {
+            return reference.definition;
+        }
+        // likewise, the identifiers are disjoint
+        self.lookup
+            .identifiers
+            .range(..= (byte, u32::MAX))
+            .next_back()
+            .filter(|((start, end), _)| *start <= byte && byte <= *end)
+            .map(|(_, id)| *id)
+    }
+
+    /// Find the references to the definition at the given position in [`Analysis::definitions`].
+    pub fn references_to(&self, id: usize) -> impl Iterator<Item = &Reference> {
+        self.lookup
+            .references
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|position| &self.references[*position])
+    }
+
+    /// Find the memory or table access made by the instruction at the given byte offset.
+    pub fn access_at(&self, byte: usize) -> Option<&Access> {
+        self.accesses
+            .iter()
+            .find(|access| access.range.start_byte() as usize <= byte && byte < access.range.end_byte() as usize)
+    }
+
+    /// Determine whether the given byte offset lies within unreachable code.
+    pub fn is_unreachable(&self, byte: u32) -> bool {
+        // the unreachable ranges are disjoint, so only the last one starting before `byte` can
+        // contain it
+        let position = self.unreachable.partition_point(|range| range.start_byte() <= byte);
+        position
+            .checked_sub(1)
+            .map_or(false, |position| byte < self.unreachable[position].end_byte())
+    }
+}
+
+/// State for the semantic analysis traversal.
+struct Analyzer<'text> {
+    kinds: &'static Kinds,
+    content: &'text ropey::Rope,
+    token: &'text core::CancellationToken,
+    analysis: Analysis,
+}
+
+impl<'text> Analyzer<'text> {
+    fn root(&mut self, root: tree_sitter::Node) -> anyhow::Result<()> {
+        // Module fields at the top-level form an implicit module.
+        let fields = children(&root)
+            .into_iter()
+            .filter_map(|child| self.module_field(child))
+            .collect::<Vec<_>>();
+        let mut module = 0;
+        if !fields.is_empty() {
+            self.module(module, &fields)?;
+            module += 1;
+        }
+
+        // Otherwise search for explicit modules (possibly nested in `.wast` commands).
+        let mut work = vec![root.clone()];
+        while let Some(node) = work.pop() {
+            self.token.tick()?;
+            if self.kinds.module == node.kind_id() {
+                let fields = children(&node)
+                    .into_iter()
+                    .filter_map(|child| self.module_field(child))
+                    .collect::<Vec<_>>();
+                self.module(module, &fields)?;
+                module += 1;
+            } else if self.kinds.module_field != node.kind_id() && !self.kinds.invalid.contains(&node.kind_id()) {
+                work.extend(children(&node).into_iter().rev());
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the concrete module field node for a `module_field` node.
+    fn module_field<'tree>(&self, node: tree_sitter::Node<'tree>) -> Option<tree_sitter::Node<'tree>> {
+        if self.kinds.module_field == node.kind_id() {
+            node.named_child(0)
+        } else {
+            None
+        }
+    }
+
+    fn module(&mut self, module: usize, fields: &[tree_sitter::Node]) -> anyhow::Result<()> {
+        let kinds = self.kinds;
+        let mut counts = std::collections::HashMap::<IndexSpace, u32>::new();
+
+        // The type definitions of recursive type groups are treated as module fields.
+        let fields = fields
+            .iter()
+            .flat_map(|field| {
+                if kinds.module_field_rec == field.kind_id() {
+                    children(field)
+                        .into_iter()
+                        .filter(|it| kinds.module_field_type == it.kind_id())
+                        .collect()
+                } else {
+                    vec![field.clone()]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // First pass: collect definitions.
+        let mut funcs = vec![];
+        let mut types = vec![];
+        for field in &fields {
+            self.token.tick()?;
+            let kind = field.kind_id();
+            let space = if kinds.module_field_data == kind {
+                Some(IndexSpace::Data)
+            } else if kinds.module_field_elem == kind {
+                Some(IndexSpace::Elem)
+            } else if kinds.module_field_func == kind {
+                Some(IndexSpace::Func)
+            } else if kinds.module_field_global == kind {
+                Some(IndexSpace::Global)
+            } else if kinds.module_field_memory == kind {
+                Some(IndexSpace::Memory)
+            } else if kinds.module_field_table == kind {
+                Some(IndexSpace::Table)
+            } else if kinds.module_field_tag == kind {
+                Some(IndexSpace::Tag)
+            } else if kinds.module_field_type == kind {
+                Some(IndexSpace::Type)
+            } else {
+                None
+            };
+
+            if let Some(space) = space {
+                let identifier = field.child_by_field_id(kinds.field_identifier);
+                let imported = has_child(field, kinds.import);
+                let exported = has_child(field, kinds.export);
+                let mutable = has_descendant(field, kinds.global_type_mut, 2);
+                let id = self.define(
+                    &mut counts,
+                    module,
+                    None,
+                    space,
+                    field,
+                    identifier,
+                    imported,
+                    exported,
+                    mutable,
+                );
+                self.limits(id, field);
+                if let Some(import) = first_child(field, kinds.import) {
+                    self.record_import(&import, id);
+                }
+                for export in children(field).into_iter().filter(|it| kinds.export == it.kind_id()) {
+                    self.record_export(&export, id);
+                }
+                if IndexSpace::Func == space {
+                    funcs.push((field.clone(), id));
+                } else if IndexSpace::Type == space {
+                    types.push((field.clone(), id));
+                }
+            } else if kinds.module_field_import == kind {
+                self.import(&mut counts, module, field, &mut funcs);
+            }
+        }
+
+        // Collect the parameters and locals for each function.
+        for (func, id) in &funcs {
+            let id = *id;
+            let mut counts = std::collections::HashMap::<IndexSpace, u32>::new();
+            // A type use without inline parameters (`(func (type $t) ...)`) takes the (unnamed)
+            // parameters of the referenced type.
+            let inline = has_descendant(func, kinds.func_type_params_one, 2)
+                || has_descendant(func, kinds.func_type_params_many, 2);
+            if let Some(type_use) = first_child(func, kinds.type_use).filter(|_| !inline) {
+                let params = self
+                    .indices(&type_use)
+                    .first()
+                    .and_then(|it| self.resolve(module, None, IndexSpace::Type, it))
+                    .and_then(|ty| types.iter().find(|(_, id)| ty == *id))
+                    .map_or(0, |(ty, _)| self.params(ty));
+                for _ in 0 .. params {
+                    self.define(
+                        &mut counts,
+                        module,
+                        Some(id),
+                        IndexSpace::Local,
+                        &type_use,
+                        None,
+                        false,
+                        false,
+                        true,
+                    );
+                }
+            }
+            let mut work = children(func);
+            work.reverse();
+            while let Some(node) = work.pop() {
+                self.token.tick()?;
+                let kind = node.kind_id();
+                if kinds.func_type_params_one == kind || kinds.func_locals_one == kind {
+                    let identifier = first_child(&node, kinds.identifier);
+                    self.define(
+                        &mut counts,
+                        module,
+                        Some(id),
+                        IndexSpace::Local,
+                        &node,
+                        identifier,
+                        false,
+                        false,
+                        true,
+                    );
+                } else if kinds.func_type_params_many == kind || kinds.func_locals_many == kind {
+                    for _ in children(&node)
+                        .into_iter()
+                        .filter(|it| kinds.value_type == it.kind_id())
+                    {
+                        self.define(
+                            &mut counts,
+                            module,
+                            Some(id),
+                            IndexSpace::Local,
+                            &node,
+                            None,
+                            false,
+                            false,
+                            true,
+                        );
+                    }
+                } else if kinds.instr != kind && kinds.expr != kind && kinds.index != kind {
+                    let mut nested = children(&node);
+                    nested.reverse();
+                    work.extend(nested);
+                }
+            }
+        }
+
+        // Collect the fields for each struct type.
+        for (ty, id) in &types {
+            let id = *id;
+            let mut counts = std::collections::HashMap::<IndexSpace, u32>::new();
+            let mut work = children(ty);
+            work.reverse();
+            while let Some(node) = work.pop() {
+                self.token.tick()?;
+                let kind = node.kind_id();
+                if kinds.struct_field_one == kind {
+                    let identifier = first_child(&node, kinds.identifier);
+                    let mutable = has_descendant(&node, kinds.token_mut, 2);
+                    self.define(
+                        &mut counts,
+                        module,
+                        Some(id),
+                        IndexSpace::Field,
+                        &node,
+                        identifier,
+                        false,
+                        false,
+                        mutable,
+                    );
+                } else if kinds.struct_field_many == kind {
+                    for field_type in children(&node)
+                        .into_iter()
+                        .filter(|it| kinds.field_type == it.kind_id())
+                    {
+                        let mutable = has_descendant(&field_type, kinds.token_mut, 1);
+                        self.define(
+                            &mut counts,
+                            module,
+                            Some(id),
+                            IndexSpace::Field,
+                            &node,
+                            None,
+                            false,
+                            false,
+                            mutable,
+                        );
+                    }
+                } else {
+                    let mut nested = children(&node);
+                    nested.reverse();
+                    work.extend(nested);
+                }
+            }
+        }
+
+        // Second pass: collect references.
+        for field in &fields {
+            let scope = funcs
+                .iter()
+                .find(|(func, _)| func.id() == field.id())
+                .map(|(_, id)| *id);
+            self.references(module, scope, field)?;
+        }
+
+        // Third pass: mark unreachable instructions.
+        for (func, _) in &funcs {
+            self.unreachable(func)?;
+        }
+
+        // Fourth pass: collect memory and table accesses.
+        for (func, _) in &funcs {
+            self.accesses(module, func)?;
+        }
+
+        // Fifth pass: collect the uses of proposals.
+        for definition in self.analysis.definitions.iter().filter(|it| it.module == module) {
+            let proposal = match definition.space {
+                IndexSpace::Tag => Proposal::Exceptions,
+                IndexSpace::Memory if definition.shared => Proposal::Threads,
+                IndexSpace::Memory | IndexSpace::Table if definition.address_type == Some(AddressType::I64) => {
+                    Proposal::Memory64
+                },
+                IndexSpace::Memory if definition.index > 0 => Proposal::MultiMemory,
+                _ => continue,
+            };
+            let range = definition.range.clone();
+            self.analysis.proposals.push(ProposalUse { proposal, range });
+        }
+        for (func, _) in &funcs {
+            self.proposals(func)?;
+        }
+        Ok(())
+    }
+
+    fn import<'tree>(
+        &mut self,
+        counts: &mut std::collections::HashMap<IndexSpace, u32>,
+        module: usize,
+        field: &tree_sitter::Node<'tree>,
+        funcs: &mut Vec<(tree_sitter::Node<'tree>, usize)>,
+    ) {
+        let kinds = self.kinds;
+        let mut work = children(field);
+        while let Some(node) = work.pop() {
+            let kind = node.kind_id();
+            let space = if kinds.import_desc_type_use == kind || kinds.import_desc_func_type == kind {
+                Some(IndexSpace::Func)
+            } else if kinds.import_desc_global_type == kind {
+                Some(IndexSpace::Global)
+            } else if kinds.import_desc_memory_type == kind {
+                Some(IndexSpace::Memory)
+            } else if kinds.import_desc_table_type == kind {
+                Some(IndexSpace::Table)
+            } else if kinds.import_desc_tag == kind {
+                Some(IndexSpace::Tag)
+            } else {
+                work.extend(children(&node));
+                None
+            };
+            if let Some(space) = space {
+                let identifier = first_child(&node, kinds.identifier);
+                let mutable = has_descendant(&node, kinds.global_type_mut, 2);
+                let id = self.define(counts, module, None, space, field, identifier, true, false, mutable);
+                self.limits(id, &node);
+                self.record_import(field, id);
+                if IndexSpace::Func == space {
+                    funcs.push((node, id));
+                }
+                break;
+            }
+        }
+    }
+
+    /// Record the module and entity names of an import (given by its first two `name` children).
+    fn record_import(&mut self, node: &tree_sitter::Node, definition: usize) {
+        let names = children(node)
+            .into_iter()
+            .filter(|it| self.kinds.name == it.kind_id())
+            .collect::<Vec<_>>();
+        if let [module, name, ..] = names.as_slice() {
+            let text = |node: &tree_sitter::Node| {
+                let text = self.content.utf8_text_for_tree_sitter_node(node);
+                text.trim_matches('"').to_string()
+            };
+            self.analysis.imports.push(Import {
+                module: text(module),
+                name: text(name),
+                module_range: module.range(),
+                name_range: name.range(),
+                definition,
+            });
+        }
+    }
+
+    /// Record the name of an export (given by its `name` child).
+    fn record_export(&mut self, node: &tree_sitter::Node, definition: usize) {
+        if let Some(name) = first_child(node, self.kinds.name) {
+            let text = self.content.utf8_text_for_tree_sitter_node(&name);
+            self.analysis.exports.push(Export {
+                name: text.trim_matches('"').to_string(),
+                name_range: name.range(),
+                definition,
+            });
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn define(
+        &mut self,
+        counts: &mut std::collections::HashMap<IndexSpace, u32>,
+        module: usize,
+        scope: Option<usize>,
+        space: IndexSpace,
+        node: &tree_sitter::Node,
+        identifier: Option<tree_sitter::Node>,
+        imported: bool,
+        exported: bool,
+        mutable: bool,
+    ) -> usize {
+        let count = counts.entry(space).or_default();
+        let index = *count;
+        *count += 1;
+        let name = identifier
+            .as_ref()
+            .map(|node| self.content.utf8_text_for_tree_sitter_node(node).to_string());
+        let identifier = identifier.map(|node| node.range());
+        let id = self.analysis.definitions.len();
+        let lookup = &mut self.analysis.lookup;
+        if let Some(name) = &name {
+            lookup.names.entry((module, space, scope, name.clone())).or_insert(id);
+        }
+        lookup.indices.entry((module, space, scope, index)).or_insert(id);
+        if let Some(range) = &identifier {
+            lookup
+                .identifiers
+                .entry((range.start_byte(), range.end_byte()))
+                .or_insert(id);
+        }
+        self.analysis.definitions.push(Definition {
+            space,
+            index,
+            module,
+            scope,
+            name,
+            range: node.range(),
+            identifier,
+            imported,
+            exported,
+            mutable,
+            shared: false,
+            address_type: None,
+        });
+        id
+    }
+
+    /// Record the address type and sharing of a memory or table definition.
+    fn limits(&mut self, id: usize, node: &tree_sitter::Node) {
+        let kinds = self.kinds;
+        let definition = &mut self.analysis.definitions[id];
+        let ty = match definition.space {
+            IndexSpace::Memory => find_descendant(node, kinds.memory_type, 3),
+            IndexSpace::Table => find_descendant(node, kinds.table_type, 3),
+            _ => return,
+        };
+        let address_type = ty
+            .as_ref()
+            .and_then(|ty| first_child(ty, kinds.address_type))
+            .map(|it| self.content.utf8_text_for_tree_sitter_node(&it));
+        definition.address_type = Some(match address_type.as_deref() {
+            Some("i64") => AddressType::I64,
+            _ => AddressType::I32,
+        });
+        definition.shared = ty
+            .as_ref()
+            .and_then(|ty| first_child(ty, kinds.share))
+            .map_or(false, |it| "shared" == self.content.utf8_text_for_tree_sitter_node(&it));
+    }
+
+    fn references(&mut self, module: usize, scope: Option<usize>, field: &tree_sitter::Node) -> anyhow::Result<()> {
+        let kinds = self.kinds;
+        let mut work = vec![field.clone()];
+        while let Some(node) = work.pop() {
+            self.token.tick()?;
+            if kinds.index == node.kind_id() {
+                if let Some(space) = self.index_space(&node) {
+                    let definition = if IndexSpace::Field == space {
+                        // field indices are resolved within the struct type given by the first index
+                        let scope = node
+                            .parent()
+                            .and_then(|parent| self.indices(&parent).into_iter().next())
+                            .and_then(|ty| self.resolve(module, None, IndexSpace::Type, &ty));
+                        self.resolve(module, scope, space, &node)
+                    } else {
+                        self.resolve(module, scope, space, &node)
+                    };
+                    if let Some(id) = definition {
+                        if kinds.module_field_export == field.kind_id() {
+                            self.analysis.definitions[id].exported = true;
+                            self.record_export(field, id);
+                        }
+                    }
+                    let text = self.content.utf8_text_for_tree_sitter_node(&node).trim().into();
+                    self.analysis.references.push(Reference {
+                        space,
+                        text,
+                        range: node.range(),
+                        definition,
+                    });
+                }
+            } else {
+                let mut nested = children(&node);
+                nested.reverse();
+                work.extend(nested);
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the instructions of a function which address a memory or table.
+    fn accesses(&mut self, module: usize, func: &tree_sitter::Node) -> anyhow::Result<()> {
+        let kinds = self.kinds;
+        let mut work = vec![func.clone()];
+        while let Some(node) = work.pop() {
+            self.token.tick()?;
+            if kinds.instr_plain == node.kind_id() {
+                if let Some(instr) = node.named_child(0) {
+                    let op = mnemonic(self.content, &instr);
+                    let space = match IndexSpace::for_instr(&op) {
+                        Some(IndexSpace::Memory) => IndexSpace::Memory,
+                        Some(IndexSpace::Table) => IndexSpace::Table,
+                        _ if "memory.init" == op => IndexSpace::Memory,
+                        _ => continue,
+                    };
+                    // an omitted (memory or table) index refers to the first entity of the space
+                    let target = self
+                        .indices(&instr)
+                        .into_iter()
+                        .find(|index| self.index_space(index) == Some(space))
+                        .map_or_else(
+                            || self.lookup(module, space, 0),
+                            |index| self.resolve(module, None, space, &index),
+                        );
+                    let address_type = target
+                        .and_then(|id| self.analysis.definitions[id].address_type)
+                        .unwrap_or_default();
+                    let align = find_descendant(&instr, kinds.align_value, 2).and_then(|it| {
+                        let text = self.content.utf8_text_for_tree_sitter_node(&it);
+                        let align = parse_u32(text.rsplit('=').next()?.trim())?;
+                        Some((align, it.range()))
+                    });
+                    self.analysis.accesses.push(Access {
+                        op,
+                        range: instr.range(),
+                        space,
+                        target,
+                        address_type,
+                        align,
+                    });
+                }
+            } else {
+                let mut nested = children(&node);
+                nested.reverse();
+                work.extend(nested);
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the instructions and clauses of a function which make use of proposals.
+    fn proposals(&mut self, func: &tree_sitter::Node) -> anyhow::Result<()> {
+        let kinds = self.kinds;
+        let mut work = vec![func.clone()];
+        while let Some(node) = work.pop() {
+            self.token.tick()?;
+            if kinds.instr_plain == node.kind_id() {
+                if let Some(instr) = node.named_child(0) {
+                    let op = mnemonic(self.content, &instr);
+                    if let Some(proposal) = Proposal::for_instr(&op) {
+                        let range = instr.range();
+                        self.analysis.proposals.push(ProposalUse { proposal, range });
+                    }
+                }
+            } else {
+                if kinds.catch_clause == node.kind_id() {
+                    let proposal = Proposal::Exceptions;
+                    let range = node.range();
+                    self.analysis.proposals.push(ProposalUse { proposal, range });
+                }
+                let mut nested = children(&node);
+                nested.reverse();
+                work.extend(nested);
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the index space an index node refers into, based on its context.
+    fn index_space(&self, node: &tree_sitter::Node) -> Option<IndexSpace> {
+        let kinds = self.kinds;
+        let parent = node.parent()?;
+        let kind = parent.kind_id();
+        if kinds.export_desc_func == kind || kinds.module_field_start == kind || kinds.elem_list == kind {
+            Some(IndexSpace::Func)
+        } else if kinds.export_desc_global == kind {
+            Some(IndexSpace::Global)
+        } else if kinds.export_desc_memory == kind || kinds.memory_use == kind {
+            Some(IndexSpace::Memory)
+        } else if kinds.export_desc_table == kind || kinds.table_use == kind || kinds.op_table_copy == kind {
+            Some(IndexSpace::Table)
+        } else if kinds.export_desc_tag == kind {
+            Some(IndexSpace::Tag)
+        } else if kinds.catch_clause == kind {
+            // `catch $tag $label` and `catch_ref $tag $label`: the remaining indices are labels.
+            let op = mnemonic(self.content, &parent);
+            let first = self.indices(&parent).first().map(|it| it.id()) == Some(node.id());
+            if first && ("catch" == op || "catch_ref" == op) {
+                Some(IndexSpace::Tag)
+            } else {
+                None
+            }
+        } else if kinds.type_use == kind
+            || kinds.sub_type == kind
+            || kinds.heap_type == kind
+            || kinds.op_array_copy == kind
+        {
+            Some(IndexSpace::Type)
+        } else if kinds.op_table_init == kind {
+            // `table.init $table? $elem`: only the last index refers to an element segment.
+            let indices = self.indices(&parent);
+            if indices.last().map(|it| it.id()) == Some(node.id()) {
+                Some(IndexSpace::Elem)
+            } else {
+                Some(IndexSpace::Table)
+            }
+        } else if kinds.op_struct_field == kind {
+            // `struct.get $type $field`: the last index refers to a field of the struct type.
+            let indices = self.indices(&parent);
+            if indices.len() > 1 && indices.last().map(|it| it.id()) == Some(node.id()) {
+                Some(IndexSpace::Field)
+            } else {
+                Some(IndexSpace::Type)
+            }
+        } else if kinds.op_array_segment == kind {
+            // `array.new_data $type $data`: the last index refers to a data or element segment.
+            let indices = self.indices(&parent);
+            if indices.len() > 1 && indices.last().map(|it| it.id()) == Some(node.id()) {
+                if mnemonic(self.content, &parent).ends_with("_data") {
+                    Some(IndexSpace::Data)
+                } else {
+                    Some(IndexSpace::Elem)
+                }
+            } else {
+                Some(IndexSpace::Type)
+            }
+        } else {
+            let op = mnemonic(self.content, &parent);
+            // `memory.init $memory? $data`: only the last index refers to a data segment.
+            let indices = self.indices(&parent);
+            if "memory.init" == op && indices.len() > 1 && indices.last().map(|it| it.id()) != Some(node.id()) {
+                Some(IndexSpace::Memory)
+            } else {
+                IndexSpace::for_instr(&op)
+            }
+        }
+    }
+
+    /// Collect the index nodes which are immediate children of a node.
+    fn indices<'tree>(&self, node: &tree_sitter::Node<'tree>) -> Vec<tree_sitter::Node<'tree>> {
+        children(node)
+            .into_iter()
+            .filter(|it| self.kinds.index == it.kind_id())
+            .collect()
+    }
+
+    /// Resolve an index node to a definition.
+    fn resolve(
+        &self,
+        module: usize,
+        scope: Option<usize>,
+        space: IndexSpace,
+        node: &tree_sitter::Node,
+    ) -> Option<usize> {
+        let text = self.content.utf8_text_for_tree_sitter_node(node);
+        self.analysis.resolve(module, scope, space, text.trim())
+    }
+
+    /// Count the parameters of a function type definition.
+    fn params(&self, ty: &tree_sitter::Node) -> usize {
+        let kinds = self.kinds;
+        let mut count = 0;
+        let mut work = children(ty);
+        while let Some(node) = work.pop() {
+            let kind = node.kind_id();
+            if kinds.func_type_params_one == kind {
+                count += 1;
+            } else if kinds.func_type_params_many == kind {
+                count += children(&node)
+                    .into_iter()
+                    .filter(|it| kinds.value_type == it.kind_id())
+                    .count();
+            } else {
+                work.extend(children(&node));
+            }
+        }
+        count
+    }
+
+    /// Find the (module-level) definition with the given index.
+    fn lookup(&self, module: usize, space: IndexSpace, index: u32) -> Option<usize> {
+        let key: Scoped<u32> = (module, space, None, index);
+        self.analysis.lookup.indices.get(&key).copied()
+    }
+
+    /// Mark instructions following an unconditional branch as unreachable.
+    fn unreachable(&mut self, node: &tree_sitter::Node) -> anyhow::Result<()> {
+        let kinds = self.kinds;
+        let mut dead = false;
+        for child in children(node) {
+            self.token.tick()?;
+            let kind = child.kind_id();
+            if kinds.token_else == kind {
+                dead = false;
+            } else if kinds.instr == kind || kinds.expr == kind {
+                if dead {
+                    self.analysis.unreachable.push(child.range());
+                } else {
+                    self.unreachable(&child)?;
+                    if let Some(instr) = plain_instr(kinds, child) {
+                        let op = mnemonic(self.content, &instr);
+                        dead = is_unconditional_branch(&op);
+                    }
+                }
+            } else {
+                self.unreachable(&child)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Determine whether an instruction unconditionally transfers control.
+pub fn is_unconditional_branch(op: &str) -> bool {
+    matches!(
+        op,
+        "unreachable"
+            | "br"
+            | "br_table"
+            | "return"
+            | "return_call"
+            | "return_call_indirect"
+            | "return_call_ref"
+            | "throw"
+            | "throw_ref"
+    )
+}
+
+/// Compute the alignment (in bytes) required of an atomic memory instruction.
+///
+/// Atomic accesses must be aligned to exactly their natural alignment, i.e., the width of the
+/// access (e.g., 2 for `i64.atomic.load16_u` or `i32.atomic.rmw16.add_u`).
+pub fn atomic_alignment(op: &str) -> Option<u32> {
+    match op {
+        "memory.atomic.notify" | "memory.atomic.wait32" => return Some(4),
+        "memory.atomic.wait64" => return Some(8),
+        _ => {},
+    }
+    let (ty, rest) = op.split_once(".atomic.")?;
+    // the width is given by the size suffix of the operation (if any) or else by the value type
+    let size = rest
+        .split('.')
+        .next()?
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .split('_')
+        .next()?;
+    let bits = match (ty, size) {
+        ("i32", "") => 32,
+        ("i64", "") => 64,
+        (_, "") => return None,
+        (_, size) => size.parse::<u32>().ok()?,
+    };
+    Some(bits / 8)
+}
+
+/// Compute the mnemonic (e.g., `i32.add`) for an instruction node.
+pub fn mnemonic(content: &ropey::Rope, node: &tree_sitter::Node) -> String {
+    let text = content.utf8_text_for_tree_sitter_node(node);
+    text.split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .find(|it| !it.is_empty())
+        .unwrap_or_default()
+        .into()
+}
+
+/// Descend from an `instr` or (folded) `expr` node to the underlying `instr_plain` node, if any.
+pub(crate) fn plain_instr<'tree>(kinds: &Kinds, node: tree_sitter::Node<'tree>) -> Option<tree_sitter::Node<'tree>> {
+    let mut node = node;
+    loop {
+        let kind = node.kind_id();
+        if kinds.instr_plain == kind {
+            return node.named_child(0);
+        } else if kinds.instr == kind || kinds.expr == kind || kinds.expr1 == kind || kinds.expr1_plain == kind {
+            node = node.named_child(0)?;
+        } else {
+            return None;
+        }
+    }
+}
+
+/// Parse an unsigned integer literal (decimal or hexadecimal, possibly with `_` separators).
+pub fn parse_u32(text: &str) -> Option<u32> {
+    let text = text.replace('_', "");
+    if let Some(hex) = text.strip_prefix("0x") {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+pub(crate) fn children<'tree>(node: &tree_sitter::Node<'tree>) -> Vec<tree_sitter::Node<'tree>> {
+    node.children(&mut node.walk()).collect()
+}
+
+pub(crate) fn first_child<'tree>(node: &tree_sitter::Node<'tree>, kind: u16) -> Option<tree_sitter::Node<'tree>> {
+    children(node).into_iter().find(|it| kind == it.kind_id())
+}
+
+fn has_child(node: &tree_sitter::Node, kind: u16) -> bool {
+    first_child(node, kind).is_some()
+}
+
+fn has_descendant(node: &tree_sitter::Node, kind: u16, depth: usize) -> bool {
+    find_descendant(node, kind, depth).is_some()
+}
+
+fn find_descendant<'tree>(
+    node: &tree_sitter::Node<'tree>,
+    kind: u16,
+    depth: usize,
+) -> Option<tree_sitter::Node<'tree>> {
+    children(node).into_iter().find_map(|it| {
+        if kind == it.kind_id() {
+            Some(it)
+        } else if depth > 1 {
+            find_descendant(&it, kind, depth - 1)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{atomic_alignment, is_unconditional_branch, parse_u32, IndexSpace};
+
+    #[test]
+    fn index_space_for_instr() {
+        assert_eq!(IndexSpace::for_instr("call"), Some(IndexSpace::Func));
+        assert_eq!(IndexSpace::for_instr("global.set"), Some(IndexSpace::Global));
+        assert_eq!(IndexSpace::for_instr("local.tee"), Some(IndexSpace::Local));
+        assert_eq!(IndexSpace::for_instr("i64.load32_u"), Some(IndexSpace::Memory));
+        assert_eq!(IndexSpace::for_instr("memory.grow"), Some(IndexSpace::Memory));
+        assert_eq!(IndexSpace::for_instr("memory.init"), Some(IndexSpace::Data));
+        assert_eq!(IndexSpace::for_instr("table.grow"), Some(IndexSpace::Table));
+        assert_eq!(IndexSpace::for_instr("struct.new"), Some(IndexSpace::Type));
+        assert_eq!(IndexSpace::for_instr("array.new_default"), Some(IndexSpace::Type));
+        assert_eq!(IndexSpace::for_instr("call_ref"), Some(IndexSpace::Type));
+        assert_eq!(IndexSpace::for_instr("return_call"), Some(IndexSpace::Func));
+        assert_eq!(IndexSpace::for_instr("return_call_indirect"), Some(IndexSpace::Table));
+        assert_eq!(IndexSpace::for_instr("throw"), Some(IndexSpace::Tag));
+        assert_eq!(IndexSpace::for_instr("throw_ref"), None);
+        assert_eq!(IndexSpace::for_instr("i32.add"), None);
+    }
+
+    #[test]
+    fn unconditional_branch() {
+        assert!(is_unconditional_branch("br"));
+        assert!(is_unconditional_branch("unreachable"));
+        assert!(is_unconditional_branch("return_call"));
+        assert!(is_unconditional_branch("throw"));
+        assert!(is_unconditional_branch("throw_ref"));
+        assert!(!is_unconditional_branch("br_if"));
+    }
+
+    #[test]
+    fn atomic_alignments() {
+        assert_eq!(atomic_alignment("i32.atomic.load"), Some(4));
+        assert_eq!(atomic_alignment("i64.atomic.store"), Some(8));
+        assert_eq!(atomic_alignment("i64.atomic.load8_u"), Some(1));
+        assert_eq!(atomic_alignment("i64.atomic.store32"), Some(4));
+        assert_eq!(atomic_alignment("i32.atomic.rmw16.add_u"), Some(2));
+        assert_eq!(atomic_alignment("i64.atomic.rmw.cmpxchg"), Some(8));
+        assert_eq!(atomic_alignment("memory.atomic.wait64"), Some(8));
+        assert_eq!(atomic_alignment("memory.atomic.notify"), Some(4));
+        assert_eq!(atomic_alignment("atomic.fence"), None);
+        assert_eq!(atomic_alignment("i32.load"), None);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_u32("42"), Some(42));
+        assert_eq!(parse_u32("0x2a"), Some(42));
+        assert_eq!(parse_u32("1_000"), Some(1000));
+        assert_eq!(parse_u32("$f"), None);
+    }
+}
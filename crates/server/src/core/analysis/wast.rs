@@ -0,0 +1,72 @@
+//! Node kinds for semantic analysis of `.wast` documents.
+
+use super::Kinds;
+use crate::core::language::wast;
+
+pub(crate) const KINDS: Kinds = Kinds {
+    module: wast::kind::MODULE,
+    module_field: wast::kind::MODULE_FIELD,
+    module_field_data: wast::kind::MODULE_FIELD_DATA,
+    module_field_elem: wast::kind::MODULE_FIELD_ELEM,
+    module_field_export: wast::kind::MODULE_FIELD_EXPORT,
+    module_field_func: wast::kind::MODULE_FIELD_FUNC,
+    module_field_global: wast::kind::MODULE_FIELD_GLOBAL,
+    module_field_import: wast::kind::MODULE_FIELD_IMPORT,
+    module_field_memory: wast::kind::MODULE_FIELD_MEMORY,
+    module_field_rec: wast::kind::MODULE_FIELD_REC,
+    module_field_start: wast::kind::MODULE_FIELD_START,
+    module_field_table: wast::kind::MODULE_FIELD_TABLE,
+    module_field_tag: wast::kind::MODULE_FIELD_TAG,
+    module_field_type: wast::kind::MODULE_FIELD_TYPE,
+    identifier: wast::kind::IDENTIFIER,
+    index: wast::kind::INDEX,
+    import: wast::kind::IMPORT,
+    name: wast::kind::NAME,
+    export: wast::kind::EXPORT,
+    export_desc_func: wast::kind::EXPORT_DESC_FUNC,
+    export_desc_global: wast::kind::EXPORT_DESC_GLOBAL,
+    export_desc_memory: wast::kind::EXPORT_DESC_MEMORY,
+    export_desc_table: wast::kind::EXPORT_DESC_TABLE,
+    export_desc_tag: wast::kind::EXPORT_DESC_TAG,
+    import_desc_func_type: wast::kind::IMPORT_DESC_FUNC_TYPE,
+    import_desc_global_type: wast::kind::IMPORT_DESC_GLOBAL_TYPE,
+    import_desc_memory_type: wast::kind::IMPORT_DESC_MEMORY_TYPE,
+    import_desc_table_type: wast::kind::IMPORT_DESC_TABLE_TYPE,
+    import_desc_tag: wast::kind::IMPORT_DESC_TAG,
+    import_desc_type_use: wast::kind::IMPORT_DESC_TYPE_USE,
+    func_type_params_one: wast::kind::FUNC_TYPE_PARAMS_ONE,
+    func_type_params_many: wast::kind::FUNC_TYPE_PARAMS_MANY,
+    func_locals_one: wast::kind::FUNC_LOCALS_ONE,
+    func_locals_many: wast::kind::FUNC_LOCALS_MANY,
+    struct_field_one: wast::kind::STRUCT_FIELD_ONE,
+    struct_field_many: wast::kind::STRUCT_FIELD_MANY,
+    field_type: wast::kind::FIELD_TYPE,
+    sub_type: wast::kind::SUB_TYPE,
+    heap_type: wast::kind::HEAP_TYPE,
+    value_type: wast::kind::VALUE_TYPE,
+    global_type_mut: wast::kind::GLOBAL_TYPE_MUT,
+    memory_type: wast::kind::MEMORY_TYPE,
+    table_type: wast::kind::TABLE_TYPE,
+    address_type: wast::kind::ADDRESS_TYPE,
+    share: wast::kind::SHARE,
+    align_value: wast::kind::ALIGN_VALUE,
+    type_use: wast::kind::TYPE_USE,
+    memory_use: wast::kind::MEMORY_USE,
+    table_use: wast::kind::TABLE_USE,
+    elem_list: wast::kind::ELEM_LIST,
+    catch_clause: wast::kind::CATCH_CLAUSE,
+    instr: wast::kind::INSTR,
+    instr_plain: wast::kind::INSTR_PLAIN,
+    expr: wast::kind::EXPR,
+    expr1: wast::kind::EXPR1,
+    expr1_plain: wast::kind::EXPR1_PLAIN,
+    op_table_copy: wast::kind::OP_TABLE_COPY,
+    op_table_init: wast::kind::OP_TABLE_INIT,
+    op_struct_field: wast::kind::OP_STRUCT_FIELD,
+    op_array_copy: wast::kind::OP_ARRAY_COPY,
+    op_array_segment: wast::kind::OP_ARRAY_SEGMENT,
+    token_else: wast::kind::token::ELSE,
+    token_mut: wast::kind::token::MUT,
+    field_identifier: wast::field::IDENTIFIER,
+    invalid: &[wast::kind::ASSERT_INVALID, wast::kind::ASSERT_MALFORMED],
+};
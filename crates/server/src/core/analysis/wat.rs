@@ -0,0 +1,72 @@
+//! Node kinds for semantic analysis of `.wat` documents.
+
+use super::Kinds;
+use crate::core::language::wat;
+
+pub(crate) const KINDS: Kinds = Kinds {
+    module: wat::kind::MODULE,
+    module_field: wat::kind::MODULE_FIELD,
+    module_field_data: wat::kind::MODULE_FIELD_DATA,
+    module_field_elem: wat::kind::MODULE_FIELD_ELEM,
+    module_field_export: wat::kind::MODULE_FIELD_EXPORT,
+    module_field_func: wat::kind::MODULE_FIELD_FUNC,
+    module_field_global: wat::kind::MODULE_FIELD_GLOBAL,
+    module_field_import: wat::kind::MODULE_FIELD_IMPORT,
+    module_field_memory: wat::kind::MODULE_FIELD_MEMORY,
+    module_field_rec: wat::kind::MODULE_FIELD_REC,
+    module_field_start: wat::kind::MODULE_FIELD_START,
+    module_field_table: wat::kind::MODULE_FIELD_TABLE,
+    module_field_tag: wat::kind::MODULE_FIELD_TAG,
+    module_field_type: wat::kind::MODULE_FIELD_TYPE,
+    identifier: wat::kind::IDENTIFIER,
+    index: wat::kind::INDEX,
+    import: wat::kind::IMPORT,
+    name: wat::kind::NAME,
+    export: wat::kind::EXPORT,
+    export_desc_func: wat::kind::EXPORT_DESC_FUNC,
+    export_desc_global: wat::kind::EXPORT_DESC_GLOBAL,
+    export_desc_memory: wat::kind::EXPORT_DESC_MEMORY,
+    export_desc_table: wat::kind::EXPORT_DESC_TABLE,
+    export_desc_tag: wat::kind::EXPORT_DESC_TAG,
+    import_desc_func_type: wat::kind::IMPORT_DESC_FUNC_TYPE,
+    import_desc_global_type: wat::kind::IMPORT_DESC_GLOBAL_TYPE,
+    import_desc_memory_type: wat::kind::IMPORT_DESC_MEMORY_TYPE,
+    import_desc_table_type: wat::kind::IMPORT_DESC_TABLE_TYPE,
+    import_desc_tag: wat::kind::IMPORT_DESC_TAG,
+    import_desc_type_use: wat::kind::IMPORT_DESC_TYPE_USE,
+    func_type_params_one: wat::kind::FUNC_TYPE_PARAMS_ONE,
+    func_type_params_many: wat::kind::FUNC_TYPE_PARAMS_MANY,
+    func_locals_one: wat::kind::FUNC_LOCALS_ONE,
+    func_locals_many: wat::kind::FUNC_LOCALS_MANY,
+    struct_field_one: wat::kind::STRUCT_FIELD_ONE,
+    struct_field_many: wat::kind::STRUCT_FIELD_MANY,
+    field_type: wat::kind::FIELD_TYPE,
+    sub_type: wat::kind::SUB_TYPE,
+    heap_type: wat::kind::HEAP_TYPE,
+    value_type: wat::kind::VALUE_TYPE,
+    global_type_mut: wat::kind::GLOBAL_TYPE_MUT,
+    memory_type: wat::kind::MEMORY_TYPE,
+    table_type: wat::kind::TABLE_TYPE,
+    address_type: wat::kind::ADDRESS_TYPE,
+    share: wat::kind::SHARE,
+    align_value: wat::kind::ALIGN_VALUE,
+    type_use: wat::kind::TYPE_USE,
+    memory_use: wat::kind::MEMORY_USE,
+    table_use: wat::kind::TABLE_USE,
+    elem_list: wat::kind::ELEM_LIST,
+    catch_clause: wat::kind::CATCH_CLAUSE,
+    instr: wat::kind::INSTR,
+    instr_plain: wat::kind::INSTR_PLAIN,
+    expr: wat::kind::EXPR,
+    expr1: wat::kind::EXPR1,
+    expr1_plain: wat::kind::EXPR1_PLAIN,
+    op_table_copy: wat::kind::OP_TABLE_COPY,
+    op_table_init: wat::kind::OP_TABLE_INIT,
+    op_struct_field: wat::kind::OP_STRUCT_FIELD,
+    op_array_copy: wat::kind::OP_ARRAY_COPY,
+    op_array_segment: wat::kind::OP_ARRAY_SEGMENT,
+    token_else: wat::kind::token::ELSE,
+    token_mut: wat::kind::token::MUT,
+    field_identifier: wat::field::IDENTIFIER,
+    invalid: &[],
+};
@@ -0,0 +1,138 @@
+//! Definitions related to the cooperative cancellation of long-running work.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The number of steps of work between two checks of a [`CancellationToken`] by
+/// [`CancellationToken::tick`].
+pub const CHECK_INTERVAL: usize = 256;
+
+/// A token for the cooperative cancellation of long-running work, such as walking the syntax tree
+/// of a large document.
+///
+/// A token is cancelled explicitly (e.g., when the client cancels the request it belongs to) or
+/// implicitly, once the [`crate::core::Snapshot`] it is bound to becomes outdated. The work is
+/// expected to check the token periodically and to stop with
+/// [`crate::core::Error::RequestCancelled`] once it has been cancelled.
+#[derive(Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    snapshot: Option<Arc<crate::core::Snapshot>>,
+    steps: AtomicUsize,
+}
+
+impl CancellationToken {
+    /// Create a new [`CancellationToken`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a [`CancellationToken`] which is cancelled together with this one, and additionally
+    /// once `snapshot` becomes outdated.
+    pub fn with_snapshot(&self, snapshot: Arc<crate::core::Snapshot>) -> Self {
+        CancellationToken {
+            cancelled: self.cancelled.clone(),
+            snapshot: Some(snapshot),
+            steps: AtomicUsize::default(),
+        }
+    }
+
+    /// Cancel the [`CancellationToken`] (and the tokens derived from it).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Determine whether the [`CancellationToken`] has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire) || self.snapshot.as_ref().map_or(false, |it| it.is_outdated())
+    }
+
+    /// Fail with [`crate::core::Error::RequestCancelled`] if the [`CancellationToken`] has been
+    /// cancelled.
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.is_cancelled() {
+            return Err(crate::core::Error::RequestCancelled.into());
+        }
+        Ok(())
+    }
+
+    /// Record a step of work, checking the [`CancellationToken`] every [`CHECK_INTERVAL`] steps.
+    pub fn tick(&self) -> anyhow::Result<()> {
+        if 0 == self.steps.fetch_add(1, Ordering::Relaxed) % CHECK_INTERVAL {
+            self.check()?;
+        }
+        Ok(())
+    }
+
+    /// Create a guard which cancels the [`CancellationToken`] when dropped.
+    pub fn drop_guard(&self) -> DropGuard {
+        DropGuard {
+            cancelled: self.cancelled.clone(),
+        }
+    }
+}
+
+impl Clone for CancellationToken {
+    fn clone(&self) -> Self {
+        CancellationToken {
+            cancelled: self.cancelled.clone(),
+            snapshot: self.snapshot.clone(),
+            steps: AtomicUsize::default(),
+        }
+    }
+}
+
+/// A guard which cancels a [`CancellationToken`] when dropped, e.g., along with the future of a
+/// request which the client cancelled.
+pub struct DropGuard {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancellationToken, CHECK_INTERVAL};
+
+    #[test]
+    fn cancel() {
+        let token = CancellationToken::new();
+        let derived = token.clone();
+        assert!(token.check().is_ok());
+        derived.cancel();
+        assert!(token.is_cancelled());
+        let error = token.check().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<crate::core::Error>(),
+            Some(&crate::core::Error::RequestCancelled)
+        );
+    }
+
+    #[test]
+    fn drop_guard() {
+        let token = CancellationToken::new();
+        {
+            let _guard = token.drop_guard();
+            assert!(!token.is_cancelled());
+        }
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn tick() {
+        let token = CancellationToken::new();
+        assert!(token.tick().is_ok());
+        token.cancel();
+        // the token is only checked once per interval
+        for _ in 1 .. CHECK_INTERVAL {
+            assert!(token.tick().is_ok());
+        }
+        assert!(token.tick().is_err());
+    }
+}
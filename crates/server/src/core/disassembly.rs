@@ -0,0 +1,20 @@
+//! Definitions related to disassembled binary `.wasm` modules.
+
+/// The URL scheme of the virtual documents holding disassembled `.wasm` modules.
+pub const SCHEME: &str = "wasm-disassembly";
+
+/// Compute the URL of the virtual document for the disassembly of the module at `uri`.
+pub fn uri(uri: &lsp::Url) -> anyhow::Result<lsp::Url> {
+    let uri = lsp::Url::parse(&format!("{}://{}", SCHEME, uri.path()))?;
+    Ok(uri)
+}
+
+/// Determine whether `uri` refers to the virtual document for a disassembled module.
+pub fn is_disassembly(uri: &lsp::Url) -> bool {
+    SCHEME == uri.scheme()
+}
+
+/// Disassemble a binary `.wasm` module into `.wat`, using the name section for identifiers.
+pub fn disassemble(bytes: &[u8]) -> anyhow::Result<String> {
+    wasmprinter::print_bytes(bytes)
+}
@@ -1,14 +1,19 @@
 //! Definitions related to LSP documents.
 
 use crate::core::{self};
-use async_lock::Mutex;
-use lsp_text::{RopeExt, TextEdit};
-use std::sync::Arc;
+use async_lock::{Mutex, RwLock};
+use lsp_text::RopeExt;
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+};
 
 /// Documents for the LSP session.
 pub struct Document {
     /// The language-id of the document.
     pub language: core::Language,
+    /// The version of the document, as reported by the client.
+    pub version: i32,
     /// The textual content of the document.
     pub content: ropey::Rope,
     /// The active parser for the document.
@@ -26,10 +31,13 @@ impl Document {
         let language = core::Language::try_from(params.text_document.language_id.as_str())?;
         let mut parser = tree_sitter::Parser::new()?;
         match language {
+            core::Language::Component => parser.set_language(&session.languages.component)?,
             core::Language::Wast => parser.set_language(&session.languages.wast)?,
-            core::Language::Wat => parser.set_language(&session.languages.wat)?,
+            core::Language::Wat | core::Language::Wasm => parser.set_language(&session.languages.wat)?,
+            core::Language::Wit => parser.set_language(&session.languages.wit)?,
         };
 
+        let version = params.text_document.version;
         let content = ropey::Rope::from(params.text_document.text);
         let result = {
             let content = content.clone();
@@ -40,56 +48,160 @@ impl Document {
         };
         Ok(result.map(|tree| core::Document {
             language,
+            version,
             content,
             parser,
             tree,
         }))
     }
 
-    ///
-    pub async fn change<'changes>(
-        session: Arc<core::Session>,
-        uri: &lsp::Url,
-        content: &ropey::Rope,
-        edits: &[TextEdit<'changes>],
-    ) -> anyhow::Result<Option<tree_sitter::Tree>> {
-        let result = {
-            let parser = session.get_mut_parser(uri).await?;
-            let mut parser = parser.lock().await;
+    /// Return the language-id, version, and textual content portion of the [`Document`].
+    pub fn text(&self) -> core::Text {
+        core::Text {
+            language: self.language,
+            version: self.version,
+            content: self.content.clone(),
+        }
+    }
+}
 
-            let callback = {
-                let mut content = content.clone();
-                content.shrink_to_fit();
-                let byte_idx = 0;
-                content.chunk_walker(byte_idx).callback_adapter_for_tree_sitter()
-            };
+/// The state of an open document, as tracked by the [`core::Session`].
+///
+/// The current content, syntax tree, and derived analyses of the document are published as an
+/// immutable [`core::Snapshot`] which is replaced (rather than modified) on every change.
+pub struct DocumentState {
+    parser: Mutex<tree_sitter::Parser>,
+    snapshot: RwLock<Arc<core::Snapshot>>,
+    latest: Arc<AtomicI32>,
+}
 
-            let old_tree = session.get_mut_tree(uri).await?;
-            let mut old_tree = old_tree.lock().await;
+impl DocumentState {
+    /// Create a new [`DocumentState`] for an opened [`Document`].
+    pub fn new(uri: lsp::Url, document: Document) -> Self {
+        let text = document.text();
+        let latest = Arc::new(AtomicI32::new(text.version));
+        let snapshot = core::Snapshot::new(uri, text, document.tree, latest.clone());
+        let parser = Mutex::new(document.parser);
+        let snapshot = RwLock::new(Arc::new(snapshot));
+        DocumentState {
+            parser,
+            snapshot,
+            latest,
+        }
+    }
 
-            for edit in edits {
-                old_tree.edit(&edit.input_edit);
-            }
+    /// Retrieve the current [`core::Snapshot`] of the document.
+    pub async fn snapshot(&self) -> Arc<core::Snapshot> {
+        self.snapshot.read().await.clone()
+    }
 
-            parser.parse_with(callback, Some(&*old_tree))?
-        };
+    /// Apply the `changes` bringing the document to `version`, incrementally reparsing its syntax
+    /// tree. Returns the new [`core::Snapshot`], or `None` if `version` is not newer than the
+    /// current version.
+    ///
+    /// Snapshots taken before the change are marked as outdated once the new snapshot has been
+    /// published, so that a change which fails leaves them current.
+    pub async fn change(
+        &self,
+        version: i32,
+        changes: &[lsp::TextDocumentContentChangeEvent],
+    ) -> anyhow::Result<Option<Arc<core::Snapshot>>> {
+        // changes are applied one at a time
+        let mut parser = self.parser.lock().await;
+        let current = self.snapshot().await;
+        if version <= current.version() {
+            return Ok(None);
+        }
 
-        if let Some(tree) = result {
-            {
-                let tree = tree.clone();
-                *session.get_mut_tree(uri).await?.value_mut() = Mutex::new(tree);
-            }
-            Ok(Some(tree))
-        } else {
-            Ok(None)
+        // the rope and tree share their unchanged parts with those of the current snapshot
+        let mut content = current.text.content.clone();
+        let mut tree = current.tree.clone();
+        let edits = changes
+            .iter()
+            .map(|change| content.build_edit(change))
+            .collect::<Result<Vec<_>, _>>()?;
+        for edit in &edits {
+            content.apply_edit(edit);
+            tree.edit(&edit.input_edit);
         }
-    }
 
-    /// Return the language-id and textual content portion of the [`Document`].
-    pub fn text(&self) -> core::Text {
-        core::Text {
-            language: self.language,
-            content: self.content.clone(),
+        let callback = {
+            let content = content.clone();
+            let byte_idx = 0;
+            content.chunk_walker(byte_idx).callback_adapter_for_tree_sitter()
+        };
+        // keep the edited tree if the content could not be reparsed
+        if let Some(result) = parser.parse_with(callback, Some(&tree))? {
+            tree = result;
         }
+
+        let text = core::Text {
+            language: current.text.language,
+            version,
+            content,
+        };
+        let snapshot = Arc::new(core::Snapshot::new(
+            current.uri.clone(),
+            text,
+            tree,
+            self.latest.clone(),
+        ));
+        *self.snapshot.write().await = snapshot.clone();
+        self.latest.store(version, Ordering::Release);
+        Ok(Some(snapshot))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::{Document, DocumentState};
+    use std::sync::Arc;
+
+    #[futures_test::test]
+    async fn change_during_request() -> anyhow::Result<()> {
+        let languages = crate::core::SessionLanguages {
+            component: wasm_lsp_languages::language::component(),
+            wast: wasm_lsp_languages::language::wast(),
+            wat: wasm_lsp_languages::language::wat(),
+            wit: wasm_lsp_languages::language::wit(),
+        };
+        let session = Arc::new(crate::core::Session::new(languages, None, None)?);
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let text_document = lsp::TextDocumentItem::new(uri.clone(), "wasm.wat".into(), 1, "(module)".into());
+        let document = Document::open(session, lsp::DidOpenTextDocumentParams { text_document })?.unwrap();
+        let state = DocumentState::new(uri, document);
+        let token = crate::core::CancellationToken::new();
+
+        // a request is running on the snapshot of version 1 when the change lands
+        let old = state.snapshot().await;
+        assert!(!old.is_outdated());
+        let change = lsp::TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "(module (func))".into(),
+        };
+        let new = state.change(2, &[change]).await?.unwrap();
+
+        // the request still sees version 1, but can tell that it is outdated
+        assert!(old.is_outdated());
+        assert_eq!(1, old.version());
+        assert_eq!("(module)", old.text.content.to_string());
+        assert!(old.analysis(&token)?.definitions.is_empty());
+
+        // the new snapshot is current and is the one served to later requests
+        assert!(!new.is_outdated());
+        assert_eq!(2, state.snapshot().await.version());
+        assert_eq!(1, new.analysis(&token)?.definitions.len());
+
+        // changes to versions which are not newer are ignored
+        let change = lsp::TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "".into(),
+        };
+        assert!(state.change(2, &[change]).await?.is_none());
+        assert!(!new.is_outdated());
+
+        Ok(())
     }
 }
@@ -0,0 +1,476 @@
+//! Definitions related to encoding `.wat` documents as binary `.wasm` modules.
+
+use anyhow::anyhow;
+
+/// Options for encoding a `.wat` document as a binary `.wasm` module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EncodeOptions {
+    /// Whether to emit the `name` section (recording the `$identifier`s of the document).
+    pub name_section: bool,
+    /// Whether to emit the custom sections given by `(@custom ...)` annotations.
+    pub custom_sections: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        EncodeOptions {
+            name_section: true,
+            custom_sections: true,
+        }
+    }
+}
+
+/// The result of encoding a `.wat` document as a binary `.wasm` module.
+#[derive(Clone, Debug)]
+pub struct Encoding {
+    /// The bytes of the binary module.
+    pub bytes: Vec<u8>,
+    /// The mapping from the code of the binary module back to the source of the document.
+    pub source_map: SourceMap,
+}
+
+/// Encode the textual content of a `.wat` document as a binary `.wasm` module.
+///
+/// Failures are reported as a [`lsp::Diagnostic`] located at the offending source range.
+pub fn encode(content: &ropey::Rope, options: &EncodeOptions) -> Result<Encoding, Box<lsp::Diagnostic>> {
+    let text = content.to_string();
+    let failed = |offset: usize, message: String| {
+        Box::new(lsp::Diagnostic {
+            range: token_range(content, offset),
+            severity: Some(lsp::DiagnosticSeverity::ERROR),
+            message,
+            ..Default::default()
+        })
+    };
+
+    let mut buffer = wast::parser::ParseBuffer::new(&text).map_err(|err| failed(err.span().offset(), err.message()))?;
+    buffer.track_instr_spans(true);
+    let mut wat =
+        wast::parser::parse::<wast::Wat>(&buffer).map_err(|err| failed(err.span().offset(), err.message()))?;
+    let bytes = wat.encode().map_err(|err| failed(err.span().offset(), err.message()))?;
+    let bytes = strip(&bytes, options).map_err(|err| failed(0, err.to_string()))?;
+    // NOTE: the source map is built from the stripped module since removing custom sections
+    // preceding the code section shifts the offsets of the function bodies
+    let source_map = SourceMap::new(&wat, &bytes).map_err(|err| failed(0, err.to_string()))?;
+
+    Ok(Encoding { bytes, source_map })
+}
+
+/// A mapping from the code of a binary `.wasm` module back to the source of a `.wat` document.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceMap {
+    /// The mappings for the functions defined (rather than imported) by the module, in order.
+    pub functions: Vec<FunctionMapping>,
+}
+
+/// The mapping for a function body of a binary `.wasm` module.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionMapping {
+    /// The index of the function within the function index space (which includes imports).
+    pub index: u32,
+    /// The source byte offset of the function definition.
+    pub source: usize,
+    /// The byte range of the function body (following its size prefix) within the module.
+    pub body: std::ops::Range<usize>,
+    /// The mappings for the instructions of the function body (excluding the implicit final `end`).
+    pub instructions: Vec<InstructionMapping>,
+}
+
+/// The mapping for an instruction of a binary `.wasm` module.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstructionMapping {
+    /// The source byte offset of the instruction.
+    pub source: usize,
+    /// The byte range of the encoded instruction within the module.
+    pub range: std::ops::Range<usize>,
+}
+
+impl SourceMap {
+    /// Build the source map for a (resolved) `.wat` document and the binary module encoded from it.
+    fn new(wat: &wast::Wat, bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut sources = vec![];
+        if let wast::Wat::Module(wast::core::Module {
+            kind: wast::core::ModuleKind::Text(fields),
+            ..
+        }) = wat
+        {
+            for field in fields {
+                if let wast::core::ModuleField::Func(wast::core::Func {
+                    span,
+                    kind: wast::core::FuncKind::Inline { expression, .. },
+                    ..
+                }) = field
+                {
+                    let instrs = expression.instr_spans.as_deref().unwrap_or_default();
+                    sources.push((span.offset(), instrs.iter().map(|it| it.offset()).collect::<Vec<_>>()));
+                }
+            }
+        }
+
+        let mut functions = vec![];
+        let mut imported = 0u32;
+        let mut sources = sources.into_iter();
+        for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+            match payload? {
+                wasmparser::Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let wasmparser::TypeRef::Func(_) | wasmparser::TypeRef::FuncExact(_) = import?.ty {
+                            imported += 1;
+                        }
+                    }
+                },
+                wasmparser::Payload::CodeSectionEntry(body) => {
+                    let (source, instrs) = sources
+                        .next()
+                        .ok_or_else(|| anyhow!("malformed module: unexpected function body"))?;
+                    let mut operators = body.get_operators_reader()?;
+                    let mut offsets = vec![];
+                    while !operators.eof() {
+                        offsets.push(operators.read_with_offset()?.1);
+                    }
+                    // the final offset is that of the implicit `end` of the body
+                    let instructions = instrs
+                        .into_iter()
+                        .zip(offsets.windows(2))
+                        .map(|(source, range)| InstructionMapping {
+                            source,
+                            range: range[0] .. range[1],
+                        })
+                        .collect();
+                    functions.push(FunctionMapping {
+                        index: imported + u32::try_from(functions.len())?,
+                        source,
+                        body: body.range(),
+                        instructions,
+                    });
+                },
+                _ => {},
+            }
+        }
+
+        Ok(SourceMap { functions })
+    }
+
+    /// Find the mapping for the function at `index` within the function index space.
+    pub fn function(&self, index: u32) -> Option<&FunctionMapping> {
+        self.functions.iter().find(|it| it.index == index)
+    }
+
+    /// Find the mapping for the function whose definition lies within a source byte range.
+    pub fn function_within(&self, range: std::ops::Range<usize>) -> Option<&FunctionMapping> {
+        self.functions.iter().find(|it| range.contains(&it.source))
+    }
+
+    /// Find the source byte offset corresponding to a byte offset within the module.
+    pub fn lookup(&self, offset: usize) -> Option<usize> {
+        self.functions
+            .iter()
+            .find(|it| it.body.contains(&offset))
+            .and_then(|it| it.lookup(offset - it.body.start))
+    }
+}
+
+impl FunctionMapping {
+    /// Find the source byte offset corresponding to a byte offset within the function body.
+    ///
+    /// Offsets which do not fall within an instruction (e.g., the local declarations or the
+    /// implicit final `end`) map to the function definition itself.
+    pub fn lookup(&self, offset: usize) -> Option<usize> {
+        let offset = self.body.start.checked_add(offset)?;
+        if !self.body.contains(&offset) {
+            return None;
+        }
+        let source = self
+            .instructions
+            .iter()
+            .find(|it| it.range.contains(&offset))
+            .map_or(self.source, |it| it.source);
+        Some(source)
+    }
+}
+
+/// Remove the custom sections from a binary module which were not requested by `options`.
+fn strip(bytes: &[u8], options: &EncodeOptions) -> anyhow::Result<Vec<u8>> {
+    let mut result = bytes[.. HEADER_LEN].to_vec();
+    for section in sections(bytes)? {
+        let keep = match section.name.as_deref() {
+            None => true,
+            Some("name") => options.name_section,
+            Some(_) => options.custom_sections,
+        };
+        if keep {
+            result.extend_from_slice(&bytes[section.range]);
+        }
+    }
+    Ok(result)
+}
+
+/// The length of the preamble (magic number and version) of a binary module.
+pub const HEADER_LEN: usize = 8;
+
+/// A section of a binary `.wasm` module.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Section {
+    /// The section id.
+    pub id: u8,
+    /// The name of the section, for custom sections.
+    pub name: Option<String>,
+    /// The byte range of the entire section (including its header) within the module.
+    pub range: std::ops::Range<usize>,
+    /// The byte range of the section content (following its header) within the module.
+    pub content: std::ops::Range<usize>,
+}
+
+impl Section {
+    /// The kind of the section (e.g., `"code"`), where the `name` custom section is distinguished
+    /// from other custom sections as `"names"`.
+    pub fn kind(&self) -> &'static str {
+        match self.id {
+            0 if Some("name") == self.name.as_deref() => "names",
+            0 => "custom",
+            1 => "type",
+            2 => "import",
+            3 => "func",
+            4 => "table",
+            5 => "memory",
+            6 => "global",
+            7 => "export",
+            8 => "start",
+            9 => "elem",
+            10 => "code",
+            11 => "data",
+            12 => "datacount",
+            13 => "tag",
+            _ => "unknown",
+        }
+    }
+}
+
+/// Split a binary `.wasm` module into its sections.
+pub fn sections(bytes: &[u8]) -> anyhow::Result<Vec<Section>> {
+    if bytes.len() < HEADER_LEN || &bytes[.. 4] != b"\0asm" {
+        return Err(anyhow!("malformed module: missing preamble"));
+    }
+    let mut sections = vec![];
+    let mut offset = HEADER_LEN;
+    while offset < bytes.len() {
+        let start = offset;
+        let id = bytes[offset];
+        offset += 1;
+        let size = usize::try_from(read_u32(bytes, &mut offset)?)?;
+        let content = offset .. offset + size;
+        if bytes.len() < content.end {
+            return Err(anyhow!("malformed module: section extends past the end"));
+        }
+        let name = if 0 == id {
+            let mut offset = content.start;
+            let len = usize::try_from(read_u32(bytes, &mut offset)?)?;
+            let name = bytes
+                .get(offset .. offset + len)
+                .ok_or_else(|| anyhow!("malformed module: custom section name extends past the end"))?;
+            Some(String::from_utf8_lossy(name).into_owned())
+        } else {
+            None
+        };
+        offset = content.end;
+        sections.push(Section {
+            id,
+            name,
+            range: start .. offset,
+            content,
+        });
+    }
+    Ok(sections)
+}
+
+/// Read an unsigned LEB128-encoded integer at `offset`, advancing `offset` past it.
+pub fn read_u32(bytes: &[u8], offset: &mut usize) -> anyhow::Result<u32> {
+    let mut result = 0u32;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*offset)
+            .ok_or_else(|| anyhow!("malformed module: unexpected end of input"))?;
+        *offset += 1;
+        if shift >= 32 {
+            return Err(anyhow!("malformed module: integer too large"));
+        }
+        result |= u32::from(byte & 0x7f) << shift;
+        if 0 == byte & 0x80 {
+            return Ok(result);
+        }
+        shift += 7;
+    }
+}
+
+/// Convert a byte offset of the content into an [`lsp::Position`].
+pub fn position(content: &ropey::Rope, offset: usize) -> lsp::Position {
+    let offset = offset.min(content.len_bytes());
+    let line = content.byte_to_line(offset);
+    let line_utf16_cu = content.char_to_utf16_cu(content.line_to_char(line));
+    let utf16_cu = content.char_to_utf16_cu(content.byte_to_char(offset));
+    lsp::Position::new(line as u32, (utf16_cu - line_utf16_cu) as u32)
+}
+
+/// Convert an [`lsp::Position`] into a byte offset of the content.
+pub fn offset(content: &ropey::Rope, position: lsp::Position) -> Option<usize> {
+    let line = usize::try_from(position.line).ok()?;
+    if line >= content.len_lines() {
+        return None;
+    }
+    let line_utf16_cu = content.char_to_utf16_cu(content.line_to_char(line));
+    let utf16_cu = line_utf16_cu + usize::try_from(position.character).ok()?;
+    if utf16_cu > content.len_utf16_cu() {
+        return None;
+    }
+    Some(content.char_to_byte(content.utf16_cu_to_char(utf16_cu)))
+}
+
+/// Compute the range of the token starting at a byte offset of the content.
+pub fn token_range(content: &ropey::Rope, offset: usize) -> lsp::Range {
+    let offset = offset.min(content.len_bytes());
+    let len = token_len(content, offset).max(1).min(content.len_bytes() - offset);
+    lsp::Range::new(position(content, offset), position(content, offset + len))
+}
+
+/// Compute the length in bytes of the token starting at a byte offset of the content.
+pub fn token_len(content: &ropey::Rope, offset: usize) -> usize {
+    content
+        .slice(content.byte_to_char(offset) ..)
+        .chars()
+        .take_while(|&c| !(c.is_whitespace() || '(' == c || ')' == c))
+        .map(char::len_utf8)
+        .sum()
+}
+
+/// Compute the length in bytes of the opcode of an encoded instruction.
+///
+/// Opcodes of the prefixed instruction families (`0xFB`, `0xFC`, `0xFD`, and `0xFE`) consist of
+/// the prefix followed by a LEB128-encoded sub-opcode.
+pub fn opcode_len(bytes: &[u8]) -> anyhow::Result<usize> {
+    match bytes.first() {
+        None => Err(anyhow!("malformed instruction: missing opcode")),
+        Some(0xfb ..= 0xfe) => {
+            let mut offset = 1;
+            read_u32(bytes, &mut offset)?;
+            Ok(offset)
+        },
+        Some(_) => Ok(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_leb128() {
+        let bytes = [0xe5, 0x8e, 0x26, 0x01];
+        let mut offset = 0;
+        assert_eq!(624485, read_u32(&bytes, &mut offset).unwrap());
+        assert_eq!(3, offset);
+        assert_eq!(1, read_u32(&bytes, &mut offset).unwrap());
+        assert!(read_u32(&bytes, &mut offset).is_err());
+    }
+
+    #[test]
+    fn opcode_lengths() {
+        // `i32.const 42`
+        assert_eq!(1, opcode_len(&[0x41, 0x2a]).unwrap());
+        // `memory.fill 0`
+        assert_eq!(2, opcode_len(&[0xfc, 0x0b, 0x00]).unwrap());
+        // `i32x4.add`
+        assert_eq!(3, opcode_len(&[0xfd, 0xae, 0x01]).unwrap());
+        assert!(opcode_len(&[]).is_err());
+        assert!(opcode_len(&[0xfd, 0xae]).is_err());
+    }
+
+    #[test]
+    fn offset_position_roundtrip() {
+        let content = ropey::Rope::from_str("(module\n  ;; \u{1f600}\n  (func))");
+        for offset in [0, 8, 13, 17, 20] {
+            assert_eq!(Some(offset), super::offset(&content, position(&content, offset)));
+        }
+        assert_eq!(None, super::offset(&content, lsp::Position::new(3, 0)));
+    }
+
+    #[test]
+    fn encode_options() {
+        let content = ropey::Rope::from_str(r#"(module (func $f) (@custom "extra" "data"))"#);
+
+        let options = EncodeOptions::default();
+        let bytes = encode(&content, &options).unwrap().bytes;
+        let names = sections(&bytes)
+            .unwrap()
+            .into_iter()
+            .filter_map(|it| it.name)
+            .collect::<Vec<_>>();
+        assert!(names.contains(&String::from("name")));
+        assert!(names.contains(&String::from("extra")));
+        let kinds = sections(&bytes).unwrap().iter().map(Section::kind).collect::<Vec<_>>();
+        assert_eq!(vec!["type", "func", "code", "names", "custom"], kinds);
+
+        let options = EncodeOptions {
+            name_section: false,
+            custom_sections: false,
+        };
+        let bytes = encode(&content, &options).unwrap().bytes;
+        let names = sections(&bytes)
+            .unwrap()
+            .into_iter()
+            .filter_map(|it| it.name)
+            .collect::<Vec<_>>();
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn encode_failure() {
+        let content = ropey::Rope::from_str("(module\n  (func (call $missing)))");
+        let diagnostic = encode(&content, &EncodeOptions::default()).unwrap_err();
+        assert_eq!(lsp::Position::new(1, 14), diagnostic.range.start);
+        assert_eq!(lsp::Position::new(1, 22), diagnostic.range.end);
+    }
+
+    #[test]
+    fn source_map() {
+        let content = ropey::Rope::from_str(indoc::indoc! {r#"
+            (module
+              (import "env" "f" (func $f))
+              (@custom "before" (before code) "data")
+              (func $g (local i32)
+                (drop (i32.const 42))
+                (call $f)))
+        "#});
+        let text = content.to_string();
+        let encoding = encode(&content, &EncodeOptions::default()).unwrap();
+        let function = encoding.source_map.function(1).unwrap();
+        assert_eq!(text.find("func $g").unwrap(), function.source);
+        assert_eq!(3, function.instructions.len());
+
+        let sources = ["i32.const", "drop", "call"]
+            .iter()
+            .map(|it| text.find(it).unwrap())
+            .collect::<Vec<_>>();
+        // local declarations
+        assert_eq!(Some(function.source), function.lookup(0));
+        // `i32.const 42` (0x41 0x2a), `drop` (0x1a), `call 0` (0x10 0x00)
+        assert_eq!(Some(sources[0]), function.lookup(3));
+        assert_eq!(Some(sources[0]), function.lookup(4));
+        assert_eq!(Some(sources[1]), function.lookup(5));
+        assert_eq!(Some(sources[2]), function.lookup(7));
+        // implicit final `end`
+        assert_eq!(Some(function.source), function.lookup(8));
+        assert_eq!(None, function.lookup(9));
+
+        // removing the custom section preceding the code section shifts the offsets
+        let options = EncodeOptions {
+            custom_sections: false,
+            ..Default::default()
+        };
+        let stripped = encode(&content, &options).unwrap();
+        let body = stripped.source_map.function(1).unwrap().body.clone();
+        assert_eq!(0x1a, stripped.bytes[body.start + 5]);
+        assert_eq!(Some(sources[1]), stripped.source_map.lookup(body.start + 5));
+        assert_eq!(None, stripped.source_map.lookup(0));
+    }
+}
@@ -9,6 +9,21 @@ pub enum Error {
     /// Error that occurs when [`core::Session.client`] is accessed and is `None`.
     #[error("ClientNotInitialzed")]
     ClientNotInitialized,
+    /// Error that occurs when a request was computed against an outdated document version.
+    #[error("core::ContentModified: uri={uri:?}, version={version:?}")]
+    ContentModified {
+        /// The URL of the modified document.
+        uri: lsp::Url,
+        /// The (outdated) version of the document the request was computed against.
+        version: i32,
+    },
+    /// Error that occurs when the parameters of a request are inconsistent.
+    #[error("core::InvalidParams: {0}")]
+    InvalidParams(String),
+    /// Error that occurs when a request was cancelled, either by the client or because the
+    /// document it was computed against has changed.
+    #[error("core::RequestCancelled")]
+    RequestCancelled,
     /// Error that occurs when a session resource is requested and does not exist.
     #[error("core::SessionResourceNotFound: kind={kind:?}, uri={uri:?}")]
     SessionResourceNotFound {
@@ -19,12 +34,44 @@ pub enum Error {
     },
 }
 
+/// The LSP `RequestFailed` error code, for requests which are valid but could not be completed.
+pub const REQUEST_FAILED: i64 = -32803;
+
+/// Determine the [`tower_lsp::jsonrpc::ErrorCode`] reported to the client for `error`.
+pub fn error_code(error: &anyhow::Error) -> tower_lsp::jsonrpc::ErrorCode {
+    use tower_lsp::jsonrpc::ErrorCode;
+    use wasm_lsp_languages::error::Error as LanguagesError;
+    if let Some(error) = error.downcast_ref::<Error>() {
+        return match error {
+            Error::ClientNotInitialized => ErrorCode::InternalError,
+            Error::ContentModified { .. } => ErrorCode::ContentModified,
+            Error::InvalidParams(_) => ErrorCode::InvalidParams,
+            Error::RequestCancelled => ErrorCode::RequestCancelled,
+            Error::SessionResourceNotFound { .. } => ErrorCode::InvalidParams,
+        };
+    }
+    if let Some(error) = error.downcast_ref::<LanguagesError>() {
+        return match error {
+            LanguagesError::InvalidLanguageId(_) => ErrorCode::InvalidParams,
+            LanguagesError::OsStrToStrFailed => ErrorCode::InvalidParams,
+            LanguagesError::PathExtensionFailed(_) => ErrorCode::InvalidParams,
+        };
+    }
+    ErrorCode::ServerError(REQUEST_FAILED)
+}
+
+/// Determine whether `error` was caused by invalid input from the client (as opposed to a
+/// failure of the server).
+pub fn is_client_error(error: &anyhow::Error) -> bool {
+    tower_lsp::jsonrpc::ErrorCode::InvalidParams == error_code(error)
+}
+
 /// Wrapper struct for converting [`anyhow::Error`] into [`tower_lsp::jsonrpc::Error`].
 pub struct IntoJsonRpcError(pub anyhow::Error);
 
 impl From<IntoJsonRpcError> for tower_lsp::jsonrpc::Error {
     fn from(error: IntoJsonRpcError) -> Self {
-        let mut rpc_error = tower_lsp::jsonrpc::Error::internal_error();
+        let mut rpc_error = tower_lsp::jsonrpc::Error::new(error_code(&error.0));
         rpc_error.data = Some(serde_json::to_value(format!("{}", error.0)).unwrap());
         rpc_error
     }
@@ -32,7 +79,7 @@ impl From<IntoJsonRpcError> for tower_lsp::jsonrpc::Error {
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, IntoJsonRpcError};
+    use super::{Error, IntoJsonRpcError, REQUEST_FAILED};
 
     #[test]
     fn from() {
@@ -46,4 +93,84 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn from_content_modified() {
+        let uri = lsp::Url::parse("inmemory:///test").unwrap();
+        let version = 1;
+        let error = Error::ContentModified { uri, version };
+        let error = error.into();
+
+        let mut expected = tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::ContentModified);
+        expected.data = Some(serde_json::to_value(format!("{}", error)).unwrap());
+
+        let actual: tower_lsp::jsonrpc::Error = IntoJsonRpcError(error).into();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn from_invalid_params() {
+        let error = Error::InvalidParams(String::from("`a` and `b` are exclusive"));
+        let error = error.into();
+
+        let mut expected = tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::InvalidParams);
+        expected.data = Some(serde_json::to_value(format!("{}", error)).unwrap());
+
+        let actual: tower_lsp::jsonrpc::Error = IntoJsonRpcError(error).into();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn from_request_cancelled() {
+        let error = Error::RequestCancelled;
+        let error = error.into();
+
+        let mut expected = tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::RequestCancelled);
+        expected.data = Some(serde_json::to_value(format!("{}", error)).unwrap());
+
+        let actual: tower_lsp::jsonrpc::Error = IntoJsonRpcError(error).into();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn from_session_resource_not_found() {
+        let kind = crate::core::SessionResourceKind::Document;
+        let uri = lsp::Url::parse("inmemory:///test").unwrap();
+        let error = Error::SessionResourceNotFound { kind, uri };
+        let error = error.into();
+
+        let mut expected = tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::InvalidParams);
+        expected.data = Some(serde_json::to_value(format!("{}", error)).unwrap());
+
+        let actual: tower_lsp::jsonrpc::Error = IntoJsonRpcError(error).into();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn from_invalid_language_id() {
+        let error = crate::core::Language::try_from("wasm.bogus").unwrap_err();
+
+        let mut expected = tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::InvalidParams);
+        expected.data = Some(serde_json::to_value(format!("{}", error)).unwrap());
+
+        let actual: tower_lsp::jsonrpc::Error = IntoJsonRpcError(error).into();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn from_other() {
+        let error = anyhow::anyhow!("failed");
+
+        let mut expected = tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::ServerError(REQUEST_FAILED));
+        expected.data = Some(serde_json::to_value(format!("{}", error)).unwrap());
+
+        let actual: tower_lsp::jsonrpc::Error = IntoJsonRpcError(error).into();
+
+        assert_eq!(expected, actual);
+    }
 }
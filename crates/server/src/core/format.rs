@@ -0,0 +1,276 @@
+//! Definitions related to formatting `.wat` and `.wast` documents.
+
+use crate::core::FormatSettings;
+
+/// The instructions which open a block of (flat) instructions, closed by `end` (or `delegate`).
+const BLOCK_START: &[&str] = &["block", "if", "loop", "try", "try_table"];
+
+/// The instructions which close a block of (flat) instructions.
+const BLOCK_END: &[&str] = &["delegate", "end"];
+
+/// The instructions which separate the parts of a block of (flat) instructions.
+const BLOCK_SEPARATOR: &[&str] = &["catch", "catch_all", "else"];
+
+/// Format the textual content of a `.wat` or `.wast` document.
+///
+/// Formatting is conservative: the line structure and the tokens of the document are kept as they
+/// are, while each line is re-indented by the nesting of its s-expressions (and of its flat block
+/// instructions), trailing whitespace is removed and the final newline is normalized. Lines which
+/// continue a string or a block comment are left untouched.
+pub fn format(text: &str, settings: &FormatSettings) -> String {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let unit = if settings.use_tabs {
+        String::from("\t")
+    } else {
+        " ".repeat(settings.indent_width as usize)
+    };
+
+    let mut scanner = Scanner::default();
+    let mut lines = vec![];
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        // whitespace in a string or a block comment is content
+        if scanner.in_literal() {
+            scanner.scan(line);
+            lines.push(String::from(line));
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let level = scanner.depth.saturating_sub(dedent(trimmed));
+        scanner.scan(trimmed);
+        let trimmed = if scanner.in_literal() {
+            trimmed
+        } else {
+            trimmed.trim_end()
+        };
+        if trimmed.is_empty() {
+            lines.push(String::new());
+        } else {
+            lines.push(unit.repeat(level) + trimmed);
+        }
+    }
+
+    let mut result = lines.join(newline);
+    if settings.insert_final_newline {
+        let len = result.trim_end_matches(&['\r', '\n'][..]).len();
+        result.truncate(len);
+        if !result.is_empty() {
+            result.push_str(newline);
+        }
+    }
+    result
+}
+
+/// Compute how many levels a line is indented less than the nesting at its start, since it begins
+/// by closing s-expressions or (the part of) a block.
+fn dedent(line: &str) -> usize {
+    let closing = line.bytes().take_while(|&b| b == b')').count();
+    if 0 < closing {
+        return closing;
+    }
+    let end = line.bytes().position(|b| !is_token_byte(b)).unwrap_or(line.len());
+    let token = &line[.. end];
+    usize::from(BLOCK_END.contains(&token) || BLOCK_SEPARATOR.contains(&token))
+}
+
+/// Determine whether the byte `b` can occur in a keyword (or an identifier, or a number). The bytes
+/// of non-ASCII characters always can.
+fn is_token_byte(b: u8) -> bool {
+    !b.is_ascii_whitespace() && !matches!(b, b'(' | b')' | b'"' | b';')
+}
+
+/// Scanner tracking the nesting of a document across lines.
+#[derive(Default)]
+struct Scanner {
+    /// The nesting of s-expressions and of flat block instructions.
+    depth: usize,
+    /// Whether the scanner is within a string.
+    string: bool,
+    /// The nesting of block comments the scanner is within.
+    comment: usize,
+}
+
+impl Scanner {
+    /// Determine whether the scanner is within a string or a block comment.
+    fn in_literal(&self) -> bool {
+        self.string || 0 < self.comment
+    }
+
+    /// Scan a line, updating the nesting.
+    fn scan(&mut self, line: &str) {
+        let bytes = line.as_bytes();
+        let next = |i: usize| bytes.get(i + 1).copied();
+        let mut after_paren = false;
+        let mut i = 0;
+        while i < bytes.len() {
+            if 0 < self.comment {
+                if bytes[i] == b'(' && next(i) == Some(b';') {
+                    self.comment += 1;
+                    i += 2;
+                } else if bytes[i] == b';' && next(i) == Some(b')') {
+                    self.comment -= 1;
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+            if self.string {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b'"' => {
+                        self.string = false;
+                        i += 1;
+                    },
+                    _ => i += 1,
+                }
+                continue;
+            }
+            match bytes[i] {
+                b';' if next(i) == Some(b';') => break,
+                b'(' if next(i) == Some(b';') => {
+                    self.comment = 1;
+                    i += 2;
+                },
+                b'(' => {
+                    self.depth += 1;
+                    after_paren = true;
+                    i += 1;
+                },
+                b')' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    after_paren = false;
+                    i += 1;
+                },
+                b'"' => {
+                    self.string = true;
+                    after_paren = false;
+                    i += 1;
+                },
+                b if b.is_ascii_whitespace() => i += 1,
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && is_token_byte(bytes[i]) {
+                        i += 1;
+                    }
+                    // a lone `;` is not part of any token
+                    if start == i {
+                        i += 1;
+                        continue;
+                    }
+                    // folded block instructions are already nested by their s-expression
+                    if !after_paren {
+                        let token = &line[start .. i];
+                        if BLOCK_START.contains(&token) {
+                            self.depth += 1;
+                        } else if BLOCK_END.contains(&token) {
+                            self.depth = self.depth.saturating_sub(1);
+                        }
+                    }
+                    after_paren = false;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+    use crate::core::FormatSettings;
+
+    #[test]
+    fn indentation() {
+        let text = "(module\n(func $f (param i32)\n        (drop\n(local.get 0))))\n";
+        let expected = "(module\n  (func $f (param i32)\n    (drop\n      (local.get 0))))\n";
+        assert_eq!(expected, format(text, &FormatSettings::default()));
+    }
+
+    #[test]
+    fn flat_blocks() {
+        let text = "(module\n(func\nblock $b\nloop\nbr 1\nend\nend\ni32.const 0\nif\nnop\nelse\nnop\nend))\n";
+        let expected = [
+            "(module",
+            "  (func",
+            "    block $b",
+            "      loop",
+            "        br 1",
+            "      end",
+            "    end",
+            "    i32.const 0",
+            "    if",
+            "      nop",
+            "    else",
+            "      nop",
+            "    end))",
+            "",
+        ];
+        assert_eq!(expected.join("\n"), format(text, &FormatSettings::default()));
+    }
+
+    #[test]
+    fn try_table() {
+        let text = "(func\ntry_table (result i32)\n(catch $e 0)\ncall $f\nend\ndrop)\n";
+        let expected = [
+            "(func",
+            "  try_table (result i32)",
+            "    (catch $e 0)",
+            "    call $f",
+            "  end",
+            "  drop)",
+            "",
+        ];
+        assert_eq!(expected.join("\n"), format(text, &FormatSettings::default()));
+    }
+
+    #[test]
+    fn folded_blocks() {
+        let text = "(func\n(block $b\n(if (then\nnop)\n(else\nnop))))";
+        let expected = "(func\n  (block $b\n    (if (then\n        nop)\n      (else\n        nop))))\n";
+        assert_eq!(expected, format(text, &FormatSettings::default()));
+    }
+
+    #[test]
+    fn literals() {
+        let text = "(module ;; (func\n  (; (block\n      comment ;)\n(data \"(\" \"\\\")\"))   \n";
+        let expected = "(module ;; (func\n  (; (block\n      comment ;)\n  (data \"(\" \"\\\")\"))\n";
+        assert_eq!(expected, format(text, &FormatSettings::default()));
+    }
+
+    #[test]
+    fn settings() {
+        let settings = FormatSettings {
+            indent_width: 4,
+            use_tabs: true,
+            insert_final_newline: false,
+        };
+        assert_eq!("(module\n\t(func))", format("(module\n  (func))", &settings));
+        let settings = FormatSettings {
+            use_tabs: false,
+            ..settings
+        };
+        assert_eq!(
+            "(module\r\n    (func))\r\n",
+            format("(module\r\n(func))\r\n", &settings)
+        );
+    }
+
+    #[test]
+    fn unicode() {
+        let text = "(module\n(export \"\u{85}\" (func $\u{e9}\u{a0})))";
+        let expected = "(module\n  (export \"\u{85}\" (func $\u{e9}\u{a0})))\n";
+        assert_eq!(expected, format(text, &FormatSettings::default()));
+    }
+
+    #[test]
+    fn idempotent() {
+        let text = "(module\n(func\nblock\nnop\nend)\n\n\n(memory 1))\n\n";
+        let settings = FormatSettings::default();
+        let formatted = format(text, &settings);
+        assert_eq!(
+            "(module\n  (func\n    block\n      nop\n    end)\n\n\n  (memory 1))\n",
+            formatted
+        );
+        assert_eq!(formatted, format(&formatted, &settings));
+    }
+}
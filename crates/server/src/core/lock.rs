@@ -1 +0,0 @@
-pub use async_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -0,0 +1,374 @@
+//! Definitions related to the project configuration files (`.wasm-lsp.toml`).
+
+use crate::core::{settings::merge, Settings};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The name of the project configuration file.
+pub const FILE_NAME: &str = ".wasm-lsp.toml";
+
+/// The keys of a project configuration (or of one of its overrides) which configure [`Settings`].
+const SETTINGS_KEYS: &[&str] = &[
+    "diagnostics",
+    "format",
+    "host",
+    "hosts",
+    "inlayHints",
+    "proposals",
+    "semanticTokens",
+];
+
+/// A project configuration, as read from a `.wasm-lsp.toml` file.
+///
+/// The configuration applies to the documents in the directory of the file (and its
+/// subdirectories). Its settings take precedence over the settings of the client, and its
+/// overrides take precedence over its settings for the documents they match:
+///
+/// ```toml
+/// host = "wasi-preview1"
+///
+/// [proposals]
+/// gc = false
+///
+/// [modules]
+/// env = "lib/env.wat"
+///
+/// [[overrides]]
+/// files = ["tests/**/*.wat"]
+/// diagnostics = { severities = { unknown-index = "warning" } }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Project {
+    /// The URL of the configuration file.
+    pub uri: lsp::Url,
+    /// The files implementing the modules which are imported from, by module name.
+    pub modules: HashMap<String, lsp::Url>,
+    /// The diagnostics for the malformed entries of the configuration file.
+    pub diagnostics: Vec<lsp::Diagnostic>,
+    root: PathBuf,
+    settings: serde_json::Value,
+    overrides: Vec<Override>,
+}
+
+/// Settings which apply to the documents matching one of the given patterns.
+#[derive(Clone, Debug)]
+struct Override {
+    patterns: Vec<glob::Pattern>,
+    settings: serde_json::Value,
+}
+
+impl Project {
+    /// Parse a project configuration from the textual content of the file at `uri`.
+    ///
+    /// Malformed entries are skipped and reported in [`Project::diagnostics`].
+    pub fn parse(uri: lsp::Url, text: &str) -> Self {
+        let root = Path::new(uri.path())
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let mut project = Project {
+            uri,
+            modules: HashMap::default(),
+            diagnostics: vec![],
+            root,
+            settings: serde_json::Value::Object(Default::default()),
+            overrides: vec![],
+        };
+
+        let table = match text.parse::<toml::Value>() {
+            Ok(toml::Value::Table(table)) => table,
+            Ok(_) => return project,
+            Err(error) => {
+                let (line, character) = error.line_col().unwrap_or_default();
+                let position = lsp::Position::new(line as u32, character as u32);
+                let range = lsp::Range::new(position, position);
+                project.diagnostics.push(diagnostic(range, error.to_string()));
+                return project;
+            },
+        };
+
+        let mut checker = Checker {
+            text,
+            diagnostics: vec![],
+        };
+        for (key, value) in table {
+            match key.as_str() {
+                "modules" => project.modules = checker.modules(&project.uri, value),
+                "overrides" => project.overrides = checker.overrides(value),
+                _ => {
+                    if let Some(value) = checker.setting(&key, value) {
+                        project.settings[key.as_str()] = value;
+                    }
+                },
+            }
+        }
+        checker.host(&project.settings);
+
+        project.diagnostics = checker.diagnostics;
+        project
+    }
+
+    /// Compute the settings for the document at `uri` by layering the settings of the project
+    /// (and of the overrides matching the document) over the given settings.
+    pub fn settings(&self, base: &Settings, uri: &lsp::Url) -> Settings {
+        let mut value = serde_json::to_value(base).unwrap_or_default();
+        merge(&mut value, &self.settings);
+        if let Ok(path) = Path::new(uri.path()).strip_prefix(&self.root) {
+            let options = glob::MatchOptions {
+                require_literal_separator: true,
+                ..Default::default()
+            };
+            for entry in &self.overrides {
+                if entry.patterns.iter().any(|it| it.matches_path_with(path, options)) {
+                    merge(&mut value, &entry.settings);
+                }
+            }
+        }
+        serde_json::from_value(value).unwrap_or_else(|_| base.clone())
+    }
+}
+
+/// Validates the entries of a project configuration, collecting diagnostics for malformed ones.
+struct Checker<'text> {
+    text: &'text str,
+    diagnostics: Vec<lsp::Diagnostic>,
+}
+
+impl<'text> Checker<'text> {
+    /// Validate a settings entry, returning its value if it is well-formed.
+    fn setting(&mut self, key: &str, value: toml::Value) -> Option<serde_json::Value> {
+        if !SETTINGS_KEYS.contains(&key) {
+            self.report(key, format!("unknown key `{}`", key));
+            return None;
+        }
+        let value = serde_json::to_value(value).ok()?;
+        let settings = serde_json::json!({ key: value.clone() });
+        if let Err(error) = serde_json::from_value::<Settings>(settings) {
+            self.report(key, format!("invalid `{}`: {}", key, error));
+            return None;
+        }
+        Some(value)
+    }
+
+    /// Validate the `modules` entry, resolving the files relative to the configuration file.
+    fn modules(&mut self, uri: &lsp::Url, value: toml::Value) -> HashMap<String, lsp::Url> {
+        let mut modules = HashMap::default();
+        let table = match value {
+            toml::Value::Table(table) => table,
+            _ => {
+                self.report(
+                    "modules",
+                    String::from("`modules` must be a table of module names to files"),
+                );
+                return modules;
+            },
+        };
+        for (name, path) in table {
+            let file = match path.as_str().map(|path| uri.join(path)) {
+                Some(Ok(file)) => file,
+                _ => {
+                    self.report(&name, format!("the file of module `{}` must be a path", name));
+                    continue;
+                },
+            };
+            if !exists(&file) {
+                self.report(&name, format!("the file of module `{}` does not exist", name));
+            }
+            modules.insert(name, file);
+        }
+        modules
+    }
+
+    /// Validate the `overrides` entry.
+    fn overrides(&mut self, value: toml::Value) -> Vec<Override> {
+        let mut overrides = vec![];
+        let entries = match value {
+            toml::Value::Array(entries) => entries,
+            _ => {
+                self.report("overrides", String::from("`overrides` must be an array of tables"));
+                return overrides;
+            },
+        };
+        for entry in entries {
+            let table = match entry {
+                toml::Value::Table(table) => table,
+                _ => {
+                    self.report("overrides", String::from("each of the `overrides` must be a table"));
+                    continue;
+                },
+            };
+            let mut patterns = None;
+            let mut settings = serde_json::Value::Object(Default::default());
+            for (key, value) in table {
+                if "files" == key {
+                    patterns = self.patterns(value);
+                } else if let Some(value) = self.setting(&key, value) {
+                    settings[key.as_str()] = value;
+                }
+            }
+            match patterns {
+                Some(patterns) => overrides.push(Override { patterns, settings }),
+                None => self.report("overrides", String::from("each of the `overrides` requires `files`")),
+            }
+        }
+        overrides
+    }
+
+    /// Validate the `files` patterns of an override.
+    fn patterns(&mut self, value: toml::Value) -> Option<Vec<glob::Pattern>> {
+        let values = match value {
+            toml::Value::String(pattern) => vec![toml::Value::String(pattern)],
+            toml::Value::Array(values) => values,
+            _ => {
+                self.report(
+                    "files",
+                    String::from("`files` must be a glob pattern or an array of them"),
+                );
+                return None;
+            },
+        };
+        let mut patterns = vec![];
+        for value in values {
+            let pattern = value.as_str().unwrap_or_default();
+            match glob::Pattern::new(pattern) {
+                Ok(pattern) => patterns.push(pattern),
+                Err(error) => self.report(pattern, format!("invalid glob pattern `{}`: {}", pattern, error)),
+            }
+        }
+        Some(patterns)
+    }
+
+    /// Validate that the selected host profile is either built in or defined in `hosts`.
+    fn host(&mut self, settings: &serde_json::Value) {
+        if let Some(host) = settings.get("host").and_then(serde_json::Value::as_str) {
+            let defined = settings.get("hosts").and_then(|hosts| hosts.get(host)).is_some();
+            if !defined && crate::core::HostProfile::builtin(host).is_none() {
+                self.report("host", format!("unknown host profile `{}`", host));
+            }
+        }
+    }
+
+    /// Report a diagnostic at the first occurrence of `needle` in the configuration file.
+    fn report(&mut self, needle: &str, message: String) {
+        let range = locate(self.text, needle);
+        self.diagnostics.push(diagnostic(range, message));
+    }
+}
+
+/// Construct a diagnostic for a malformed entry of a project configuration.
+fn diagnostic(range: lsp::Range, message: String) -> lsp::Diagnostic {
+    lsp::Diagnostic {
+        range,
+        severity: Some(lsp::DiagnosticSeverity::ERROR),
+        source: Some(String::from(FILE_NAME)),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Compute the range of the first occurrence of `needle` in `text` (or the start of the text).
+fn locate(text: &str, needle: &str) -> lsp::Range {
+    for (line, content) in text.lines().enumerate() {
+        if let Some(offset) = content.find(needle) {
+            let start = content[.. offset].encode_utf16().count() as u32;
+            let end = start + needle.encode_utf16().count() as u32;
+            let line = line as u32;
+            return lsp::Range::new(lsp::Position::new(line, start), lsp::Position::new(line, end));
+        }
+    }
+    Default::default()
+}
+
+/// Find the project configuration file governing the document at `uri`, in the directory of the
+/// document or in one of its ancestors.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn find(uri: &lsp::Url) -> Option<lsp::Url> {
+    let path = uri.to_file_path().ok()?;
+    path.ancestors()
+        .skip(1)
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|file| file.is_file())
+        .and_then(|file| lsp::Url::from_file_path(file).ok())
+}
+
+/// Find the project configuration file governing the document at `uri`, in the directory of the
+/// document or in one of its ancestors.
+#[cfg(target_arch = "wasm32")]
+pub fn find(_uri: &lsp::Url) -> Option<lsp::Url> {
+    None
+}
+
+/// Determine whether the file at `uri` exists.
+#[cfg(not(target_arch = "wasm32"))]
+fn exists(uri: &lsp::Url) -> bool {
+    uri.to_file_path().map_or(false, |path| path.is_file())
+}
+
+/// Determine whether the file at `uri` exists.
+#[cfg(target_arch = "wasm32")]
+fn exists(_uri: &lsp::Url) -> bool {
+    true
+}
+
+/// Determine whether the file at `uri` is a project configuration file.
+pub fn is_project(uri: &lsp::Url) -> bool {
+    uri.path().ends_with(FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Project;
+    use crate::core::{Rule, Settings};
+
+    fn uri(path: &str) -> lsp::Url {
+        lsp::Url::parse(&format!("file:///project/{}", path)).unwrap()
+    }
+
+    #[test]
+    fn settings() {
+        let text = indoc::indoc! {r#"
+            [proposals]
+            gc = false
+
+            [[overrides]]
+            files = ["tests/**/*.wat"]
+            diagnostics = { severities = { unknown-index = "off" } }
+        "#};
+        let project = Project::parse(uri(".wasm-lsp.toml"), text);
+        assert!(project.diagnostics.is_empty());
+
+        let settings = project.settings(&Settings::default(), &uri("src/lib.wat"));
+        assert!(!settings.proposals.gc);
+        assert!(settings.diagnostics.severity(Rule::UnknownIndex).is_some());
+
+        let settings = project.settings(&Settings::default(), &uri("tests/unit/lib.wat"));
+        assert!(!settings.proposals.gc);
+        assert!(settings.diagnostics.severity(Rule::UnknownIndex).is_none());
+    }
+
+    #[test]
+    fn diagnostics() {
+        let text = indoc::indoc! {r#"
+            host = "nowhere"
+
+            [proposals]
+            gc = "no"
+        "#};
+        let project = Project::parse(uri(".wasm-lsp.toml"), text);
+        let lines = project
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range.start.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 0]);
+    }
+
+    #[test]
+    fn syntax_error() {
+        let project = Project::parse(uri(".wasm-lsp.toml"), "[proposals\n");
+        assert_eq!(project.diagnostics.len(), 1);
+        assert_eq!(project.diagnostics[0].range.start.line, 0);
+    }
+}
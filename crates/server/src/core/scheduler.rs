@@ -0,0 +1,188 @@
+//! Definitions related to scheduling the computation of diagnostics.
+
+use async_lock::{Mutex, RwLock};
+use futures::future::{AbortHandle, Abortable, BoxFuture, FutureExt};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Function used to spawn background tasks onto the runtime hosting the server.
+pub type Spawner = Arc<dyn Fn(BoxFuture<'static, ()>) + Send + Sync>;
+
+/// The default delay between a change to a document and the computation of its diagnostics.
+pub const DEFAULT_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Scheduler for computing and publishing the diagnostics of documents.
+///
+/// When constructed with a [`Spawner`], diagnostics are computed in the background after a
+/// debounce delay, and pending work for a document is cancelled when a newer version arrives
+/// (including work which is already walking the syntax tree of the document). Otherwise
+/// diagnostics are computed immediately as part of the notification handler.
+pub struct DiagnosticsScheduler {
+    spawner: Option<Spawner>,
+    debounce: RwLock<Duration>,
+    pending: Mutex<HashMap<lsp::Url, Pending>>,
+    generation: AtomicU64,
+}
+
+/// The pending computation of diagnostics for a document.
+struct Pending {
+    generation: u64,
+    handle: AbortHandle,
+    token: crate::core::CancellationToken,
+}
+
+impl Pending {
+    fn cancel(self) {
+        self.handle.abort();
+        self.token.cancel();
+    }
+}
+
+impl DiagnosticsScheduler {
+    /// Create a new [`DiagnosticsScheduler`].
+    pub fn new(spawner: Option<Spawner>) -> Self {
+        let debounce = RwLock::new(DEFAULT_DIAGNOSTICS_DEBOUNCE);
+        let pending = Mutex::new(HashMap::default());
+        let generation = AtomicU64::new(0);
+        DiagnosticsScheduler {
+            spawner,
+            debounce,
+            pending,
+            generation,
+        }
+    }
+
+    /// Retrieve the current debounce delay.
+    pub async fn debounce(&self) -> Duration {
+        *self.debounce.read().await
+    }
+
+    /// Configure the debounce delay.
+    pub async fn set_debounce(&self, debounce: Duration) {
+        *self.debounce.write().await = debounce;
+    }
+
+    /// Schedule the computation of diagnostics for `version` of the document at `uri`.
+    pub async fn schedule(
+        &self,
+        session: Arc<crate::core::Session>,
+        uri: lsp::Url,
+        version: i32,
+    ) -> anyhow::Result<()> {
+        let token = crate::core::CancellationToken::new();
+        if let Some(spawner) = &self.spawner {
+            let (handle, registration) = AbortHandle::new_pair();
+            let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+            let pending = Pending {
+                generation,
+                handle,
+                token: token.clone(),
+            };
+            if let Some(pending) = self.pending.lock().await.insert(uri.clone(), pending) {
+                pending.cancel();
+            }
+            let debounce = self.debounce().await;
+            let task = async move {
+                futures_timer::Delay::new(debounce).await;
+                if let Err(err) = run(session.clone(), &uri, version, &token, vec![]).await {
+                    log::error!(
+                        "diagnostics failed :: uri: {}, version: {}, error: {}",
+                        uri,
+                        version,
+                        err
+                    );
+                }
+                session.diagnostics.finish(&uri, generation).await;
+            };
+            spawner(Abortable::new(task, registration).map(drop).boxed());
+            Ok(())
+        } else {
+            run(session, &uri, version, &token, vec![]).await
+        }
+    }
+
+    /// Compute and publish the diagnostics for `version` of the document at `uri` immediately,
+    /// together with the `extra` diagnostics (e.g., the failure to encode the document), replacing
+    /// any pending computation.
+    pub async fn publish(
+        &self,
+        session: Arc<crate::core::Session>,
+        uri: lsp::Url,
+        version: i32,
+        extra: Vec<lsp::Diagnostic>,
+    ) -> anyhow::Result<()> {
+        self.cancel(&uri).await;
+        let token = crate::core::CancellationToken::new();
+        run(session, &uri, version, &token, extra).await
+    }
+
+    /// Cancel any pending computation of diagnostics for the document at `uri`.
+    pub async fn cancel(&self, uri: &lsp::Url) {
+        if let Some(pending) = self.pending.lock().await.remove(uri) {
+            pending.cancel();
+        }
+    }
+
+    /// Remove the finished computation of diagnostics with the given `generation` for the document
+    /// at `uri` (unless it has been superseded in the meantime).
+    async fn finish(&self, uri: &lsp::Url, generation: u64) {
+        let mut pending = self.pending.lock().await;
+        if pending.get(uri).map(|it| it.generation) == Some(generation) {
+            pending.remove(uri);
+        }
+    }
+}
+
+/// Compute and publish the diagnostics for a document in stages (syntax first, then semantics),
+/// stopping early if the document moves past `version` or `token` is cancelled. The `extra`
+/// diagnostics are published along with those of each stage.
+async fn run(
+    session: Arc<crate::core::Session>,
+    uri: &lsp::Url,
+    version: i32,
+    token: &crate::core::CancellationToken,
+    extra: Vec<lsp::Diagnostic>,
+) -> anyhow::Result<()> {
+    let client = session.client()?;
+    let settings = session.document_settings(uri).await;
+    let snapshot = session.get_snapshot(uri).await?;
+    if snapshot.version() != version {
+        return Ok(());
+    }
+    let token = token.with_snapshot(snapshot.clone());
+
+    // syntax stage
+    let syntax = crate::provider::text_document::diagnostics(&snapshot.tree, &snapshot.text, &settings, &token);
+    if token.is_cancelled() {
+        return Ok(());
+    }
+    let mut syntax = syntax?;
+    syntax.extend(extra);
+    client
+        .publish_diagnostics(uri.clone(), syntax.clone(), Some(version))
+        .await;
+
+    // semantic stage
+    let semantic = snapshot.analysis(&token).and_then(|analysis| {
+        crate::provider::text_document::semantic_diagnostics(&snapshot.text, &analysis, &settings, &token)
+    });
+    if token.is_cancelled() {
+        return Ok(());
+    }
+    let semantic = semantic?;
+    if !semantic.is_empty() {
+        let mut diagnostics = syntax;
+        diagnostics.extend(semantic);
+        client
+            .publish_diagnostics(uri.clone(), diagnostics, Some(version))
+            .await;
+    }
+
+    Ok(())
+}
@@ -1,68 +1,247 @@
 //! Definitions related to the LSP session.
 
-use crate::core::MapExt;
-use async_lock::{Mutex, RwLock};
+use async_lock::RwLock;
+use futures::{channel::oneshot, future::FutureExt};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        OnceLock,
+    },
+};
 
 /// The LSP server session. This contains the relevant state for workspace.
 pub struct Session {
-    /// The pre-loaded tree-sitter languages for `.wast` and `.wat`.
+    /// The pre-loaded tree-sitter languages for components, `.wast`, `.wat` and `.wit`.
     pub languages: SessionLanguages,
     /// The current server LSP capabilities configuration.
     pub server_capabilities: RwLock<lsp::ServerCapabilities>,
     /// The current client LSP capabilities configuration.
     pub client_capabilities: RwLock<Option<lsp::ClientCapabilities>>,
-    client: Option<tower_lsp::Client>,
-    texts: crate::core::Map<lsp::Url, crate::core::Text>,
-    parsers: crate::core::Map<lsp::Url, Mutex<tree_sitter::Parser>>,
-    trees: crate::core::Map<lsp::Url, Mutex<tree_sitter::Tree>>,
+    /// The root URLs of the workspace folders.
+    pub workspace_folders: RwLock<Vec<lsp::Url>>,
+    /// The scheduler for computing and publishing diagnostics.
+    pub diagnostics: crate::core::DiagnosticsScheduler,
+    /// The current settings of the server.
+    pub settings: RwLock<crate::core::Settings>,
+    /// The project configurations (`.wasm-lsp.toml`) which have been loaded, by URL.
+    pub projects: RwLock<HashMap<lsp::Url, Arc<crate::core::project::Project>>>,
+    /// The cached index of the workspace files and the diagnostics last computed for them.
+    pub workspace: RwLock<crate::core::workspace::Cache>,
+    client: OnceLock<tower_lsp::Client>,
+    spawner: Option<crate::core::Spawner>,
+    diagnostic_client_capabilities: OnceLock<crate::ext::DiagnosticClientCapabilities>,
+    diagnostic_pull: AtomicBool,
+    documents: RwLock<HashMap<lsp::Url, Arc<crate::core::DocumentState>>>,
 }
 
 impl Session {
     /// Create a new [`Session`].
-    pub fn new(languages: SessionLanguages, client: Option<tower_lsp::Client>) -> anyhow::Result<Self> {
+    pub fn new(
+        languages: SessionLanguages,
+        client: Option<tower_lsp::Client>,
+        spawner: Option<crate::core::Spawner>,
+    ) -> anyhow::Result<Self> {
         let server_capabilities = RwLock::new(crate::Server::capabilities());
         let client_capabilities = RwLock::new(Default::default());
-        let texts = crate::core::Map::default();
-        let parsers = crate::core::Map::default();
-        let trees = crate::core::Map::default();
+        let workspace_folders = RwLock::new(Default::default());
+        let diagnostics = crate::core::DiagnosticsScheduler::new(spawner.clone());
+        let settings = RwLock::new(Default::default());
+        let projects = RwLock::new(Default::default());
+        let workspace = RwLock::new(Default::default());
+        let diagnostic_client_capabilities = OnceLock::new();
+        let diagnostic_pull = AtomicBool::new(false);
+        let documents = RwLock::new(Default::default());
+        let client = client.map(OnceLock::from).unwrap_or_default();
         Ok(Session {
             languages,
             server_capabilities,
             client_capabilities,
+            workspace_folders,
+            diagnostics,
+            settings,
+            projects,
+            workspace,
             client,
-            texts,
-            parsers,
-            trees,
+            spawner,
+            diagnostic_client_capabilities,
+            diagnostic_pull,
+            documents,
         })
     }
 
     /// Retrieve the handle for the LSP client.
     pub fn client(&self) -> anyhow::Result<&tower_lsp::Client> {
         self.client
-            .as_ref()
+            .get()
             .ok_or_else(|| crate::core::Error::ClientNotInitialized.into())
     }
 
-    /// Insert a [`crate::core::Document`] into the [`Session`].
-    pub async fn insert_document(&self, uri: lsp::Url, document: crate::core::Document) -> anyhow::Result<()> {
-        let text = document.text();
-        let result = self.texts.insert(uri.clone(), text).await;
-        debug_assert!(result.is_none());
-        let result = self.parsers.insert(uri.clone(), Mutex::new(document.parser)).await;
-        debug_assert!(result.is_none());
-        let result = self.trees.insert(uri, Mutex::new(document.tree)).await;
-        debug_assert!(result.is_none());
-        Ok(())
+    /// Set the handle for the LSP client, for a [`Session`] created without one. The handle of a
+    /// [`Session`] which already has one is kept.
+    pub fn set_client(&self, client: tower_lsp::Client) {
+        let _ = self.client.set(client);
+    }
+
+    /// Run the `work` of a request which observes `token`.
+    ///
+    /// When the [`Session`] was created with a [`crate::core::Spawner`], the work runs in the
+    /// background so that further messages (e.g., `$/cancelRequest`) are processed meanwhile. If
+    /// the request is dropped before the work completes (which is how the client cancelling the
+    /// request surfaces), `token` is cancelled so that the work stops early.
+    pub async fn run<T>(
+        &self,
+        token: &crate::core::CancellationToken,
+        work: impl Future<Output = anyhow::Result<T>> + Send + 'static,
+    ) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+    {
+        let spawner = match &self.spawner {
+            Some(spawner) => spawner,
+            None => return work.await,
+        };
+        let _guard = token.drop_guard();
+        let (sender, receiver) = oneshot::channel();
+        spawner(
+            async move {
+                sender.send(work.await).ok();
+            }
+            .boxed(),
+        );
+        receiver
+            .await
+            .unwrap_or_else(|_| Err(crate::core::Error::RequestCancelled.into()))
+    }
+
+    /// Insert a [`crate::core::Document`] into the [`Session`], returning the state of the
+    /// document previously open at `uri` (if any).
+    pub async fn insert_document(
+        &self,
+        uri: lsp::Url,
+        document: crate::core::Document,
+    ) -> Option<Arc<crate::core::DocumentState>> {
+        let state = Arc::new(crate::core::DocumentState::new(uri.clone(), document));
+        self.documents.write().await.insert(uri, state)
+    }
+
+    /// Remove a [`crate::core::Document`] from the [`Session`], returning its state (if it was
+    /// open).
+    pub async fn remove_document(&self, uri: &lsp::Url) -> Option<Arc<crate::core::DocumentState>> {
+        self.documents.write().await.remove(uri)
+    }
+
+    /// Determine whether a [`crate::core::Document`] for `uri` is in the [`Session`].
+    pub async fn contains_document(&self, uri: &lsp::Url) -> bool {
+        self.documents.read().await.contains_key(uri)
+    }
+
+    /// Run the blocking `work` (e.g., accessing the file system) on the [`crate::core::Spawner`],
+    /// so that it does not block the executor processing the messages from the client. When the
+    /// [`Session`] was created without a [`crate::core::Spawner`], the work runs in place.
+    pub async fn unblock<T>(&self, work: impl FnOnce() -> anyhow::Result<T> + Send + 'static) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+    {
+        let spawner = match &self.spawner {
+            Some(spawner) => spawner,
+            None => return work(),
+        };
+        let (sender, receiver) = oneshot::channel();
+        spawner(
+            async move {
+                sender.send(work()).ok();
+            }
+            .boxed(),
+        );
+        receiver
+            .await
+            .unwrap_or_else(|_| Err(crate::core::Error::RequestCancelled.into()))
+    }
+
+    /// Retrieve the URLs of the `.wast`, `.wat` and `.wit` files of the workspace folders, indexing
+    /// the folders (see [`Session::unblock`]) if they have not been indexed yet.
+    pub async fn workspace_files(&self) -> anyhow::Result<Vec<lsp::Url>> {
+        if let Some(files) = self.workspace.read().await.files() {
+            return Ok(files);
+        }
+        let folders = self.workspace_folders.read().await.clone();
+        let files = self
+            .unblock(move || Ok(crate::core::workspace::index(&folders)))
+            .await?;
+        self.workspace.write().await.insert_files(files.clone());
+        Ok(files)
+    }
+
+    /// Read the textual content of the workspace file at `uri` (see [`Session::unblock`]).
+    pub async fn read_workspace_file(&self, uri: &lsp::Url) -> anyhow::Result<String> {
+        let uri = uri.clone();
+        self.unblock(move || crate::core::workspace::read(&uri)).await
+    }
+
+    /// Retrieve the URLs of the [`crate::core::Document`]s which are currently open.
+    pub async fn document_uris(&self) -> Vec<lsp::Url> {
+        self.documents.read().await.keys().cloned().collect()
+    }
+
+    /// Retrieve the client capabilities for the pull model for diagnostics, if the client
+    /// supports it.
+    ///
+    /// These are not represented by `lsp-types` 0.93, so they are recorded by [`crate::Service`]
+    /// from the raw `initialize` request (see [`Session::set_diagnostic_client_capabilities`]).
+    pub fn diagnostic_client_capabilities(&self) -> Option<&crate::ext::DiagnosticClientCapabilities> {
+        self.diagnostic_client_capabilities.get()
+    }
+
+    /// Record the client capabilities for the pull model for diagnostics. The capabilities which
+    /// have already been recorded are kept.
+    pub fn set_diagnostic_client_capabilities(&self, capabilities: crate::ext::DiagnosticClientCapabilities) {
+        let _ = self.diagnostic_client_capabilities.set(capabilities);
+    }
+
+    /// Determine whether the client requests diagnostics itself (pull model) rather than having
+    /// them published by the server (push model), as decided when initializing the session.
+    pub async fn diagnostic_pull_support(&self) -> bool {
+        self.diagnostic_pull.load(Ordering::Acquire)
     }
 
-    /// Remove a [`crate::core::Document`] from the [`Session`].
-    pub async fn remove_document(&self, uri: &lsp::Url) -> anyhow::Result<()> {
-        let result = self.texts.remove(uri).await;
-        debug_assert!(result.is_some());
-        let result = self.parsers.remove(uri).await;
-        debug_assert!(result.is_some());
-        let result = self.trees.remove(uri).await;
-        debug_assert!(result.is_some());
+    /// Record whether the client requests diagnostics itself (pull model).
+    pub fn set_diagnostic_pull_support(&self, support: bool) {
+        self.diagnostic_pull.store(support, Ordering::Release);
+    }
+
+    /// Determine whether the client supports the dynamic registration of the
+    /// `workspace/didChangeConfiguration` notification.
+    pub async fn configuration_registration_support(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|capabilities| capabilities.did_change_configuration.as_ref())
+            .and_then(|capabilities| capabilities.dynamic_registration)
+            .unwrap_or_default()
+    }
+
+    /// Determine whether the client supports pulling settings via `workspace/configuration`.
+    pub async fn configuration_support(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|capabilities| capabilities.configuration)
+            .unwrap_or_default()
+    }
+
+    /// Ensure that the [`crate::core::Document`] for `uri` is still at `version`, failing with
+    /// [`crate::core::Error::ContentModified`] otherwise.
+    pub async fn ensure_version(&self, uri: &lsp::Url, version: i32) -> anyhow::Result<()> {
+        let current = self.get_snapshot(uri).await?.version();
+        if current != version {
+            let uri = uri.clone();
+            return Err(crate::core::Error::ContentModified { uri, version }.into());
+        }
         Ok(())
     }
 
@@ -81,78 +260,132 @@ impl Session {
         }
     }
 
-    /// Get a reference to the [`crate::core::Text`] for a [`crate::core::Document`] in the
-    /// [`Session`].
-    pub async fn get_text<'a>(
-        &'a self,
-        uri: &'a lsp::Url,
-    ) -> anyhow::Result<crate::core::Ref<'a, lsp::Url, crate::core::Text>> {
-        self.texts.get(uri).await.ok_or_else(|| {
-            let kind = SessionResourceKind::Document;
-            let uri = uri.clone();
-            crate::core::Error::SessionResourceNotFound { kind, uri }.into()
-        })
+    /// Determine whether the client supports semantic tokens which span multiple lines.
+    pub async fn semantic_tokens_multiline_support(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.text_document.as_ref())
+            .and_then(|capabilities| capabilities.semantic_tokens.as_ref())
+            .and_then(|capabilities| capabilities.multiline_token_support)
+            .unwrap_or_default()
     }
 
-    /// Get a mutable reference to the [`crate::core::Text`] for a [`crate::core::Document`] in the
-    /// [`Session`].
-    pub async fn get_mut_text<'a>(
-        &'a self,
-        uri: &'a lsp::Url,
-    ) -> anyhow::Result<crate::core::RefMut<'a, lsp::Url, crate::core::Text>> {
-        self.texts.get_mut(uri).await.ok_or_else(|| {
-            let kind = SessionResourceKind::Document;
-            let uri = uri.clone();
-            crate::core::Error::SessionResourceNotFound { kind, uri }.into()
-        })
+    /// Determine whether the client supports refreshing semantic tokens via
+    /// `workspace/semanticTokens/refresh`.
+    pub async fn semantic_tokens_refresh_support(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|capabilities| capabilities.semantic_tokens.as_ref())
+            .and_then(|capabilities| capabilities.refresh_support)
+            .unwrap_or_default()
     }
 
-    /// Get a mutable reference to the [`tree_sitter::Parser`] for a [`crate::core::Document`] in
-    /// the [`Session`].
-    pub async fn get_mut_parser<'a>(
-        &'a self,
-        uri: &'a lsp::Url,
-    ) -> anyhow::Result<crate::core::RefMut<'a, lsp::Url, Mutex<tree_sitter::Parser>>> {
-        self.parsers.get_mut(uri).await.ok_or_else(|| {
-            let kind = SessionResourceKind::Parser;
-            let uri = uri.clone();
-            crate::core::Error::SessionResourceNotFound { kind, uri }.into()
-        })
+    /// Determine whether the client supports refreshing inlay hints via
+    /// `workspace/inlayHint/refresh`.
+    pub async fn inlay_hint_refresh_support(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|capabilities| capabilities.inlay_hint.as_ref())
+            .and_then(|capabilities| capabilities.refresh_support)
+            .unwrap_or_default()
     }
 
-    /// Get a reference to the [`tree_sitter::Tree`] for a [`crate::core::Document`] in the
-    /// [`Session`].
-    pub async fn get_tree<'a>(
-        &'a self,
-        uri: &'a lsp::Url,
-    ) -> anyhow::Result<crate::core::Ref<'a, lsp::Url, Mutex<tree_sitter::Tree>>> {
-        self.trees.get(uri).await.ok_or_else(|| {
-            let kind = SessionResourceKind::Tree;
-            let uri = uri.clone();
-            crate::core::Error::SessionResourceNotFound { kind, uri }.into()
-        })
+    /// Determine whether the client supports the dynamic registration of the
+    /// `workspace/didChangeWatchedFiles` notification.
+    pub async fn watched_files_registration_support(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|capabilities| capabilities.did_change_watched_files.as_ref())
+            .and_then(|capabilities| capabilities.dynamic_registration)
+            .unwrap_or_default()
+    }
+
+    /// Replace the settings of the [`Session`], returning the previous settings.
+    pub async fn update_settings(&self, settings: crate::core::Settings) -> crate::core::Settings {
+        self.diagnostics.set_debounce(settings.diagnostics.debounce()).await;
+        std::mem::replace(&mut *self.settings.write().await, settings)
+    }
+
+    /// Compute the settings for the document at `uri`, taking its project configuration into
+    /// account.
+    pub async fn document_settings(&self, uri: &lsp::Url) -> crate::core::Settings {
+        let settings = self.settings.read().await.clone();
+        match self.project(uri).await {
+            Some(project) => project.settings(&settings, uri),
+            None => settings,
+        }
+    }
+
+    /// Retrieve the project configuration governing the document at `uri` (if any), loading it
+    /// when it has not been loaded yet.
+    pub async fn project(&self, uri: &lsp::Url) -> Option<Arc<crate::core::project::Project>> {
+        let config = crate::core::project::find(uri)?;
+        if let Some(project) = self.projects.read().await.get(&config) {
+            return Some(project.clone());
+        }
+        match self.load_project(&config).await {
+            Ok(project) => Some(project),
+            Err(err) => {
+                log::warn!("failed to load project configuration :: uri: {}, err: {}", config, err);
+                None
+            },
+        }
     }
 
-    /// Get a mutable reference to the [`tree_sitter::Tree`] for a [`crate::core::Document`] in the
-    /// [`Session`].
-    pub async fn get_mut_tree<'a>(
-        &'a self,
-        uri: &'a lsp::Url,
-    ) -> anyhow::Result<crate::core::RefMut<'a, lsp::Url, Mutex<tree_sitter::Tree>>> {
-        self.trees.get_mut(uri).await.ok_or_else(|| {
-            let kind = SessionResourceKind::Tree;
+    /// Load (or reload) the project configuration file at `config` into the [`Session`].
+    ///
+    /// The diagnostics for the malformed entries of the configuration are published (when the
+    /// [`Session`] has a client) whenever it is loaded, regardless of which request loaded it.
+    pub async fn load_project(&self, config: &lsp::Url) -> anyhow::Result<Arc<crate::core::project::Project>> {
+        let text = self.read_workspace_file(config).await?;
+        let project = Arc::new(crate::core::project::Project::parse(config.clone(), &text));
+        self.projects.write().await.insert(config.clone(), project.clone());
+        if let Some(client) = self.client.get() {
+            let diagnostics = project.diagnostics.clone();
+            client.publish_diagnostics(config.clone(), diagnostics, None).await;
+        }
+        Ok(project)
+    }
+
+    /// Remove the project configuration file at `config` from the [`Session`].
+    pub async fn unload_project(&self, config: &lsp::Url) -> Option<Arc<crate::core::project::Project>> {
+        self.projects.write().await.remove(config)
+    }
+
+    /// Get the [`crate::core::DocumentState`] for a [`crate::core::Document`] in the [`Session`].
+    pub async fn get_document(&self, uri: &lsp::Url) -> anyhow::Result<Arc<crate::core::DocumentState>> {
+        self.documents.read().await.get(uri).cloned().ok_or_else(|| {
+            let kind = SessionResourceKind::Document;
             let uri = uri.clone();
             crate::core::Error::SessionResourceNotFound { kind, uri }.into()
         })
     }
+
+    /// Get the current [`crate::core::Snapshot`] of a [`crate::core::Document`] in the [`Session`].
+    pub async fn get_snapshot(&self, uri: &lsp::Url) -> anyhow::Result<Arc<crate::core::Snapshot>> {
+        let document = self.get_document(uri).await?;
+        let snapshot = document.snapshot().await;
+        Ok(snapshot)
+    }
 }
 
-/// Pre-loaded tree-sitter languages for `.wast` and `.wat`.
+/// Pre-loaded tree-sitter languages for components, `.wast`, `.wat` and `.wit`.
 pub struct SessionLanguages {
+    /// Pre-loaded tree-sitter language for the component model text format.
+    pub component: tree_sitter::Language,
     /// Pre-loaded tree-sitter language for `.wast`.
     pub wast: tree_sitter::Language,
     /// Pre-loaded tree-sitter language for `.wat`.
     pub wat: tree_sitter::Language,
+    /// Pre-loaded tree-sitter language for `.wit`.
+    pub wit: tree_sitter::Language,
 }
 
 /// A tag representing of the kinds of session resource.
@@ -160,8 +393,4 @@ pub struct SessionLanguages {
 pub enum SessionResourceKind {
     /// A tag representing a [`crate::core::Document`].
     Document,
-    /// A tag representing a [`tree_sitter::Parser`].
-    Parser,
-    /// A tag representing a [`tree_sitter::Tree`].
-    Tree,
 }
@@ -0,0 +1,409 @@
+//! Definitions related to the configurable settings of the server.
+
+use crate::core::analysis::Proposal;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+
+/// The configuration section holding the settings of the server (e.g., `"wasm-lsp"` in the
+/// `settings.json` of VS Code).
+pub const SECTION: &str = "wasm-lsp";
+
+/// The settings of the server.
+///
+/// Settings are read from the `initializationOptions` of the `initialize` request, and are then
+/// pulled from the client via `workspace/configuration` (or taken from the notification itself)
+/// whenever `workspace/didChangeConfiguration` is received. Settings which are missing take their
+/// default values.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    /// The WebAssembly proposals which are enabled.
+    pub proposals: ProposalSettings,
+    /// The settings for diagnostics.
+    pub diagnostics: DiagnosticsSettings,
+    /// The settings for formatting.
+    pub format: FormatSettings,
+    /// The settings for inlay hints.
+    pub inlay_hints: InlayHintsSettings,
+    /// The settings for semantic tokens.
+    pub semantic_tokens: SemanticTokensSettings,
+    /// The name of the host API profile which the imports of modules are checked against, if any.
+    pub host: Option<String>,
+    /// The custom host API profiles, by name.
+    pub hosts: HashMap<String, HostProfile>,
+}
+
+impl Settings {
+    /// Retrieve the selected host API profile, which is either custom or built in.
+    pub fn host_profile(&self) -> Option<(&str, HostProfile)> {
+        let host = self.host.as_deref()?;
+        let profile = self.hosts.get(host).cloned().or_else(|| HostProfile::builtin(host))?;
+        Some((host, profile))
+    }
+
+    /// Parse [`Settings`] from a JSON value, treating `null` as the default settings.
+    pub fn from_value(value: serde_json::Value) -> anyhow::Result<Self> {
+        if value.is_null() {
+            return Ok(Settings::default());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Layer the settings of a JSON value over these settings, keeping the current value of the
+    /// settings which are missing from it.
+    pub fn merged(&self, value: &serde_json::Value) -> anyhow::Result<Self> {
+        if value.is_null() {
+            return Ok(self.clone());
+        }
+        let mut merged = serde_json::to_value(self)?;
+        merge(&mut merged, value);
+        Ok(serde_json::from_value(merged)?)
+    }
+
+    /// Parse [`Settings`] from the `initializationOptions` or the settings of a
+    /// `workspace/didChangeConfiguration` notification, which either hold the settings themselves
+    /// or hold them under the [`SECTION`] key.
+    pub fn from_options(value: serde_json::Value) -> anyhow::Result<Self> {
+        match value {
+            serde_json::Value::Object(mut object) if object.contains_key(SECTION) => {
+                Self::from_value(object.remove(SECTION).unwrap_or_default())
+            },
+            value => Self::from_value(value),
+        }
+    }
+}
+
+/// Merge a JSON value into another, recursively for objects.
+pub(crate) fn merge(target: &mut serde_json::Value, source: &serde_json::Value) {
+    match (target, source) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(source)) => {
+            for (key, value) in source {
+                merge(target.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        },
+        (target, source) => *target = source.clone(),
+    }
+}
+
+/// The settings controlling which WebAssembly proposals are enabled.
+///
+/// Uses of a disabled proposal are reported with the [`Rule::DisabledProposal`] diagnostic.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProposalSettings {
+    /// Whether the exception handling proposal is enabled.
+    pub exceptions: bool,
+    /// Whether the garbage collection proposal is enabled.
+    pub gc: bool,
+    /// Whether the 64-bit memory proposal is enabled.
+    pub memory64: bool,
+    /// Whether the multiple memories proposal is enabled.
+    pub multi_memory: bool,
+    /// Whether the fixed-width SIMD proposal is enabled.
+    pub simd: bool,
+    /// Whether the tail call proposal is enabled.
+    pub tail_call: bool,
+    /// Whether the threads proposal is enabled.
+    pub threads: bool,
+}
+
+impl ProposalSettings {
+    /// Determine whether a proposal is enabled.
+    pub fn enabled(&self, proposal: Proposal) -> bool {
+        match proposal {
+            Proposal::Exceptions => self.exceptions,
+            Proposal::Gc => self.gc,
+            Proposal::Memory64 => self.memory64,
+            Proposal::MultiMemory => self.multi_memory,
+            Proposal::Simd => self.simd,
+            Proposal::TailCall => self.tail_call,
+            Proposal::Threads => self.threads,
+        }
+    }
+}
+
+impl Default for ProposalSettings {
+    fn default() -> Self {
+        ProposalSettings {
+            exceptions: true,
+            gc: true,
+            memory64: true,
+            multi_memory: true,
+            simd: true,
+            tail_call: true,
+            threads: true,
+        }
+    }
+}
+
+/// A host API profile, describing the entities which a host provides for import.
+///
+/// A profile maps module names to the names of the entities the module provides, where `"*"`
+/// stands for any entity (e.g., `{ env = ["abort", "memory"], wasi_snapshot_preview1 = ["*"] }`).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct HostProfile {
+    /// The names of the provided entities, by module name.
+    pub modules: HashMap<String, Vec<String>>,
+}
+
+impl HostProfile {
+    /// Retrieve one of the built-in profiles (`wasi-preview1` and `wasi-unstable`) by name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let module = match name {
+            "wasi-preview1" => "wasi_snapshot_preview1",
+            "wasi-unstable" => "wasi_unstable",
+            _ => return None,
+        };
+        let modules = [(String::from(module), vec![String::from("*")])].into_iter().collect();
+        Some(HostProfile { modules })
+    }
+
+    /// Determine whether the host provides the entity `name` of `module`.
+    pub fn provides(&self, module: &str, name: &str) -> bool {
+        self.modules
+            .get(module)
+            .map_or(false, |names| names.iter().any(|it| it == "*" || it == name))
+    }
+}
+
+/// The rules for which diagnostics are reported.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Syntax errors.
+    Syntax,
+    /// Uses of an index (or identifier) which does not refer to any entity.
+    UnknownIndex,
+    /// Atomic instructions with an alignment other than their natural alignment.
+    AtomicAlignment,
+    /// Atomic instructions on memories which are not shared.
+    AtomicNonShared,
+    /// Uses of a proposal which is not enabled.
+    DisabledProposal,
+    /// Imports which are not provided by the selected host API profile.
+    UnknownImport,
+}
+
+impl Rule {
+    /// The code identifying the rule in diagnostics (and in the settings).
+    pub fn code(self) -> &'static str {
+        match self {
+            Rule::Syntax => "syntax",
+            Rule::UnknownIndex => "unknown-index",
+            Rule::AtomicAlignment => "atomic-alignment",
+            Rule::AtomicNonShared => "atomic-non-shared",
+            Rule::DisabledProposal => "disabled-proposal",
+            Rule::UnknownImport => "unknown-import",
+        }
+    }
+
+    /// The severity of the rule when it is not configured.
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Rule::AtomicNonShared | Rule::UnknownImport => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// The configurable severity of a diagnostic rule.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The rule is not reported.
+    Off,
+    /// The rule is reported as an error.
+    Error,
+    /// The rule is reported as a warning.
+    Warning,
+    /// The rule is reported as information.
+    Information,
+    /// The rule is reported as a hint.
+    Hint,
+}
+
+impl Severity {
+    /// Convert the severity into an [`lsp::DiagnosticSeverity`], or [`None`] if it is turned off.
+    pub fn to_lsp(self) -> Option<lsp::DiagnosticSeverity> {
+        match self {
+            Severity::Off => None,
+            Severity::Error => Some(lsp::DiagnosticSeverity::ERROR),
+            Severity::Warning => Some(lsp::DiagnosticSeverity::WARNING),
+            Severity::Information => Some(lsp::DiagnosticSeverity::INFORMATION),
+            Severity::Hint => Some(lsp::DiagnosticSeverity::HINT),
+        }
+    }
+}
+
+/// The settings for diagnostics.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DiagnosticsSettings {
+    /// The delay (in milliseconds) between a change to a document and the computation of its
+    /// diagnostics.
+    pub debounce: u64,
+    /// The severities of the rules, overriding their defaults.
+    pub severities: HashMap<Rule, Severity>,
+}
+
+impl DiagnosticsSettings {
+    /// The delay between a change to a document and the computation of its diagnostics.
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce)
+    }
+
+    /// The severity of a rule, or [`None`] if it is turned off.
+    pub fn severity(&self, rule: Rule) -> Option<lsp::DiagnosticSeverity> {
+        let severity = self.severities.get(&rule).copied();
+        severity.unwrap_or_else(|| rule.default_severity()).to_lsp()
+    }
+
+    /// Construct the diagnostic for a violation of a rule, or [`None`] if the rule is turned off.
+    pub fn diagnostic(&self, rule: Rule, range: lsp::Range, message: String) -> Option<lsp::Diagnostic> {
+        let severity = Some(self.severity(rule)?);
+        let code = Some(lsp::NumberOrString::String(rule.code().into()));
+        Some(lsp::Diagnostic {
+            range,
+            severity,
+            code,
+            message,
+            ..Default::default()
+        })
+    }
+}
+
+impl Default for DiagnosticsSettings {
+    fn default() -> Self {
+        let debounce = crate::core::DEFAULT_DIAGNOSTICS_DEBOUNCE.as_millis() as u64;
+        let severities = HashMap::default();
+        DiagnosticsSettings { debounce, severities }
+    }
+}
+
+/// The settings for formatting.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FormatSettings {
+    /// The number of spaces per level of indentation.
+    pub indent_width: u32,
+    /// Whether to indent with tabs rather than spaces.
+    pub use_tabs: bool,
+    /// Whether to end documents with a newline.
+    pub insert_final_newline: bool,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        FormatSettings {
+            indent_width: 2,
+            use_tabs: false,
+            insert_final_newline: true,
+        }
+    }
+}
+
+/// The settings for inlay hints.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InlayHintsSettings {
+    /// Whether inlay hints are shown at all.
+    pub enabled: bool,
+    /// Whether the indices of named entities are shown at their definitions.
+    pub indices: bool,
+}
+
+impl Default for InlayHintsSettings {
+    fn default() -> Self {
+        InlayHintsSettings {
+            enabled: true,
+            indices: true,
+        }
+    }
+}
+
+/// The settings for semantic tokens.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SemanticTokensSettings {
+    /// Whether semantic tokens are provided at all.
+    pub enabled: bool,
+    /// The token types (of the legend) which are provided, or [`None`] for all of them.
+    pub token_types: Option<Vec<String>>,
+    /// The token modifiers (of the legend) which are provided, or [`None`] for all of them.
+    pub token_modifiers: Option<Vec<String>>,
+}
+
+impl SemanticTokensSettings {
+    /// Determine whether a token type of the legend is provided.
+    pub fn token_type_enabled(&self, token_type: &lsp::SemanticTokenType) -> bool {
+        self.token_types
+            .as_ref()
+            .map_or(true, |types| types.iter().any(|it| it == token_type.as_str()))
+    }
+
+    /// Determine whether a token modifier of the legend is provided.
+    pub fn token_modifier_enabled(&self, token_modifier: &lsp::SemanticTokenModifier) -> bool {
+        self.token_modifiers.as_ref().map_or(true, |modifiers| {
+            modifiers.iter().any(|it| it == token_modifier.as_str())
+        })
+    }
+}
+
+impl Default for SemanticTokensSettings {
+    fn default() -> Self {
+        SemanticTokensSettings {
+            enabled: true,
+            token_types: None,
+            token_modifiers: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rule, Settings, Severity};
+
+    #[test]
+    fn from_options() {
+        let value = serde_json::json!({
+            "wasm-lsp": {
+                "proposals": { "gc": false },
+                "diagnostics": { "severities": { "atomic-non-shared": "off", "unknown-index": "hint" } },
+            }
+        });
+        let settings = Settings::from_options(value).unwrap();
+        assert!(!settings.proposals.gc);
+        assert!(settings.proposals.threads);
+        assert_eq!(None, settings.diagnostics.severity(Rule::AtomicNonShared));
+        assert_eq!(
+            Some(lsp::DiagnosticSeverity::HINT),
+            settings.diagnostics.severity(Rule::UnknownIndex)
+        );
+        assert_eq!(
+            Some(lsp::DiagnosticSeverity::ERROR),
+            settings.diagnostics.severity(Rule::Syntax)
+        );
+        assert_eq!(
+            Some(&Severity::Off),
+            settings.diagnostics.severities.get(&Rule::AtomicNonShared)
+        );
+    }
+
+    #[test]
+    fn merged() {
+        let current = Settings::from_options(serde_json::json!({ "proposals": { "gc": false } })).unwrap();
+        let value = serde_json::json!({ "inlayHints": { "indices": false } });
+        let settings = current.merged(&value).unwrap();
+        assert!(!settings.proposals.gc);
+        assert!(!settings.inlay_hints.indices);
+        assert!(settings.inlay_hints.enabled);
+        assert_eq!(current, current.merged(&serde_json::Value::Null).unwrap());
+    }
+
+    #[test]
+    fn from_options_null() {
+        let settings = Settings::from_options(serde_json::Value::Null).unwrap();
+        assert_eq!(Settings::default(), settings);
+    }
+}
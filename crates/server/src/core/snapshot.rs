@@ -0,0 +1,88 @@
+//! Definitions related to immutable snapshots of documents.
+
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+    OnceLock,
+};
+
+/// An immutable snapshot of a [`crate::core::Document`] at a particular version.
+///
+/// Every change to a document produces a new snapshot, so providers can compute their results
+/// against a snapshot without blocking (or being blocked by) further changes. Providers which
+/// run for a while should check [`Snapshot::is_outdated`] and stop early once it holds.
+pub struct Snapshot {
+    /// The URL of the document.
+    pub uri: lsp::Url,
+    /// The language-id, version, and textual content of the document.
+    pub text: crate::core::Text,
+    /// The syntax tree for the textual content of the document.
+    pub tree: tree_sitter::Tree,
+    latest: Arc<AtomicI32>,
+    analysis: OnceLock<Arc<crate::core::analysis::Analysis>>,
+    encoding: OnceLock<Result<Arc<crate::core::encoding::Encoding>, Box<lsp::Diagnostic>>>,
+}
+
+impl Snapshot {
+    /// Create a new [`Snapshot`]. The `latest` counter is shared by all snapshots of a document
+    /// and tracks its most recent version.
+    pub(crate) fn new(uri: lsp::Url, text: crate::core::Text, tree: tree_sitter::Tree, latest: Arc<AtomicI32>) -> Self {
+        let analysis = OnceLock::new();
+        let encoding = OnceLock::new();
+        Snapshot {
+            uri,
+            text,
+            tree,
+            latest,
+            analysis,
+            encoding,
+        }
+    }
+
+    /// The version of the document captured by the [`Snapshot`].
+    pub fn version(&self) -> i32 {
+        self.text.version
+    }
+
+    /// Retrieve the [`crate::core::analysis::Analysis`] of the [`Snapshot`], computing it on
+    /// first use.
+    ///
+    /// The computation is abandoned once `token` is cancelled, in which case nothing is cached and
+    /// the next call computes the analysis again.
+    pub fn analysis(
+        &self,
+        token: &crate::core::CancellationToken,
+    ) -> anyhow::Result<Arc<crate::core::analysis::Analysis>> {
+        if let Some(analysis) = self.analysis.get() {
+            return Ok(analysis.clone());
+        }
+        let analysis = crate::core::analysis::Analysis::new(self.text.language, &self.tree, &self.text.content, token)?;
+        Ok(self.analysis.get_or_init(|| Arc::new(analysis)).clone())
+    }
+
+    /// Retrieve the binary encoding of the [`Snapshot`] (with the default
+    /// [`crate::core::encoding::EncodeOptions`]), computing it on first use.
+    ///
+    /// Only `.wat` documents can be encoded; failures are reported as a [`lsp::Diagnostic`].
+    pub fn encoding(&self) -> Result<Arc<crate::core::encoding::Encoding>, Box<lsp::Diagnostic>> {
+        self.encoding
+            .get_or_init(|| crate::core::encoding::encode(&self.text.content, &Default::default()).map(Arc::new))
+            .clone()
+    }
+
+    /// Determine whether the document has changed since the [`Snapshot`] was taken.
+    pub fn is_outdated(&self) -> bool {
+        self.latest.load(Ordering::Acquire) != self.text.version
+    }
+
+    /// Ensure that the document has not changed since the [`Snapshot`] was taken, failing with
+    /// [`crate::core::Error::ContentModified`] otherwise.
+    pub fn ensure_current(&self) -> anyhow::Result<()> {
+        if self.is_outdated() {
+            let uri = self.uri.clone();
+            let version = self.text.version;
+            return Err(crate::core::Error::ContentModified { uri, version }.into());
+        }
+        Ok(())
+    }
+}
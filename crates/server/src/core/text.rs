@@ -1,23 +1,32 @@
 //! Definitions related to working with textual content.
 
-/// Convenience struct for packaging the language-id and textual content of a [`core::Document`].
+/// Convenience struct for packaging the language-id, version, and textual content of a
+/// [`core::Document`].
+#[derive(Clone)]
 pub struct Text {
     /// The language-id of the [`core::Document`].
     pub language: crate::core::Language,
+    /// The version of the [`core::Document`], as reported by the client.
+    pub version: i32,
     /// The textual content of the [`core::Document`].
     pub content: ropey::Rope,
 }
 
 impl Text {
-    /// Create a new [`Text`] from a language-id and some textual content.
+    /// Create a new [`Text`] from a language-id, a version, and some textual content.
     pub fn new(
         language_id: impl TryInto<crate::core::Language, Error = anyhow::Error>,
+        version: i32,
         text: impl AsRef<str>,
     ) -> anyhow::Result<Self> {
         let text = text.as_ref();
         let language = language_id.try_into()?;
         let content = ropey::Rope::from_str(text);
-        Ok(Text { language, content })
+        Ok(Text {
+            language,
+            version,
+            content,
+        })
     }
 }
 
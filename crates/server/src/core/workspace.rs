@@ -0,0 +1,216 @@
+//! Definitions related to the files of the workspace.
+
+use anyhow::anyhow;
+use std::collections::HashMap;
+
+/// The names of the directories which are skipped when indexing the workspace: build outputs,
+/// version control metadata and installed packages.
+pub const EXCLUDED_DIRS: &[&str] = &["target", ".git", "node_modules"];
+
+/// The cached index of the workspace files and the diagnostics last computed for them.
+///
+/// The cache is invalidated by `workspace/didChangeWatchedFiles` (for the files) and whenever the
+/// settings affecting diagnostics change (for the reports).
+#[derive(Debug, Default)]
+pub struct Cache {
+    files: Option<Vec<lsp::Url>>,
+    reports: HashMap<lsp::Url, Report>,
+}
+
+impl Cache {
+    /// Retrieve the URLs of the `.wast`, `.wat` and `.wit` files of the workspace folders, if they
+    /// have been indexed (see [`index`]).
+    pub fn files(&self) -> Option<Vec<lsp::Url>> {
+        self.files.clone()
+    }
+
+    /// Record the URLs of the files of the workspace folders, as collected by [`index`].
+    pub fn insert_files(&mut self, files: Vec<lsp::Url>) {
+        self.files = Some(files);
+    }
+
+    /// Retrieve the [`Report`] last computed for the workspace file at `uri`, if it is still valid
+    /// for the given `version` of the document ([`None`] if the document is not open).
+    pub fn report(&self, uri: &lsp::Url, version: Option<i32>) -> Option<&Report> {
+        self.reports.get(uri).filter(|report| report.version == version)
+    }
+
+    /// Record the [`Report`] computed for the workspace file at `uri`.
+    pub fn insert_report(&mut self, uri: lsp::Url, report: Report) {
+        self.reports.insert(uri, report);
+    }
+
+    /// Discard the [`Report`]s, e.g., because the settings affecting diagnostics have changed.
+    pub fn clear_reports(&mut self) {
+        self.reports.clear();
+    }
+
+    /// Invalidate the cached state of a workspace file which was created, changed or deleted.
+    pub fn invalidate(&mut self, event: &lsp::FileEvent) {
+        self.reports.remove(&event.uri);
+        if lsp::FileChangeType::CHANGED != event.typ {
+            self.files = None;
+        }
+    }
+}
+
+/// The diagnostics last computed for a workspace file.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// The version of the document the diagnostics were computed for ([`None`] if the diagnostics
+    /// were computed from the file on disk).
+    pub version: Option<i32>,
+    /// The `resultId` identifying the diagnostics.
+    pub result_id: String,
+    /// The diagnostics.
+    pub items: Vec<lsp::Diagnostic>,
+}
+
+/// Determine the [`crate::core::Language`] of a workspace file from its extension.
+pub fn language(uri: &lsp::Url) -> anyhow::Result<crate::core::Language> {
+    let path = std::path::Path::new(uri.path());
+    crate::core::Language::try_from(path)
+}
+
+/// Collect the URLs of the `.wast`, `.wat` and `.wit` files contained in the given workspace
+/// folders, skipping the [`EXCLUDED_DIRS`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn index(folders: &[lsp::Url]) -> Vec<lsp::Url> {
+    let mut uris = vec![];
+    let mut pending = folders
+        .iter()
+        .filter_map(|folder| folder.to_file_path().ok())
+        .collect::<Vec<_>>();
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::warn!("failed to index workspace directory :: dir: {:?}, err: {}", dir, err);
+                continue;
+            },
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let excluded = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| EXCLUDED_DIRS.contains(&name));
+                if !excluded {
+                    pending.push(path);
+                }
+            } else if let Ok(crate::core::Language::Wast | crate::core::Language::Wat | crate::core::Language::Wit) =
+                crate::core::Language::try_from(path.as_path())
+            {
+                if let Ok(uri) = lsp::Url::from_file_path(&path) {
+                    uris.push(uri);
+                }
+            }
+        }
+    }
+    uris.sort();
+    uris
+}
+
+/// Collect the URLs of the `.wast`, `.wat` and `.wit` files contained in the given workspace
+/// folders.
+#[cfg(target_arch = "wasm32")]
+pub fn index(_folders: &[lsp::Url]) -> Vec<lsp::Url> {
+    vec![]
+}
+
+/// Read the textual content of a workspace file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(uri: &lsp::Url) -> anyhow::Result<String> {
+    let path = uri
+        .to_file_path()
+        .map_err(|()| anyhow!("workspace file is not a local path :: uri: {}", uri))?;
+    let text = std::fs::read_to_string(path)?;
+    Ok(text)
+}
+
+/// Read the binary content of a workspace file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_bytes(uri: &lsp::Url) -> anyhow::Result<Vec<u8>> {
+    let path = uri
+        .to_file_path()
+        .map_err(|()| anyhow!("workspace file is not a local path :: uri: {}", uri))?;
+    let bytes = std::fs::read(path)?;
+    Ok(bytes)
+}
+
+/// Read the binary content of a workspace file.
+#[cfg(target_arch = "wasm32")]
+pub fn read_bytes(uri: &lsp::Url) -> anyhow::Result<Vec<u8>> {
+    Err(anyhow!("workspace files cannot be read on this target :: uri: {}", uri))
+}
+
+/// Compute the URL of the file next to the workspace file at `uri`, with the given extension.
+pub fn sibling(uri: &lsp::Url, extension: &str) -> anyhow::Result<lsp::Url> {
+    let path = std::path::Path::new(uri.path()).with_extension(extension);
+    let path = path
+        .to_str()
+        .ok_or_else(|| anyhow!("workspace file path is not valid unicode :: uri: {}", uri))?;
+    let mut sibling = uri.clone();
+    sibling.set_path(path);
+    Ok(sibling)
+}
+
+/// Write the binary content of a workspace file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_bytes(uri: &lsp::Url, bytes: &[u8]) -> anyhow::Result<()> {
+    let path = uri
+        .to_file_path()
+        .map_err(|()| anyhow!("workspace file is not a local path :: uri: {}", uri))?;
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Write the binary content of a workspace file.
+#[cfg(target_arch = "wasm32")]
+pub fn write_bytes(uri: &lsp::Url, _bytes: &[u8]) -> anyhow::Result<()> {
+    Err(anyhow!(
+        "workspace files cannot be written on this target :: uri: {}",
+        uri
+    ))
+}
+
+/// Read the textual content of a workspace file.
+#[cfg(target_arch = "wasm32")]
+pub fn read(uri: &lsp::Url) -> anyhow::Result<String> {
+    Err(anyhow!("workspace files cannot be read on this target :: uri: {}", uri))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::Cache;
+
+    #[test]
+    fn index() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("wasm-lsp-workspace-{}", std::process::id()));
+        for dir in ["src", "target/debug", ".git", "node_modules/pkg"] {
+            std::fs::create_dir_all(root.join(dir))?;
+            std::fs::write(root.join(dir).join("module.wat"), "(module)")?;
+        }
+        std::fs::write(root.join("src").join("notes.txt"), "")?;
+
+        let folders = [lsp::Url::from_directory_path(&root).unwrap()];
+        let module = lsp::Url::from_file_path(root.join("src").join("module.wat")).unwrap();
+        let mut cache = Cache::default();
+        assert_eq!(cache.files(), None);
+        cache.insert_files(super::index(&folders));
+        assert_eq!(cache.files(), Some(vec![module.clone()]));
+
+        // the index is kept until a workspace file is created or deleted
+        let other = lsp::Url::from_file_path(root.join("other.wat")).unwrap();
+        std::fs::write(root.join("other.wat"), "(module)")?;
+        cache.invalidate(&lsp::FileEvent::new(other.clone(), lsp::FileChangeType::CHANGED));
+        assert_eq!(cache.files(), Some(vec![module.clone()]));
+        cache.invalidate(&lsp::FileEvent::new(other.clone(), lsp::FileChangeType::CREATED));
+        assert_eq!(cache.files(), None);
+        assert_eq!(super::index(&folders), vec![other, module]);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+//! Definitions for the custom LSP protocol extensions supported by the server.
+
+use serde::{Deserialize, Serialize};
+
+/// Custom request for disassembling a binary `.wasm` module into a virtual `.wat` document.
+pub enum Disassemble {}
+
+impl lsp::request::Request for Disassemble {
+    type Params = DisassembleParams;
+    type Result = DisassembleResult;
+
+    const METHOD: &'static str = "wasm/disassemble";
+}
+
+/// Parameters for the [`Disassemble`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleParams {
+    /// The URL of the binary `.wasm` module.
+    pub uri: lsp::Url,
+    /// The base64-encoded content of the module, if it should not be read from `uri`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<String>,
+}
+
+/// Result for the [`Disassemble`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleResult {
+    /// The (virtual) URL of the disassembled `.wat` document.
+    pub uri: lsp::Url,
+    /// The textual content of the disassembled `.wat` document.
+    pub text: String,
+}
+
+/// Custom request for assembling a `.wat` document into a binary `.wasm` module.
+pub enum Assemble {}
+
+impl lsp::request::Request for Assemble {
+    type Params = AssembleParams;
+    type Result = AssembleResult;
+
+    const METHOD: &'static str = "wasm/assemble";
+}
+
+/// The command (for `workspace/executeCommand`) corresponding to the [`Assemble`] request.
+///
+/// The command takes a single argument: the [`AssembleParams`].
+pub const ASSEMBLE_COMMAND: &str = "wasm.assemble";
+
+/// Parameters for the [`Assemble`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssembleParams {
+    /// The `.wat` document to assemble.
+    pub text_document: lsp::TextDocumentIdentifier,
+    /// How the resulting binary module should be delivered.
+    #[serde(default)]
+    pub output: AssembleOutput,
+    /// Whether to emit the `name` section (defaults to `true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_section: Option<bool>,
+    /// Whether to emit the custom sections given by `(@custom ...)` annotations (defaults to
+    /// `true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_sections: Option<bool>,
+}
+
+/// How the binary module produced by the [`Assemble`] request should be delivered.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssembleOutput {
+    /// Return the module as base64-encoded content.
+    Base64,
+    /// Write the module next to the source document (with the `.wasm` extension).
+    File,
+}
+
+impl Default for AssembleOutput {
+    fn default() -> Self {
+        AssembleOutput::Base64
+    }
+}
+
+/// Result for the [`Assemble`] request.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssembleResult {
+    /// The base64-encoded binary module, for [`AssembleOutput::Base64`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<String>,
+    /// The URL of the written binary module, for [`AssembleOutput::File`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<lsp::Url>,
+    /// The diagnostics describing why encoding failed, if it did.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<lsp::Diagnostic>,
+}
+
+/// Custom request for mapping a byte offset of the binary encoding of a `.wat` document back to
+/// the location of the corresponding instruction in the document.
+///
+/// The offset is given as a byte offset into the module, optionally together with the index of the
+/// function it lies in (e.g., as reported by `wasm-function[12]:0x1a3` in JS API and browser stack
+/// traces, where `0x1a3` is relative to the module), or as a function index together with an
+/// offset into the body of that function.
+pub enum SourceLocation {}
+
+impl lsp::request::Request for SourceLocation {
+    type Params = SourceLocationParams;
+    type Result = Option<lsp::Location>;
+
+    const METHOD: &'static str = "wasm/sourceLocation";
+}
+
+/// Parameters for the [`SourceLocation`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceLocationParams {
+    /// The `.wat` document whose binary encoding the offset refers to.
+    pub text_document: lsp::TextDocumentIdentifier,
+    /// The index of the function (within the function index space, which includes imports).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<u32>,
+    /// The byte offset within the body of `function` (which cannot be combined with
+    /// `module_offset`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    /// The byte offset within the module, which must lie within the body of `function` if that is
+    /// given as well.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_offset: Option<u32>,
+}
+
+/// Custom request for computing the size breakdown of the binary encoding of a `.wat` document.
+pub enum SizeReport {}
+
+impl lsp::request::Request for SizeReport {
+    type Params = SizeReportParams;
+    type Result = SizeReportResult;
+
+    const METHOD: &'static str = "wasm/sizeReport";
+}
+
+/// The command (for `workspace/executeCommand`) corresponding to the [`SizeReport`] request, which
+/// is attached to the code lenses showing the sizes of the functions.
+///
+/// The command takes a single argument: the [`SizeReportParams`].
+pub const SIZE_REPORT_COMMAND: &str = "wasm.sizeReport";
+
+/// Parameters for the [`SizeReport`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeReportParams {
+    /// The `.wat` document to report on.
+    pub text_document: lsp::TextDocumentIdentifier,
+}
+
+/// Result for the [`SizeReport`] request.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeReportResult {
+    /// The size in bytes of the entire module.
+    pub size: u32,
+    /// The sizes of the sections of the module, in order.
+    pub sections: Vec<SectionSize>,
+    /// The sizes of the functions defined by the module, in order.
+    pub functions: Vec<FunctionSize>,
+}
+
+/// The size of a section of a binary module.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionSize {
+    /// The kind of section (e.g., `"type"`, `"code"`, or `"names"` for the `name` section).
+    pub kind: String,
+    /// The name of the section, for custom sections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The size in bytes of the section (including its header).
+    pub size: u32,
+}
+
+/// The size of a function body of a binary module.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionSize {
+    /// The index of the function within the function index space (which includes imports).
+    pub index: u32,
+    /// The symbolic name (`$identifier`) of the function, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The size in bytes of the function body (excluding its size prefix).
+    pub size: u32,
+    /// The number of instructions of the function body (excluding the implicit final `end`).
+    pub instructions: u32,
+    /// The range of the function definition within the document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<lsp::Range>,
+}
+
+/// Request for pulling the diagnostics of a document (`textDocument/diagnostic`).
+///
+/// The pull model for diagnostics was introduced with LSP 3.17, which postdates the version of
+/// `lsp-types` (shared with `lsp-text`) that the server is built against, so its definitions are
+/// given here along with the custom requests.
+pub enum DocumentDiagnostic {}
+
+impl lsp::request::Request for DocumentDiagnostic {
+    type Params = DocumentDiagnosticParams;
+    type Result = DocumentDiagnosticReport;
+
+    const METHOD: &'static str = "textDocument/diagnostic";
+}
+
+/// Parameters for the [`DocumentDiagnostic`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+    /// The document to compute the diagnostics for.
+    pub text_document: lsp::TextDocumentIdentifier,
+    /// The identifier of the diagnostic provider, if given at registration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// The `resultId` of the report which the client received previously (if any).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_result_id: Option<String>,
+}
+
+/// Result for the [`DocumentDiagnostic`] request.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DocumentDiagnosticReport {
+    /// The full set of diagnostics.
+    Full(FullDocumentDiagnosticReport),
+    /// The diagnostics are unchanged since the report with the previous `resultId`.
+    Unchanged(UnchangedDocumentDiagnosticReport),
+}
+
+/// A report containing the full set of diagnostics of a document.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullDocumentDiagnosticReport {
+    /// The identifier of the report, which the client sends back with the next request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    /// The diagnostics of the document.
+    pub items: Vec<lsp::Diagnostic>,
+}
+
+/// A report indicating that the diagnostics of a document are unchanged.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnchangedDocumentDiagnosticReport {
+    /// The identifier of the report, which matches the `resultId` sent by the client.
+    pub result_id: String,
+}
+
+/// Request for pulling the diagnostics of all the documents of the workspace
+/// (`workspace/diagnostic`).
+pub enum WorkspaceDiagnostic {}
+
+impl lsp::request::Request for WorkspaceDiagnostic {
+    type Params = WorkspaceDiagnosticParams;
+    type Result = WorkspaceDiagnosticReport;
+
+    const METHOD: &'static str = "workspace/diagnostic";
+}
+
+/// Parameters for the [`WorkspaceDiagnostic`] request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticParams {
+    /// The identifier of the diagnostic provider, if given at registration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// The `resultId`s of the reports which the client received previously.
+    pub previous_result_ids: Vec<PreviousResultId>,
+}
+
+/// The `resultId` of a report which the client received previously for a document.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousResultId {
+    /// The URL of the document.
+    pub uri: lsp::Url,
+    /// The `resultId` of the report.
+    pub value: String,
+}
+
+/// Result for the [`WorkspaceDiagnostic`] request.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticReport {
+    /// The reports for the documents of the workspace.
+    pub items: Vec<WorkspaceDocumentDiagnosticReport>,
+}
+
+/// The report for a single document of a [`WorkspaceDiagnosticReport`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum WorkspaceDocumentDiagnosticReport {
+    /// The full set of diagnostics.
+    Full(WorkspaceFullDocumentDiagnosticReport),
+    /// The diagnostics are unchanged since the report with the previous `resultId`.
+    Unchanged(WorkspaceUnchangedDocumentDiagnosticReport),
+}
+
+/// A [`FullDocumentDiagnosticReport`] for a document of the workspace.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFullDocumentDiagnosticReport {
+    /// The URL of the document.
+    pub uri: lsp::Url,
+    /// The version of the document, or [`None`] if it is not open.
+    pub version: Option<i64>,
+    /// The report for the document.
+    #[serde(flatten)]
+    pub full_document_diagnostic_report: FullDocumentDiagnosticReport,
+}
+
+/// An [`UnchangedDocumentDiagnosticReport`] for a document of the workspace.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceUnchangedDocumentDiagnosticReport {
+    /// The URL of the document.
+    pub uri: lsp::Url,
+    /// The version of the document, or [`None`] if it is not open.
+    pub version: Option<i64>,
+    /// The report for the document.
+    #[serde(flatten)]
+    pub unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport,
+}
+
+/// Request from the server asking the client to pull the diagnostics again
+/// (`workspace/diagnostic/refresh`).
+pub enum WorkspaceDiagnosticRefresh {}
+
+impl lsp::request::Request for WorkspaceDiagnosticRefresh {
+    type Params = ();
+    type Result = ();
+
+    const METHOD: &'static str = "workspace/diagnostic/refresh";
+}
+
+/// The client capabilities for the pull model for diagnostics (`textDocument.diagnostic`).
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticClientCapabilities {
+    /// Whether the client supports the dynamic registration of the pull model for diagnostics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dynamic_registration: Option<bool>,
+    /// Whether the client supports reports for related documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub related_document_support: Option<bool>,
+}
+
+/// The server capabilities for the pull model for diagnostics (`diagnosticProvider`).
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticOptions {
+    /// The identifier of the diagnostic provider, sent back by the client with its requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// Whether the diagnostics of a document can change because of changes to other documents.
+    pub inter_file_dependencies: bool,
+    /// Whether the server supports pulling the diagnostics of the workspace
+    /// ([`WorkspaceDiagnostic`]).
+    pub workspace_diagnostics: bool,
+}
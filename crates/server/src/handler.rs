@@ -3,12 +3,36 @@
 /// LSP message handler functions for `textDocument/*`.
 pub mod text_document;
 
+/// LSP message handler functions for the custom `wasm/*` requests.
+pub mod wasm;
+
+/// LSP message handler functions for `workspace/*`.
+pub mod workspace;
+
 use std::sync::Arc;
 
 /// LSP message handler function for `initialize`.
 pub async fn initialize(session: Arc<crate::core::Session>, params: lsp::InitializeParams) -> lsp::InitializeResult {
     // Received the client capabilities and store them in the server session
     *session.client_capabilities.write().await = Some(params.capabilities);
+    // Decide whether the client pulls diagnostics (LSP 3.17) or they are published by the server
+    let diagnostic_pull = session.diagnostic_client_capabilities().is_some();
+    session.set_diagnostic_pull_support(diagnostic_pull);
+    // Record the workspace folders (or the root, for older clients) in the server session
+    *session.workspace_folders.write().await = params
+        .workspace_folders
+        .map(|folders| folders.into_iter().map(|folder| folder.uri).collect())
+        .or_else(|| params.root_uri.map(|uri| vec![uri]))
+        .unwrap_or_default();
+    // Apply the settings given as initialization options (if any)
+    if let Some(options) = params.initialization_options {
+        match crate::core::Settings::from_options(options) {
+            Ok(settings) => {
+                session.update_settings(settings).await;
+            },
+            Err(error) => log::warn!("ignoring malformed 'initializationOptions' :: error: {}", error),
+        }
+    }
     // Retrieve the server capabilities for the response to the client
     let capabilities = session.server_capabilities.read().await.clone();
     lsp::InitializeResult {
@@ -16,3 +40,46 @@ pub async fn initialize(session: Arc<crate::core::Session>, params: lsp::Initial
         ..lsp::InitializeResult::default()
     }
 }
+
+/// LSP message handler function for `initialized`.
+pub async fn initialized(session: Arc<crate::core::Session>, _: lsp::InitializedParams) -> anyhow::Result<()> {
+    let mut registrations = vec![];
+    // Ask to be notified of changes to the settings (which some clients only send when registered)
+    if session.configuration_registration_support().await {
+        registrations.push(lsp::Registration {
+            id: String::from("wasm-lsp/didChangeConfiguration"),
+            method: String::from("workspace/didChangeConfiguration"),
+            register_options: None,
+        });
+    }
+    // Ask to be notified of changes to the project configuration files and the workspace files
+    if session.watched_files_registration_support().await {
+        let options = lsp::DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![
+                lsp::FileSystemWatcher {
+                    glob_pattern: format!("**/{}", crate::core::project::FILE_NAME),
+                    kind: None,
+                },
+                lsp::FileSystemWatcher {
+                    glob_pattern: String::from("**/*.{wast,wat,wit}"),
+                    kind: None,
+                },
+            ],
+        };
+        registrations.push(lsp::Registration {
+            id: String::from("wasm-lsp/didChangeWatchedFiles"),
+            method: String::from("workspace/didChangeWatchedFiles"),
+            register_options: Some(serde_json::to_value(options)?),
+        });
+    }
+    if !registrations.is_empty() {
+        session.client()?.register_capability(registrations).await?;
+    }
+    // Pull the current settings, which take precedence over the initialization options
+    if session.configuration_support().await {
+        if let Some(settings) = crate::handler::workspace::pull_settings(&session).await? {
+            crate::handler::workspace::configure(session, settings).await?;
+        }
+    }
+    Ok(())
+}
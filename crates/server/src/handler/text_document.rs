@@ -1,34 +1,97 @@
-use lsp_text::RopeExt;
 use std::sync::Arc;
 
+/// LSP message handler function for `textDocument/codeLens`.
+pub async fn code_lens(
+    session: Arc<crate::core::Session>,
+    params: lsp::CodeLensParams,
+) -> anyhow::Result<Option<Vec<lsp::CodeLens>>> {
+    let uri = params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::code_lens(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
+/// LSP message handler function for `textDocument/definition`.
+pub async fn definition(
+    session: Arc<crate::core::Session>,
+    params: lsp::GotoDefinitionParams,
+) -> anyhow::Result<Option<lsp::GotoDefinitionResponse>> {
+    let uri = params.text_document_position_params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::definition(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
 /// LSP message handler function for `textDocument/didChange`.
 pub async fn did_change(
     session: Arc<crate::core::Session>,
     params: lsp::DidChangeTextDocumentParams,
 ) -> anyhow::Result<()> {
     let uri = &params.text_document.uri;
-    let mut text = session.get_mut_text(uri).await?;
+    let version = params.text_document.version;
+
+    // disassembled modules are read-only
+    if crate::core::disassembly::is_disassembly(uri) {
+        log::warn!(
+            "'textDocument/didChange' rejected for read-only document :: uri: {}",
+            uri
+        );
+        return Ok(());
+    }
 
-    let edits = params
-        .content_changes
-        .iter()
-        .map(|change| text.content.build_edit(change))
-        .collect::<Result<Vec<_>, _>>()?;
+    // changes to a document which was never opened cannot be applied
+    let document = match session.get_document(uri).await {
+        Ok(document) => document,
+        Err(_) => {
+            log::warn!("'textDocument/didChange' rejected for unknown document :: uri: {}", uri);
+            return Ok(());
+        },
+    };
 
-    for edit in &edits {
-        text.content.apply_edit(edit);
+    // reject changes which arrive out of order with respect to the current version
+    let current = document.snapshot().await.version();
+    if version <= current {
+        log::warn!(
+            "'textDocument/didChange' rejected stale version :: uri: {}, version: {}, current: {}",
+            uri,
+            version,
+            current
+        );
+        return Ok(());
     }
 
-    if let Some(tree) = crate::core::Document::change(session.clone(), uri, &text.content, &edits).await? {
-        let diagnostics = crate::provider::text_document::diagnostics(&tree, &text);
-        let version = Default::default();
-        session
-            .client()?
-            .publish_diagnostics(uri.clone(), diagnostics, version)
-            .await;
+    if document.change(version, &params.content_changes).await?.is_none() {
+        return Ok(());
     }
 
-    Ok(())
+    // diagnostics are requested by the client instead when it supports the pull model
+    if session.diagnostic_pull_support().await {
+        return Ok(());
+    }
+    session
+        .diagnostics
+        .schedule(session.clone(), uri.clone(), version)
+        .await
+}
+
+/// LSP message handler function for `textDocument/diagnostic`.
+pub async fn diagnostic(
+    session: Arc<crate::core::Session>,
+    params: crate::ext::DocumentDiagnosticParams,
+) -> anyhow::Result<crate::ext::DocumentDiagnosticReport> {
+    let uri = params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::diagnostic(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
 }
 
 /// LSP message handler function for `textDocument/didClose`.
@@ -37,7 +100,10 @@ pub async fn did_close(
     params: lsp::DidCloseTextDocumentParams,
 ) -> anyhow::Result<()> {
     let uri = params.text_document.uri;
-    session.remove_document(&uri).await?;
+    session.diagnostics.cancel(&uri).await;
+    if session.remove_document(&uri).await.is_none() {
+        log::warn!("'textDocument/didClose' for unknown document :: uri: {}", uri);
+    }
     let diagnostics = Default::default();
     let version = Default::default();
     session.client()?.publish_diagnostics(uri, diagnostics, version).await;
@@ -51,12 +117,20 @@ pub async fn did_open(
 ) -> anyhow::Result<()> {
     let uri = params.text_document.uri.clone();
     if let Some(document) = crate::core::Document::open(session.clone(), params)? {
-        let tree = document.tree.clone();
-        let text = document.text();
-        session.insert_document(uri.clone(), document).await?;
-        let diagnostics = crate::provider::text_document::diagnostics(&tree, &text);
-        let version = Default::default();
-        session.client()?.publish_diagnostics(uri, diagnostics, version).await;
+        let version = document.version;
+        // reopening a document replaces its previous state (disassembled modules are already
+        // present in the session after `wasm/disassemble`)
+        let previous = session.insert_document(uri.clone(), document).await;
+        if previous.is_some() && !crate::core::disassembly::is_disassembly(&uri) {
+            log::warn!("'textDocument/didOpen' replaced already open document :: uri: {}", uri);
+        }
+        // load the project configuration of the document (reporting its malformed entries)
+        crate::handler::workspace::discover_project(session.clone(), &uri).await?;
+        // diagnostics are requested by the client instead when it supports the pull model
+        if session.diagnostic_pull_support().await {
+            return Ok(());
+        }
+        session.diagnostics.schedule(session.clone(), uri, version).await?;
     } else {
         log::warn!("'textDocument/didOpen' failed :: uri: {:#?}", uri);
     }
@@ -68,7 +142,78 @@ pub async fn document_symbol(
     session: Arc<crate::core::Session>,
     params: lsp::DocumentSymbolParams,
 ) -> anyhow::Result<Option<lsp::DocumentSymbolResponse>> {
-    crate::provider::text_document::document_symbol(session, params).await
+    let uri = params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::document_symbol(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
+/// LSP message handler function for `textDocument/formatting`.
+pub async fn formatting(
+    session: Arc<crate::core::Session>,
+    params: lsp::DocumentFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp::TextEdit>>> {
+    let uri = params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let result = crate::provider::text_document::formatting(session.clone(), params).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
+/// LSP message handler function for `textDocument/hover`.
+pub async fn hover(session: Arc<crate::core::Session>, params: lsp::HoverParams) -> anyhow::Result<Option<lsp::Hover>> {
+    let uri = params.text_document_position_params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::hover(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
+/// LSP message handler function for `textDocument/inlayHint`.
+pub async fn inlay_hint(
+    session: Arc<crate::core::Session>,
+    params: lsp::InlayHintParams,
+) -> anyhow::Result<Option<Vec<lsp::InlayHint>>> {
+    let uri = params.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::inlay_hint(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
+/// LSP message handler function for `textDocument/references`.
+pub async fn references(
+    session: Arc<crate::core::Session>,
+    params: lsp::ReferenceParams,
+) -> anyhow::Result<Option<Vec<lsp::Location>>> {
+    let uri = params.text_document_position.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::references(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
+}
+
+/// LSP message handler function for `textDocument/rename`.
+pub async fn rename(
+    session: Arc<crate::core::Session>,
+    params: lsp::RenameParams,
+) -> anyhow::Result<Option<lsp::WorkspaceEdit>> {
+    let uri = params.text_document_position.text_document.uri.clone();
+    let version = session.get_snapshot(&uri).await?.version();
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::text_document::rename(session.clone(), params, token.clone());
+    let result = session.run(&token, work).await?;
+    session.ensure_version(&uri, version).await?;
+    Ok(result)
 }
 
 /// LSP message handler function for `textDocument/semanticTokens/*`.
@@ -80,7 +225,13 @@ pub mod semantic_tokens {
         session: Arc<crate::core::Session>,
         params: lsp::SemanticTokensParams,
     ) -> anyhow::Result<Option<lsp::SemanticTokensResult>> {
-        crate::provider::text_document::semantic_tokens::full(session, params).await
+        let uri = params.text_document.uri.clone();
+        let version = session.get_snapshot(&uri).await?.version();
+        let token = crate::core::CancellationToken::new();
+        let work = crate::provider::text_document::semantic_tokens::full(session.clone(), params, token.clone());
+        let result = session.run(&token, work).await?;
+        session.ensure_version(&uri, version).await?;
+        Ok(result)
     }
 
     /// LSP message handler function for `textDocument/semanticTokens/range`.
@@ -88,6 +239,12 @@ pub mod semantic_tokens {
         session: Arc<crate::core::Session>,
         params: lsp::SemanticTokensRangeParams,
     ) -> anyhow::Result<Option<lsp::SemanticTokensRangeResult>> {
-        crate::provider::text_document::semantic_tokens::range(session, params).await
+        let uri = params.text_document.uri.clone();
+        let version = session.get_snapshot(&uri).await?.version();
+        let token = crate::core::CancellationToken::new();
+        let work = crate::provider::text_document::semantic_tokens::range(session.clone(), params, token.clone());
+        let result = session.run(&token, work).await?;
+        session.ensure_version(&uri, version).await?;
+        Ok(result)
     }
 }
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+/// LSP message handler function for the custom `wasm/assemble` request.
+pub async fn assemble(
+    session: Arc<crate::core::Session>,
+    params: crate::ext::AssembleParams,
+) -> anyhow::Result<crate::ext::AssembleResult> {
+    crate::provider::wasm::assemble(session, params).await
+}
+
+/// LSP message handler function for the custom `wasm/disassemble` request.
+pub async fn disassemble(
+    session: Arc<crate::core::Session>,
+    params: crate::ext::DisassembleParams,
+) -> anyhow::Result<crate::ext::DisassembleResult> {
+    crate::provider::wasm::disassemble(session, params).await
+}
+
+/// LSP message handler function for the custom `wasm/sizeReport` request.
+pub async fn size_report(
+    session: Arc<crate::core::Session>,
+    params: crate::ext::SizeReportParams,
+) -> anyhow::Result<crate::ext::SizeReportResult> {
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::wasm::size_report(session.clone(), params, token.clone());
+    session.run(&token, work).await
+}
+
+/// LSP message handler function for the custom `wasm/sourceLocation` request.
+pub async fn source_location(
+    session: Arc<crate::core::Session>,
+    params: crate::ext::SourceLocationParams,
+) -> anyhow::Result<Option<lsp::Location>> {
+    crate::provider::wasm::source_location(session, params).await
+}
@@ -0,0 +1,167 @@
+use anyhow::anyhow;
+use std::sync::Arc;
+
+/// LSP message handler function for `workspace/diagnostic`.
+pub async fn diagnostic(
+    session: Arc<crate::core::Session>,
+    params: crate::ext::WorkspaceDiagnosticParams,
+) -> anyhow::Result<crate::ext::WorkspaceDiagnosticReport> {
+    let token = crate::core::CancellationToken::new();
+    let work = crate::provider::workspace::diagnostic(session.clone(), params, token.clone());
+    session.run(&token, work).await
+}
+
+/// LSP message handler function for `workspace/didChangeConfiguration`.
+pub async fn did_change_configuration(
+    session: Arc<crate::core::Session>,
+    params: lsp::DidChangeConfigurationParams,
+) -> anyhow::Result<()> {
+    // The notification only carries the settings for clients without `workspace/configuration`
+    let settings = if session.configuration_support().await {
+        match pull_settings(&session).await? {
+            Some(settings) => settings,
+            // the client has no settings for the server, so the current ones are kept
+            None => return Ok(()),
+        }
+    } else {
+        crate::core::Settings::from_options(params.settings)?
+    };
+    configure(session, settings).await
+}
+
+/// LSP message handler function for `workspace/didChangeWatchedFiles`.
+///
+/// The cached state of the changed workspace files is invalidated. Project configuration files
+/// (`.wasm-lsp.toml`) are reloaded when they change, after which the providers affected by their
+/// settings are re-run.
+pub async fn did_change_watched_files(
+    session: Arc<crate::core::Session>,
+    params: lsp::DidChangeWatchedFilesParams,
+) -> anyhow::Result<()> {
+    let mut changed = false;
+    for event in params.changes {
+        session.workspace.write().await.invalidate(&event);
+        if !crate::core::project::is_project(&event.uri) {
+            continue;
+        }
+        changed = true;
+        if lsp::FileChangeType::DELETED == event.typ {
+            session.unload_project(&event.uri).await;
+            let version = Default::default();
+            session.client()?.publish_diagnostics(event.uri, vec![], version).await;
+        } else {
+            // reloading the configuration publishes its diagnostics
+            session.load_project(&event.uri).await?;
+        }
+    }
+    if changed {
+        refresh(session, true, true, true).await?;
+    }
+    Ok(())
+}
+
+/// LSP message handler function for `workspace/executeCommand`.
+pub async fn execute_command(
+    session: Arc<crate::core::Session>,
+    params: lsp::ExecuteCommandParams,
+) -> anyhow::Result<Option<serde_json::Value>> {
+    match params.command.as_str() {
+        crate::ext::ASSEMBLE_COMMAND => {
+            let argument = params
+                .arguments
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("missing argument for command :: command: {}", params.command))?;
+            let params = serde_json::from_value(argument)?;
+            let result = crate::handler::wasm::assemble(session, params).await?;
+            Ok(Some(serde_json::to_value(result)?))
+        },
+        crate::ext::SIZE_REPORT_COMMAND => {
+            let argument = params
+                .arguments
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("missing argument for command :: command: {}", params.command))?;
+            let params = serde_json::from_value(argument)?;
+            let result = crate::handler::wasm::size_report(session, params).await?;
+            Ok(Some(serde_json::to_value(result)?))
+        },
+        _ => Err(anyhow!("unknown command :: command: {}", params.command)),
+    }
+}
+
+/// Pull the settings of the server from the client via `workspace/configuration`, returning
+/// [`None`] if the client has no settings for the server.
+///
+/// The pulled settings are layered over the current settings, so that the settings which the
+/// client does not have keep their current values (e.g., from the `initializationOptions`).
+pub async fn pull_settings(session: &crate::core::Session) -> anyhow::Result<Option<crate::core::Settings>> {
+    let item = lsp::ConfigurationItem {
+        scope_uri: None,
+        section: Some(String::from(crate::core::SECTION)),
+    };
+    let mut values = session.client()?.configuration(vec![item]).await?;
+    match values.pop() {
+        Some(value) if !value.is_null() => Ok(Some(session.settings.read().await.merged(&value)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Apply new settings of the server, re-running the providers affected by the changed settings.
+pub async fn configure(session: Arc<crate::core::Session>, settings: crate::core::Settings) -> anyhow::Result<()> {
+    let previous = session.update_settings(settings.clone()).await;
+    // diagnostics depend on the enabled proposals, the severities of the rules and the host profile
+    let diagnostics = previous.proposals != settings.proposals
+        || previous.diagnostics.severities != settings.diagnostics.severities
+        || previous.host != settings.host
+        || previous.hosts != settings.hosts;
+    let semantic_tokens = previous.semantic_tokens != settings.semantic_tokens;
+    let inlay_hints = previous.inlay_hints != settings.inlay_hints;
+    refresh(session, diagnostics, semantic_tokens, inlay_hints).await
+}
+
+/// Re-run the providers for diagnostics, semantic tokens and inlay hints of the open documents,
+/// by publishing diagnostics again or by asking the client to refresh them.
+pub async fn refresh(
+    session: Arc<crate::core::Session>,
+    diagnostics: bool,
+    semantic_tokens: bool,
+    inlay_hints: bool,
+) -> anyhow::Result<()> {
+    let client = session.client()?;
+
+    if diagnostics {
+        session.workspace.write().await.clear_reports();
+        if session.diagnostic_pull_support().await {
+            // the client capabilities for refreshing (`workspace.diagnostics`) are not recorded
+            let refresh = client.send_request::<crate::ext::WorkspaceDiagnosticRefresh>(());
+            if let Err(error) = refresh.await {
+                log::debug!("failed to refresh pulled diagnostics :: error: {}", error);
+            }
+        } else {
+            for uri in session.document_uris().await {
+                let version = session.get_snapshot(&uri).await?.version();
+                session.diagnostics.schedule(session.clone(), uri, version).await?;
+            }
+        }
+    }
+
+    if semantic_tokens && session.semantic_tokens_refresh_support().await {
+        client.semantic_tokens_refresh().await?;
+    }
+
+    if inlay_hints && session.inlay_hint_refresh_support().await {
+        client.send_request::<lsp::request::InlayHintRefreshRequest>(()).await?;
+    }
+
+    Ok(())
+}
+
+/// Load the project configuration governing the document at `uri` if it has not been loaded yet.
+///
+/// The diagnostics for its malformed entries are published by
+/// [`crate::core::Session::load_project`], like for every other path which loads the configuration.
+pub async fn discover_project(session: Arc<crate::core::Session>, uri: &lsp::Url) -> anyhow::Result<()> {
+    session.project(uri).await;
+    Ok(())
+}
@@ -9,6 +9,9 @@
 /// Core definitions for server functionality.
 pub mod core;
 
+/// Definitions for the custom LSP protocol extensions supported by the server.
+pub mod ext;
+
 /// LSP message handler functions.
 pub mod handler;
 
@@ -18,8 +21,13 @@ pub mod metadata;
 /// LSP feature provider functions.
 pub mod provider;
 
-use std::sync::Arc;
-use tower_lsp::jsonrpc;
+use futures::future::{BoxFuture, FutureExt, TryFutureExt};
+use lsp::request::Request;
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_lsp::{jsonrpc, ClientSocket, LspService};
 
 /// The WebAssembly language server structure.
 pub struct Server {
@@ -31,41 +39,118 @@ pub struct Server {
 
 impl Server {
     /// Create a new [`Server`] instance.
-    pub fn new(languages: crate::core::SessionLanguages, client: tower_lsp::Client) -> anyhow::Result<Self> {
-        let session = Arc::new(crate::core::Session::new(languages, Some(client.clone()))?);
+    ///
+    /// When a [`crate::core::Spawner`] is given, diagnostics are computed in the background.
+    pub fn new(
+        languages: crate::core::SessionLanguages,
+        client: tower_lsp::Client,
+        spawner: Option<crate::core::Spawner>,
+    ) -> anyhow::Result<Self> {
+        let session = Arc::new(crate::core::Session::new(languages, Some(client.clone()), spawner)?);
         Ok(Server { client, session })
     }
 
+    /// Build the [`Service`] for a [Server], including the handlers for the custom (or not yet
+    /// natively supported) methods.
+    ///
+    /// The session is created before the service, so that failing to create it is reported to the
+    /// caller rather than panicking within the builder of the service.
+    pub fn service(
+        languages: crate::core::SessionLanguages,
+        spawner: Option<crate::core::Spawner>,
+    ) -> anyhow::Result<(Service, ClientSocket)> {
+        let session = Arc::new(crate::core::Session::new(languages, None, spawner)?);
+        let (inner, socket) = LspService::build({
+            let session = session.clone();
+            |client| {
+                session.set_client(client.clone());
+                Server { client, session }
+            }
+        })
+        .custom_method(crate::ext::DocumentDiagnostic::METHOD, Server::diagnostic)
+        .custom_method(lsp::request::InlayHintRequest::METHOD, Server::inlay_hint)
+        .custom_method(crate::ext::WorkspaceDiagnostic::METHOD, Server::workspace_diagnostic)
+        .custom_method(crate::ext::Assemble::METHOD, Server::assemble)
+        .custom_method(crate::ext::Disassemble::METHOD, Server::disassemble)
+        .custom_method(crate::ext::SizeReport::METHOD, Server::size_report)
+        .custom_method(crate::ext::SourceLocation::METHOD, Server::source_location)
+        .finish();
+        Ok((Service { inner, session }, socket))
+    }
+
+    /// Convenience function for building the [`crate::ext::DiagnosticOptions`] for [Server], which
+    /// are advertised as `diagnosticProvider` along with the [`lsp::ServerCapabilities`].
+    pub fn diagnostic_capabilities() -> crate::ext::DiagnosticOptions {
+        crate::ext::DiagnosticOptions {
+            // diagnostics for imports depend on the exports of the other files of the workspace
+            inter_file_dependencies: true,
+            workspace_diagnostics: true,
+            ..Default::default()
+        }
+    }
+
     /// Convenience function for building [`lsp::ServerCapabilities`] for [Server].
     pub fn capabilities() -> lsp::ServerCapabilities {
+        let code_lens_provider = Some(lsp::CodeLensOptions {
+            resolve_provider: Some(false),
+        });
+
+        let definition_provider = Some(lsp::OneOf::Left(true));
+
         let document_symbol_provider = Some(lsp::OneOf::Left(true));
 
-        // let semantic_tokens_provider = {
-        //     let token_types = vec![
-        //         lsp::SemanticTokenType::COMMENT,
-        //         lsp::SemanticTokenType::FUNCTION,
-        //         lsp::SemanticTokenType::KEYWORD,
-        //         lsp::SemanticTokenType::NAMESPACE,
-        //         lsp::SemanticTokenType::OPERATOR,
-        //         lsp::SemanticTokenType::PARAMETER,
-        //         lsp::SemanticTokenType::STRING,
-        //         lsp::SemanticTokenType::TYPE,
-        //         lsp::SemanticTokenType::TYPE_PARAMETER,
-        //         lsp::SemanticTokenType::VARIABLE,
-        //     ];
-        //     let token_modifiers = Default::default();
-
-        //     let options = lsp::SemanticTokensOptions {
-        //         legend: lsp::SemanticTokensLegend {
-        //             token_types,
-        //             token_modifiers,
-        //         },
-        //         range: Some(true),
-        //         full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
-        //         ..Default::default()
-        //     };
-        //     Some(lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options))
-        // };
+        let execute_command_provider = Some(lsp::ExecuteCommandOptions {
+            commands: vec![
+                String::from(crate::ext::ASSEMBLE_COMMAND),
+                String::from(crate::ext::SIZE_REPORT_COMMAND),
+            ],
+            ..Default::default()
+        });
+
+        let document_formatting_provider = Some(lsp::OneOf::Left(true));
+
+        let hover_provider = Some(lsp::HoverProviderCapability::Simple(true));
+
+        let inlay_hint_provider = Some(lsp::OneOf::Left(true));
+
+        let references_provider = Some(lsp::OneOf::Left(true));
+
+        let rename_provider = Some(lsp::OneOf::Left(true));
+
+        let semantic_tokens_provider = {
+            let token_types = vec![
+                lsp::SemanticTokenType::COMMENT,
+                lsp::SemanticTokenType::EVENT,
+                lsp::SemanticTokenType::FUNCTION,
+                lsp::SemanticTokenType::KEYWORD,
+                lsp::SemanticTokenType::NAMESPACE,
+                lsp::SemanticTokenType::OPERATOR,
+                lsp::SemanticTokenType::PARAMETER,
+                lsp::SemanticTokenType::PROPERTY,
+                lsp::SemanticTokenType::STRING,
+                lsp::SemanticTokenType::TYPE,
+                lsp::SemanticTokenType::TYPE_PARAMETER,
+                lsp::SemanticTokenType::VARIABLE,
+            ];
+            let token_modifiers = vec![
+                lsp::SemanticTokenModifier::DECLARATION,
+                lsp::SemanticTokenModifier::DEFAULT_LIBRARY,
+                lsp::SemanticTokenModifier::READONLY,
+                crate::provider::text_document::semantic_tokens::modifier::EXPORTED,
+                crate::provider::text_document::semantic_tokens::modifier::UNREACHABLE,
+            ];
+
+            let options = lsp::SemanticTokensOptions {
+                legend: lsp::SemanticTokensLegend {
+                    token_types,
+                    token_modifiers,
+                },
+                range: Some(true),
+                full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
+                ..Default::default()
+            };
+            Some(lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options))
+        };
 
         let text_document_sync = {
             let options = lsp::TextDocumentSyncOptions {
@@ -78,13 +163,158 @@ impl Server {
 
         lsp::ServerCapabilities {
             text_document_sync,
+            code_lens_provider,
+            definition_provider,
+            document_formatting_provider,
             document_symbol_provider,
-            // semantic_tokens_provider,
+            execute_command_provider,
+            hover_provider,
+            inlay_hint_provider,
+            references_provider,
+            rename_provider,
+            semantic_tokens_provider,
             ..Default::default()
         }
     }
 }
 
+/// The [`LspService`] for a [Server].
+///
+/// The `initialize` request is also handled here for the parts of LSP 3.17 which `lsp-types` 0.93
+/// does not represent (see [`crate::ext`]), and which would otherwise be dropped by `tower-lsp`:
+/// the client capabilities for the pull model for diagnostics are recorded in the session before
+/// the request is handled, and the [`Server::diagnostic_capabilities`] are added to the response.
+pub struct Service {
+    inner: LspService<Server>,
+    session: Arc<crate::core::Session>,
+}
+
+impl Service {
+    /// Add the [`Server::diagnostic_capabilities`] to the (successful) response to `initialize`.
+    fn advertise_diagnostics(response: jsonrpc::Response) -> jsonrpc::Response {
+        let (id, result) = response.into_parts();
+        let result = result.map(|mut result| {
+            let capabilities = result
+                .get_mut("capabilities")
+                .and_then(serde_json::Value::as_object_mut);
+            if let (Some(capabilities), Ok(options)) =
+                (capabilities, serde_json::to_value(Server::diagnostic_capabilities()))
+            {
+                capabilities.insert(String::from("diagnosticProvider"), options);
+            }
+            result
+        });
+        jsonrpc::Response::from_parts(id, result)
+    }
+}
+
+impl tower_service::Service<jsonrpc::Request> for Service {
+    type Error = tower_lsp::ExitedError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = Option<jsonrpc::Response>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: jsonrpc::Request) -> Self::Future {
+        if request.method() != lsp::request::Initialize::METHOD {
+            return self.inner.call(request);
+        }
+        let capabilities = request
+            .params()
+            .and_then(|params| params.pointer("/capabilities/textDocument/diagnostic"))
+            .and_then(|capabilities| serde_json::from_value(capabilities.clone()).ok());
+        if let Some(capabilities) = capabilities {
+            self.session.set_diagnostic_client_capabilities(capabilities);
+        }
+        self.inner
+            .call(request)
+            .map_ok(|response| response.map(Service::advertise_diagnostics))
+            .boxed()
+    }
+}
+
+impl Server {
+    /// Report the failure of the handler for the notification `method` to the client.
+    ///
+    /// Notifications have no response, so the failure is logged with `window/logMessage`. When
+    /// the failure was caused by invalid input from the client (e.g., an unsupported language-id)
+    /// it is also shown to the user with `window/showMessage`.
+    async fn report(&self, method: &str, error: anyhow::Error) {
+        let typ = lsp::MessageType::ERROR;
+        let message = format!("'{}' failed :: error: {}", method, error);
+        if crate::core::error::is_client_error(&error) {
+            self.client.show_message(typ, message.clone()).await;
+        }
+        self.client.log_message(typ, message).await;
+    }
+
+    /// Handler for the LSP `textDocument/diagnostic` request.
+    pub async fn diagnostic(
+        &self,
+        params: crate::ext::DocumentDiagnosticParams,
+    ) -> jsonrpc::Result<crate::ext::DocumentDiagnosticReport> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::diagnostic(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    /// Handler for the LSP `textDocument/inlayHint` request.
+    pub async fn inlay_hint(&self, params: lsp::InlayHintParams) -> jsonrpc::Result<Option<Vec<lsp::InlayHint>>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::inlay_hint(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    /// Handler for the LSP `workspace/diagnostic` request.
+    pub async fn workspace_diagnostic(
+        &self,
+        params: crate::ext::WorkspaceDiagnosticParams,
+    ) -> jsonrpc::Result<crate::ext::WorkspaceDiagnosticReport> {
+        let session = self.session.clone();
+        let result = crate::handler::workspace::diagnostic(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    /// Handler for the custom `wasm/assemble` request.
+    pub async fn assemble(&self, params: crate::ext::AssembleParams) -> jsonrpc::Result<crate::ext::AssembleResult> {
+        let session = self.session.clone();
+        let result = crate::handler::wasm::assemble(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    /// Handler for the custom `wasm/disassemble` request.
+    pub async fn disassemble(
+        &self,
+        params: crate::ext::DisassembleParams,
+    ) -> jsonrpc::Result<crate::ext::DisassembleResult> {
+        let session = self.session.clone();
+        let result = crate::handler::wasm::disassemble(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    /// Handler for the custom `wasm/sizeReport` request.
+    pub async fn size_report(
+        &self,
+        params: crate::ext::SizeReportParams,
+    ) -> jsonrpc::Result<crate::ext::SizeReportResult> {
+        let session = self.session.clone();
+        let result = crate::handler::wasm::size_report(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    /// Handler for the custom `wasm/sourceLocation` request.
+    pub async fn source_location(
+        &self,
+        params: crate::ext::SourceLocationParams,
+    ) -> jsonrpc::Result<Option<lsp::Location>> {
+        let session = self.session.clone();
+        let result = crate::handler::wasm::source_location(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+}
+
 #[tower_lsp::async_trait]
 impl tower_lsp::LanguageServer for Server {
     async fn initialize(&self, params: lsp::InitializeParams) -> jsonrpc::Result<lsp::InitializeResult> {
@@ -93,7 +323,13 @@ impl tower_lsp::LanguageServer for Server {
         Ok(result)
     }
 
-    async fn initialized(&self, _: lsp::InitializedParams) {
+    async fn initialized(&self, params: lsp::InitializedParams) {
+        let session = self.session.clone();
+        if let Err(error) = crate::handler::initialized(session, params).await {
+            let typ = lsp::MessageType::WARNING;
+            let message = format!("failed to retrieve the settings :: error: {}", error);
+            self.client.log_message(typ, message).await;
+        }
         let typ = lsp::MessageType::INFO;
         let message = "WebAssembly language server initialized!";
         self.client.log_message(typ, message).await;
@@ -105,19 +341,53 @@ impl tower_lsp::LanguageServer for Server {
 
     async fn did_open(&self, params: lsp::DidOpenTextDocumentParams) {
         let session = self.session.clone();
-        crate::handler::text_document::did_open(session, params).await.unwrap()
+        if let Err(error) = crate::handler::text_document::did_open(session, params).await {
+            self.report("textDocument/didOpen", error).await;
+        }
     }
 
     async fn did_change(&self, params: lsp::DidChangeTextDocumentParams) {
         let session = self.session.clone();
-        crate::handler::text_document::did_change(session, params)
-            .await
-            .unwrap()
+        if let Err(error) = crate::handler::text_document::did_change(session, params).await {
+            self.report("textDocument/didChange", error).await;
+        }
     }
 
     async fn did_close(&self, params: lsp::DidCloseTextDocumentParams) {
         let session = self.session.clone();
-        crate::handler::text_document::did_close(session, params).await.unwrap()
+        if let Err(error) = crate::handler::text_document::did_close(session, params).await {
+            self.report("textDocument/didClose", error).await;
+        }
+    }
+
+    async fn did_change_configuration(&self, params: lsp::DidChangeConfigurationParams) {
+        let session = self.session.clone();
+        if let Err(error) = crate::handler::workspace::did_change_configuration(session, params).await {
+            let typ = lsp::MessageType::WARNING;
+            let message = format!("failed to apply the settings :: error: {}", error);
+            self.client.log_message(typ, message).await;
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: lsp::DidChangeWatchedFilesParams) {
+        let session = self.session.clone();
+        if let Err(error) = crate::handler::workspace::did_change_watched_files(session, params).await {
+            let typ = lsp::MessageType::WARNING;
+            let message = format!("failed to reload the project configuration :: error: {}", error);
+            self.client.log_message(typ, message).await;
+        }
+    }
+
+    async fn code_lens(&self, params: lsp::CodeLensParams) -> jsonrpc::Result<Option<Vec<lsp::CodeLens>>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::code_lens(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    async fn formatting(&self, params: lsp::DocumentFormattingParams) -> jsonrpc::Result<Option<Vec<lsp::TextEdit>>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::formatting(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
     }
 
     async fn document_symbol(
@@ -129,6 +399,39 @@ impl tower_lsp::LanguageServer for Server {
         Ok(result.map_err(crate::core::IntoJsonRpcError)?)
     }
 
+    async fn execute_command(&self, params: lsp::ExecuteCommandParams) -> jsonrpc::Result<Option<serde_json::Value>> {
+        let session = self.session.clone();
+        let result = crate::handler::workspace::execute_command(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    async fn goto_definition(
+        &self,
+        params: lsp::GotoDefinitionParams,
+    ) -> jsonrpc::Result<Option<lsp::GotoDefinitionResponse>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::definition(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    async fn hover(&self, params: lsp::HoverParams) -> jsonrpc::Result<Option<lsp::Hover>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::hover(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    async fn references(&self, params: lsp::ReferenceParams) -> jsonrpc::Result<Option<Vec<lsp::Location>>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::references(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
+    async fn rename(&self, params: lsp::RenameParams) -> jsonrpc::Result<Option<lsp::WorkspaceEdit>> {
+        let session = self.session.clone();
+        let result = crate::handler::text_document::rename(session, params).await;
+        Ok(result.map_err(crate::core::IntoJsonRpcError)?)
+    }
+
     async fn semantic_tokens_full(
         &self,
         params: lsp::SemanticTokensParams,
@@ -2,3 +2,9 @@
 
 /// Provider definitions for LSP `textDocument/documentSymbol`
 pub mod text_document;
+
+/// Provider definitions for the custom `wasm/*` requests.
+pub mod wasm;
+
+/// Provider definitions for LSP `workspace/*`
+pub mod workspace;
@@ -1,11 +1,43 @@
+/// Provider definitions for LSP `textDocument/codeLens`.
+pub mod code_lens;
+
+/// Provider definitions for LSP `textDocument/definition`.
+pub mod definition;
+
+/// Provider definitions for LSP `textDocument/diagnostic`.
+pub mod diagnostic;
+
 /// Provider definitions for LSP `textDocument/documentSymbol`
 pub mod document_symbol;
 
+/// Provider definitions for LSP `textDocument/formatting`.
+pub mod formatting;
+
+/// Provider definitions for LSP `textDocument/hover`.
+pub mod hover;
+
+/// Provider definitions for LSP `textDocument/inlayHint`.
+pub mod inlay_hint;
+
 /// Provider definitions for LSP `textDocument/publishDiagnostics`.
 pub mod publish_diagnostics;
 
+/// Provider definitions for LSP `textDocument/references`.
+pub mod references;
+
+/// Provider definitions for LSP `textDocument/rename`.
+pub mod rename;
+
 /// Provider definitions for LSP `textDocument/semanticTokens/*`
 pub mod semantic_tokens;
 
+pub use code_lens::code_lens;
+pub use definition::definition;
+pub use diagnostic::diagnostic;
 pub use document_symbol::document_symbol;
+pub use formatting::formatting;
+pub use hover::hover;
+pub use inlay_hint::inlay_hint;
 pub use publish_diagnostics::*;
+pub use references::references;
+pub use rename::rename;
@@ -0,0 +1,65 @@
+use crate::core::{self, analysis::IndexSpace};
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+/// Provider function for LSP `textDocument/codeLens`.
+///
+/// Each function definition is annotated with the size of its encoded body and the number of
+/// instructions it contains.
+pub async fn code_lens(
+    session: Arc<core::Session>,
+    params: lsp::CodeLensParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<Option<Vec<lsp::CodeLens>>> {
+    let uri = &params.text_document.uri;
+    let snapshot = session.get_snapshot(uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wast | core::Language::Wit = text.language {
+        return Ok(None);
+    }
+    let content = &text.content;
+
+    // documents which fail to encode are already covered by diagnostics
+    let encoding = match snapshot.encoding() {
+        Ok(encoding) => encoding,
+        Err(_) => return Ok(None),
+    };
+
+    let analysis = snapshot.analysis(&token)?;
+    // the lenses open the size report of the document
+    let command = String::from(crate::ext::SIZE_REPORT_COMMAND);
+    let arguments = {
+        let text_document = lsp::TextDocumentIdentifier::new(uri.clone());
+        Some(vec![serde_json::to_value(crate::ext::SizeReportParams {
+            text_document,
+        })?])
+    };
+
+    let lenses = analysis
+        .definitions
+        .iter()
+        .filter(|it| IndexSpace::Func == it.space && !it.imported)
+        .filter_map(|definition| {
+            let function = encoding
+                .source_map
+                .function_within(definition.range.start_byte() as usize .. definition.range.end_byte() as usize)?;
+            let size = function.body.len();
+            let count = function.instructions.len();
+            let title = format!(
+                "{} {}, {} {}",
+                size,
+                if 1 == size { "byte" } else { "bytes" },
+                count,
+                if 1 == count { "instruction" } else { "instructions" },
+            );
+            Some(lsp::CodeLens {
+                range: content.tree_sitter_range_to_lsp_range(definition.range.clone()),
+                command: Some(lsp::Command::new(title, command.clone(), arguments.clone())),
+                data: None,
+            })
+        })
+        .collect();
+
+    Ok(Some(lenses))
+}
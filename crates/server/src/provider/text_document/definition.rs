@@ -0,0 +1,322 @@
+use crate::core::{
+    self,
+    language::{wat, wit},
+};
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+/// Provider function for LSP `textDocument/definition`.
+///
+/// Go-to-definition resolves the `use` paths of `.wit` documents: the interface of a path (e.g.,
+/// `wasi:io/streams` or a local `types`) resolves to its `interface` item, and the names imported
+/// by a `use` (e.g., the `error` of `use types.{error}`) resolve to the type items within it. Paths
+/// are resolved in the document itself, then in the other open documents, and finally in the
+/// `.wit` files of the workspace folders.
+///
+/// For `.wat` documents, the module names of imports resolve to the files they are mapped to in
+/// the project configuration (`[modules]` of `.wasm-lsp.toml`), and the entity names of imports
+/// resolve to the corresponding exports within those files.
+///
+/// For `.wat` and `.wast` documents, indices (symbolic or numeric) resolve to the definition of the
+/// entity they refer to in any index space, e.g., the memory index of a load or store, or the tag
+/// of a `throw`.
+pub async fn definition(
+    session: Arc<core::Session>,
+    params: lsp::GotoDefinitionParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<Option<lsp::GotoDefinitionResponse>> {
+    let params = params.text_document_position_params;
+    let uri = params.text_document.uri;
+
+    let snapshot = session.get_snapshot(&uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    if core::Language::Wat == snapshot.text.language {
+        if let Some(response) = import(session.clone(), &snapshot, params.position, &token).await? {
+            return Ok(Some(response));
+        }
+    }
+    if let core::Language::Wast | core::Language::Wat = snapshot.text.language {
+        return Ok(index(&snapshot, params.position, &token)?
+            .map(|range| lsp::GotoDefinitionResponse::Scalar(lsp::Location::new(uri, range))));
+    }
+
+    let (target, package) = {
+        let text = &snapshot.text;
+        // NOTE: only the `use` paths of `.wit` documents are (yet) resolved
+        if core::Language::Wit != text.language {
+            return Ok(None);
+        }
+        let content = &text.content;
+        let root = snapshot.tree.root_node();
+
+        let target = match {
+            let range = lsp::Range::new(params.position, params.position);
+            let range = content.lsp_range_to_tree_sitter_range(range)?;
+            root.descendant_for_point_range(range.start_point(), range.end_point())
+        }
+        .and_then(|node| Target::new(content, node))
+        {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+
+        let package = package_name(content, &root);
+        if target.package.is_none() || target.package == package {
+            if let Some(range) = target.resolve(content, &root) {
+                return Ok(Some(lsp::GotoDefinitionResponse::Scalar(lsp::Location::new(
+                    uri, range,
+                ))));
+            }
+        }
+
+        (target, package)
+    };
+
+    // paths without a package refer to interfaces of the same package
+    let expected = target.package.clone().or(package);
+
+    // resolve the path in the other open documents
+    let opened = session.document_uris().await;
+    for other in opened.iter().filter(|other| **other != uri) {
+        let snapshot = session.get_snapshot(other).await?;
+        let text = &snapshot.text;
+        if core::Language::Wit != text.language {
+            continue;
+        }
+        let root = snapshot.tree.root_node();
+        if package_name(&text.content, &root) != expected {
+            continue;
+        }
+        if let Some(range) = target.resolve(&text.content, &root) {
+            let location = lsp::Location::new(other.clone(), range);
+            return Ok(Some(lsp::GotoDefinitionResponse::Scalar(location)));
+        }
+    }
+
+    // resolve the path in the workspace files which are not currently open
+    for other in session.workspace_files().await? {
+        if opened.contains(&other) || core::Language::Wit != core::workspace::language(&other)? {
+            continue;
+        }
+        let text_document = {
+            let language_id = core::Language::Wit.id().into();
+            let version = Default::default();
+            let text = session.read_workspace_file(&other).await?;
+            lsp::TextDocumentItem::new(other.clone(), language_id, version, text)
+        };
+        let params = lsp::DidOpenTextDocumentParams { text_document };
+        if let Some(document) = core::Document::open(session.clone(), params)? {
+            let root = document.tree.root_node();
+            if package_name(&document.content, &root) != expected {
+                continue;
+            }
+            if let Some(range) = target.resolve(&document.content, &root) {
+                let location = lsp::Location::new(other, range);
+                return Ok(Some(lsp::GotoDefinitionResponse::Scalar(location)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Resolve the index (or identifier) at `position` to the range of the definition it refers to,
+/// i.e., the identifier of the definition or (for unnamed definitions) the entire definition.
+fn index(
+    snapshot: &core::Snapshot,
+    position: lsp::Position,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<lsp::Range>> {
+    let content = &snapshot.text.content;
+    let range = content.lsp_range_to_tree_sitter_range(lsp::Range::new(position, position))?;
+    let analysis = snapshot.analysis(token)?;
+    Ok(analysis.target_at(range.start_byte()).map(|id| {
+        let definition = &analysis.definitions[id];
+        let range = definition.identifier.as_ref().unwrap_or(&definition.range).clone();
+        content.tree_sitter_range_to_lsp_range(range)
+    }))
+}
+
+/// Resolve the module or entity name of an import at `position` through the module mappings of
+/// the project configuration.
+async fn import(
+    session: Arc<core::Session>,
+    snapshot: &core::Snapshot,
+    position: lsp::Position,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<lsp::GotoDefinitionResponse>> {
+    let uri = &snapshot.uri;
+    let contains = |range: lsp::Range| range.start <= position && position <= range.end;
+
+    let (module, name) = {
+        let text = &snapshot.text;
+        let analysis = snapshot.analysis(token)?;
+        let found = analysis.imports.iter().find_map(|import| {
+            if contains(text.content.tree_sitter_range_to_lsp_range(import.module_range.clone())) {
+                Some((import.module.clone(), None))
+            } else if contains(text.content.tree_sitter_range_to_lsp_range(import.name_range.clone())) {
+                Some((import.module.clone(), Some(import.name.clone())))
+            } else {
+                None
+            }
+        });
+        match found {
+            Some(found) => found,
+            None => return Ok(None),
+        }
+    };
+
+    let target = match session.project(uri).await {
+        Some(project) => match project.modules.get(&module) {
+            Some(target) => target.clone(),
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+
+    // the module name resolves to the start of the file, and the entity name to its export
+    let range = match name {
+        Some(name) => export(&session, &target, &name).await?.unwrap_or_default(),
+        None => Default::default(),
+    };
+    Ok(Some(lsp::GotoDefinitionResponse::Scalar(lsp::Location::new(
+        target, range,
+    ))))
+}
+
+/// Find the range of the name of the export `name` within the `.wat` document at `uri`, which is
+/// either open or read from the workspace.
+async fn export(session: &Arc<core::Session>, uri: &lsp::Url, name: &str) -> anyhow::Result<Option<lsp::Range>> {
+    if let Ok(snapshot) = session.get_snapshot(uri).await {
+        if core::Language::Wat != snapshot.text.language {
+            return Ok(None);
+        }
+        return Ok(find_export(&snapshot.text.content, snapshot.tree.root_node(), name));
+    }
+
+    if core::Language::Wat != core::workspace::language(uri)? {
+        return Ok(None);
+    }
+    let text_document = {
+        let language_id = core::Language::Wat.id().into();
+        let version = Default::default();
+        let text = session.read_workspace_file(uri).await?;
+        lsp::TextDocumentItem::new(uri.clone(), language_id, version, text)
+    };
+    let params = lsp::DidOpenTextDocumentParams { text_document };
+    Ok(core::Document::open(session.clone(), params)?
+        .and_then(|document| find_export(&document.content, document.tree.root_node(), name)))
+}
+
+/// Find the range of the name of the export `name` (either inline or a module field) in a tree.
+fn find_export(content: &ropey::Rope, root: tree_sitter::Node, name: &str) -> Option<lsp::Range> {
+    let mut work = vec![root];
+    while let Some(node) = work.pop() {
+        if wat::kind::EXPORT == node.kind_id() || wat::kind::MODULE_FIELD_EXPORT == node.kind_id() {
+            let found = node.children(&mut node.walk()).find(|it| {
+                wat::kind::NAME == it.kind_id() && content.utf8_text_for_tree_sitter_node(it).trim_matches('"') == name
+            });
+            if let Some(found) = found {
+                return Some(content.tree_sitter_range_to_lsp_range(found.range()));
+            }
+        } else {
+            work.extend(node.children(&mut node.walk()));
+        }
+    }
+    None
+}
+
+/// The entity referenced by (a part of) a `use`.
+struct Target {
+    /// The package of the used interface (e.g., `wasi:io`), if the path is qualified.
+    package: Option<String>,
+    /// The name of the used interface.
+    interface: String,
+    /// The name of a type imported from the interface.
+    name: Option<String>,
+}
+
+impl Target {
+    /// Compute the target for an identifier within a `use` path or `use` name.
+    fn new(content: &ropey::Rope, node: tree_sitter::Node) -> Option<Self> {
+        if wit::kind::IDENTIFIER != node.kind_id() {
+            return None;
+        }
+        let parent = node.parent()?;
+        let (path, name) = if wit::kind::USE_PATH == parent.kind_id() {
+            (parent, None)
+        } else if wit::kind::USE_NAME == parent.kind_id() {
+            // the alias of a used name is a declaration rather than a reference
+            if parent.child_by_field_id(wit::field::NAME)?.id() != node.id() {
+                return None;
+            }
+            let item = parent.parent()?;
+            let path = item
+                .children(&mut item.walk())
+                .find(|it| wit::kind::USE_PATH == it.kind_id())?;
+            (path, Some(text(content, &node)))
+        } else {
+            return None;
+        };
+
+        let package = match (
+            path.child_by_field_id(wit::field::NAMESPACE),
+            path.child_by_field_id(wit::field::PACKAGE),
+        ) {
+            (Some(namespace), Some(package)) => {
+                Some(format!("{}:{}", text(content, &namespace), text(content, &package)))
+            },
+            _ => None,
+        };
+        let interface = text(content, &path.child_by_field_id(wit::field::INTERFACE)?);
+
+        Some(Self {
+            package,
+            interface,
+            name,
+        })
+    }
+
+    /// Resolve the target within a document, returning the range of the defining identifier.
+    fn resolve(&self, content: &ropey::Rope, root: &tree_sitter::Node) -> Option<lsp::Range> {
+        let interface = named_child(content, root, &[wit::kind::INTERFACE_ITEM], &self.interface)?;
+        let item = match &self.name {
+            Some(name) => named_child(content, &interface, wit::grouped::TYPE_ITEMS, name)?,
+            None => interface,
+        };
+        let identifier = item.child_by_field_id(wit::field::IDENTIFIER)?;
+        Some(content.tree_sitter_range_to_lsp_range(identifier.range()))
+    }
+}
+
+/// Find the child of a node with one of the given kinds which is named by the given identifier.
+fn named_child<'tree>(
+    content: &ropey::Rope,
+    node: &tree_sitter::Node<'tree>,
+    kinds: &[u16],
+    name: &str,
+) -> Option<tree_sitter::Node<'tree>> {
+    node.children(&mut node.walk()).find(|it| {
+        kinds.contains(&it.kind_id())
+            && it
+                .child_by_field_id(wit::field::IDENTIFIER)
+                .map_or(false, |identifier| text(content, &identifier) == name)
+    })
+}
+
+/// Compute the name (without version) of the package declared by a document (e.g., `wasi:io`).
+fn package_name(content: &ropey::Rope, root: &tree_sitter::Node) -> Option<String> {
+    let decl = root
+        .children(&mut root.walk())
+        .find(|it| wit::kind::PACKAGE_DECL == it.kind_id())?;
+    let name = decl.child_by_field_id(wit::field::NAME)?;
+    let namespace = name.child_by_field_id(wit::field::NAMESPACE)?;
+    let package = name.child_by_field_id(wit::field::PACKAGE)?;
+    Some(format!("{}:{}", text(content, &namespace), text(content, &package)))
+}
+
+/// Compute the text of an identifier, without the `%` prefix of escaped keywords.
+fn text(content: &ropey::Rope, node: &tree_sitter::Node) -> String {
+    let text = content.utf8_text_for_tree_sitter_node(node);
+    text.trim_start_matches('%').to_string()
+}
@@ -0,0 +1,49 @@
+use crate::core;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// Compute the `resultId` identifying a set of diagnostics for pull-model reports.
+pub fn result_id(diagnostics: &[lsp::Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(diagnostics).unwrap_or_default().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Provider function for LSP `textDocument/diagnostic`.
+pub async fn diagnostic(
+    session: Arc<core::Session>,
+    params: crate::ext::DocumentDiagnosticParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<crate::ext::DocumentDiagnosticReport> {
+    let uri = &params.text_document.uri;
+
+    let items = {
+        let settings = session.document_settings(uri).await;
+        let snapshot = session.get_snapshot(uri).await?;
+        let token = token.with_snapshot(snapshot.clone());
+        let mut items = super::diagnostics(&snapshot.tree, &snapshot.text, &settings, &token)?;
+        let analysis = snapshot.analysis(&token)?;
+        items.extend(super::semantic_diagnostics(
+            &snapshot.text,
+            &analysis,
+            &settings,
+            &token,
+        )?);
+        items
+    };
+    let result_id = result_id(&items);
+
+    let report = if params.previous_result_id.as_deref() == Some(result_id.as_str()) {
+        crate::ext::DocumentDiagnosticReport::Unchanged(crate::ext::UnchangedDocumentDiagnosticReport { result_id })
+    } else {
+        crate::ext::DocumentDiagnosticReport::Full(crate::ext::FullDocumentDiagnosticReport {
+            result_id: Some(result_id),
+            items,
+        })
+    };
+
+    Ok(report)
+}
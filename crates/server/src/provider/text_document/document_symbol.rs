@@ -4,12 +4,18 @@ use crate::core::{self};
 use lsp_text::RopeExt;
 use std::sync::Arc;
 
+/// Provider definitions for LSP `textDocument/documentSymbol` for component model documents.
+pub mod component;
+
 /// Provider definitions for LSP `textDocument/documentSymbol` for `.wast` documents.
 pub mod wast;
 
 /// Provider definitions for LSP `textDocument/documentSymbol` for `.wat` documents.
 pub mod wat;
 
+/// Provider definitions for LSP `textDocument/documentSymbol` for `.wit` documents.
+pub mod wit;
+
 /// Encodes data for constructing upcoming DocumentSymbols.
 #[derive(Clone, Debug)]
 pub(self) struct Data<'tree> {
@@ -68,17 +74,53 @@ pub(self) fn symbol_range(
     }
 }
 
+/// Collect the descendants of a node with one of the given kinds (without descending into them).
+pub(self) fn descendants<'tree>(node: &tree_sitter::Node<'tree>, kinds: &[u16]) -> Vec<tree_sitter::Node<'tree>> {
+    let mut result = vec![];
+    let mut work = node.children(&mut node.walk()).collect::<Vec<_>>();
+    work.reverse();
+    while let Some(node) = work.pop() {
+        if kinds.contains(&node.kind_id()) {
+            result.push(node);
+        } else {
+            let mut children = node.children(&mut node.walk()).collect::<Vec<_>>();
+            children.reverse();
+            work.extend(children);
+        }
+    }
+    result
+}
+
+/// Compute the detail for a memory or table symbol from its type (e.g., `i64 1 16 shared`).
+pub(self) fn type_detail(
+    content: &ropey::Rope,
+    node: &tree_sitter::Node,
+    type_kinds: &[u16],
+    address_type: u16,
+) -> Option<String> {
+    let ty = descendants(node, type_kinds).into_iter().next()?;
+    let text = content.utf8_text_for_tree_sitter_node(&ty);
+    let mut detail = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    // memories and tables without an explicit address type are 32-bit
+    if !ty.children(&mut ty.walk()).any(|it| address_type == it.kind_id()) {
+        detail.insert_str(0, "i32 ");
+    }
+    Some(detail)
+}
+
 /// Provider function for LSP `textDocument/documentSymbol`.
 pub async fn document_symbol(
     session: Arc<core::Session>,
     params: lsp::DocumentSymbolParams,
+    token: core::CancellationToken,
 ) -> anyhow::Result<Option<lsp::DocumentSymbolResponse>> {
-    let uri = params.text_document.uri.clone();
-    let text = session.get_text(&uri).await?;
-    let session = session.clone();
-    let response = match text.language {
-        core::Language::Wast => self::wast::document_symbol(session, params, &text.content).await?,
-        core::Language::Wat => self::wat::document_symbol(session, params, &text.content).await?,
+    let snapshot = session.get_snapshot(&params.text_document.uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let response = match snapshot.text.language {
+        core::Language::Component => self::component::document_symbol(&snapshot, &token).await?,
+        core::Language::Wast => self::wast::document_symbol(&snapshot, &token).await?,
+        core::Language::Wat | core::Language::Wasm => self::wat::document_symbol(&snapshot, &token).await?,
+        core::Language::Wit => self::wit::document_symbol(&snapshot, &token).await?,
     };
     Ok(response)
 }
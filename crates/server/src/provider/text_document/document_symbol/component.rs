@@ -0,0 +1,259 @@
+//! Provider definitions for LSP `textDocument/documentSymbol` for component model documents.
+
+use crate::{
+    core::{self, language::component, node::NodeExt},
+    provider::text_document::document_symbol::{symbol_range, Data, SymbolRange, Work},
+};
+use lsp_text::RopeExt;
+
+/// Provider function for LSP `textDocument/documentSymbol` for component model documents.
+pub async fn document_symbol(
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<lsp::DocumentSymbolResponse>> {
+    let content = &snapshot.text.content;
+
+    // Vector to collect document symbols into as they are constructed.
+    let mut syms: Vec<lsp::DocumentSymbol> = vec![];
+
+    // Prepare the syntax tree.
+    let node = snapshot.tree.root_node();
+
+    // Prepare the stack machine:
+    //   data: contains data for constructing upcoming DocumentSymbols
+    //   work: contains remaining tree_sitter nodes to process
+    let mut data: Vec<Data> = vec![];
+    let mut work: Vec<Work> = vec![Work::Node(node)];
+
+    // The stack machine work loop.
+    while let Some(next) = work.pop() {
+        token.tick()?;
+        match next {
+            // Construct a DocumentSymbol and pop data stack
+            Work::Data => {
+                if let Some(Data {
+                    node,
+                    children_count,
+                    kind,
+                    name_hint,
+                }) = data.pop()
+                {
+                    let interface = interface_name(content, &node);
+                    let SymbolRange {
+                        name,
+                        range,
+                        selection_range,
+                    } = { symbol_range(content, node, name_hint, component::field::IDENTIFIER) };
+                    // imports and exports without an identifier are named by their interface name
+                    let (name, selection_range) = match interface {
+                        Some(interface) if selection_range == range => interface,
+                        _ => (name, selection_range),
+                    };
+
+                    #[allow(deprecated)]
+                    let sym = lsp::DocumentSymbol {
+                        children: if syms.is_empty() {
+                            None
+                        } else {
+                            // Drain the syms array by the number of children nodes we counted for this DocumentSymbol.
+                            // This allows us to properly reconstruct symbol nesting.
+                            let children = syms.drain(syms.len() - children_count ..);
+                            let children = children.rev();
+                            Some(children.collect())
+                        },
+                        deprecated: Default::default(),
+                        detail: Default::default(),
+                        kind,
+                        name: name.to_string(),
+                        range,
+                        selection_range,
+                        tags: Default::default(),
+                    };
+                    syms.push(sym);
+                }
+            },
+
+            Work::Node(node) if component::kind::ROOT == node.kind_id() => {
+                let mut cursor = node.walk();
+                let children = node
+                    .children(&mut cursor)
+                    .filter(|it| component::grouped::DEFINITIONS.contains(&it.kind_id()))
+                    .map(Work::Node);
+                work.extend(children);
+            },
+
+            Work::Node(node) if component::kind::COMPONENT == node.kind_id() => {
+                work.push(Work::Data);
+
+                let mut children_count = 0;
+                for child in node.children(&mut node.walk()) {
+                    if component::grouped::DEFINITIONS.contains(&child.kind_id()) {
+                        work.push(Work::Node(child));
+                        children_count += 1;
+                    }
+                }
+
+                data.push(Data {
+                    node,
+                    children_count,
+                    kind: lsp::SymbolKind::PACKAGE,
+                    name_hint: "component",
+                });
+            },
+
+            Work::Node(node) if component::kind::CORE_MODULE == node.kind_id() => {
+                work.push(Work::Data);
+
+                let mut children_count = 0;
+                for child in node.children(&mut node.walk()) {
+                    if child.matches_subtypes(component::kind::MODULE_FIELD, component::grouped::MODULE_FIELDS) {
+                        work.push(Work::Node(child));
+                        children_count += 1;
+                    }
+                }
+
+                data.push(Data {
+                    node,
+                    children_count,
+                    kind: lsp::SymbolKind::MODULE,
+                    name_hint: "module",
+                });
+            },
+
+            Work::Node(node)
+                if component::kind::CORE_INSTANCE == node.kind_id() || component::kind::INSTANCE == node.kind_id() =>
+            {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::OBJECT,
+                    name_hint: "instance",
+                });
+            },
+
+            Work::Node(node) if component::kind::ALIAS == node.kind_id() => {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::VARIABLE,
+                    name_hint: "alias",
+                });
+            },
+
+            Work::Node(node)
+                if component::kind::CANON == node.kind_id()
+                    || component::kind::COMPONENT_FUNC == node.kind_id()
+                    || component::kind::CORE_FUNC == node.kind_id() =>
+            {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::FUNCTION,
+                    name_hint: "func",
+                });
+            },
+
+            Work::Node(node) if component::kind::COMPONENT_EXPORT == node.kind_id() => {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::INTERFACE,
+                    name_hint: "export",
+                });
+            },
+
+            Work::Node(node) if component::kind::COMPONENT_IMPORT == node.kind_id() => {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::INTERFACE,
+                    name_hint: "import",
+                });
+            },
+
+            Work::Node(node)
+                if component::kind::CORE_TYPE == node.kind_id()
+                    || component::kind::TYPE_DEFINITION == node.kind_id() =>
+            {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::TYPE_PARAMETER,
+                    name_hint: "type",
+                });
+            },
+
+            // the fields of nested core modules
+            Work::Node(node) if component::kind::MODULE_FIELD == node.kind_id() => {
+                let mut cursor = node.walk();
+                let children = node
+                    .children(&mut cursor)
+                    .filter(|it| component::grouped::MODULE_FIELDS.contains(&it.kind_id()))
+                    .map(Work::Node);
+                work.extend(children);
+            },
+
+            Work::Node(node) if component::grouped::MODULE_FIELDS.contains(&node.kind_id()) => {
+                let kind = node.kind_id();
+                let (kind, name_hint) = if component::kind::MODULE_FIELD_DATA == kind {
+                    (lsp::SymbolKind::KEY, "data")
+                } else if component::kind::MODULE_FIELD_ELEM == kind {
+                    (lsp::SymbolKind::FIELD, "elem")
+                } else if component::kind::MODULE_FIELD_FUNC == kind {
+                    (lsp::SymbolKind::FUNCTION, "func")
+                } else if component::kind::MODULE_FIELD_GLOBAL == kind {
+                    (lsp::SymbolKind::EVENT, "global")
+                } else if component::kind::MODULE_FIELD_MEMORY == kind {
+                    (lsp::SymbolKind::ARRAY, "memory")
+                } else if component::kind::MODULE_FIELD_REC == kind {
+                    (lsp::SymbolKind::NAMESPACE, "rec")
+                } else if component::kind::MODULE_FIELD_TABLE == kind {
+                    (lsp::SymbolKind::INTERFACE, "table")
+                } else if component::kind::MODULE_FIELD_TAG == kind {
+                    (lsp::SymbolKind::OBJECT, "tag")
+                } else {
+                    (lsp::SymbolKind::TYPE_PARAMETER, "type")
+                };
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind,
+                    name_hint,
+                });
+            },
+
+            _ => {},
+        }
+    }
+    // Reverse the syms vec so that document symbols are returned in the correct order. Note that
+    // children nodes are reversed _as the symbols are nested_.
+    syms.reverse();
+
+    Ok(Some(lsp::DocumentSymbolResponse::Nested(syms)))
+}
+
+/// Compute the (unquoted) interface name and its range for an import or export.
+fn interface_name(content: &ropey::Rope, node: &tree_sitter::Node) -> Option<(String, lsp::Range)> {
+    let kind = node.kind_id();
+    if component::kind::COMPONENT_IMPORT != kind && component::kind::COMPONENT_EXPORT != kind {
+        return None;
+    }
+    let name = node
+        .children(&mut node.walk())
+        .find(|it| [component::kind::IMPORT_NAME, component::kind::EXPORT_NAME].contains(&it.kind_id()))?;
+    let mut string = name.named_child(0)?;
+    if component::kind::INTERFACE_NAME == string.kind_id() {
+        string = string.named_child(0)?;
+    }
+    let text = content.utf8_text_for_tree_sitter_node(&string);
+    let text = text.trim_matches('"').to_string();
+    let range = content.tree_sitter_range_to_lsp_range(string.range());
+    Some((text, range))
+}
@@ -2,23 +2,21 @@
 
 use crate::{
     core::{self, language::wast, node::NodeExt},
-    provider::text_document::document_symbol::{symbol_range, Data, SymbolRange, Work},
+    provider::text_document::document_symbol::{descendants, symbol_range, type_detail, Data, SymbolRange, Work},
 };
-use std::sync::Arc;
 
 /// Provider function for LSP `textDocument/documentSymbol` for `.wast` documents.
 pub async fn document_symbol(
-    session: Arc<core::Session>,
-    params: lsp::DocumentSymbolParams,
-    content: &ropey::Rope,
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
 ) -> anyhow::Result<Option<lsp::DocumentSymbolResponse>> {
+    let content = &snapshot.text.content;
+
     // Vector to collect document symbols into as they are constructed.
     let mut syms: Vec<lsp::DocumentSymbol> = vec![];
 
     // Prepare the syntax tree.
-    let tree = session.get_tree(&params.text_document.uri).await?;
-    let tree = tree.lock().await;
-    let node = tree.root_node();
+    let node = snapshot.tree.root_node();
 
     // Prepare the stack machine:
     //   data: contains data for constructing upcoming DocumentSymbols
@@ -28,6 +26,7 @@ pub async fn document_symbol(
 
     // The stack machine work loop.
     while let Some(next) = work.pop() {
+        token.tick()?;
         match next {
             // Construct a DocumentSymbol and pop data stack
             Work::Data => {
@@ -38,6 +37,18 @@ pub async fn document_symbol(
                     name_hint,
                 }) = data.pop()
                 {
+                    let detail = if [
+                        wast::kind::MODULE_FIELD_IMPORT,
+                        wast::kind::MODULE_FIELD_MEMORY,
+                        wast::kind::MODULE_FIELD_TABLE,
+                    ]
+                    .contains(&node.kind_id())
+                    {
+                        let type_kinds = [wast::kind::MEMORY_TYPE, wast::kind::TABLE_TYPE];
+                        type_detail(content, &node, &type_kinds, wast::kind::ADDRESS_TYPE)
+                    } else {
+                        None
+                    };
                     let SymbolRange {
                         name,
                         range,
@@ -56,7 +67,7 @@ pub async fn document_symbol(
                             Some(children.collect())
                         },
                         deprecated: Default::default(),
-                        detail: Default::default(),
+                        detail,
                         kind,
                         name: name.to_string(),
                         range,
@@ -163,6 +174,25 @@ pub async fn document_symbol(
                 });
             },
 
+            Work::Node(node) if wast::kind::MODULE_FIELD_REC == node.kind_id() => {
+                work.push(Work::Data);
+
+                let mut children_count = 0;
+                for child in node.children(&mut node.walk()) {
+                    if wast::kind::MODULE_FIELD_TYPE == child.kind_id() {
+                        work.push(Work::Node(child));
+                        children_count += 1;
+                    }
+                }
+
+                data.push(Data {
+                    node,
+                    children_count,
+                    kind: lsp::SymbolKind::NAMESPACE,
+                    name_hint: "rec",
+                });
+            },
+
             Work::Node(node) if wast::kind::MODULE_FIELD_TABLE == node.kind_id() => {
                 work.push(Work::Data);
                 data.push(Data {
@@ -173,16 +203,45 @@ pub async fn document_symbol(
                 });
             },
 
-            Work::Node(node) if wast::kind::MODULE_FIELD_TYPE == node.kind_id() => {
+            Work::Node(node) if wast::kind::MODULE_FIELD_TAG == node.kind_id() => {
                 work.push(Work::Data);
                 data.push(Data {
                     node,
                     children_count: 0,
+                    kind: lsp::SymbolKind::OBJECT,
+                    name_hint: "tag",
+                });
+            },
+
+            Work::Node(node) if wast::kind::MODULE_FIELD_TYPE == node.kind_id() => {
+                work.push(Work::Data);
+
+                // the fields of struct types
+                let fields = descendants(&node, &[wast::kind::STRUCT_FIELD_MANY, wast::kind::STRUCT_FIELD_ONE]);
+                let children_count = fields.len();
+                work.extend(fields.into_iter().map(Work::Node));
+
+                data.push(Data {
+                    node,
+                    children_count,
                     kind: lsp::SymbolKind::TYPE_PARAMETER,
                     name_hint: "type",
                 });
             },
 
+            Work::Node(node)
+                if wast::kind::STRUCT_FIELD_MANY == node.kind_id()
+                    || wast::kind::STRUCT_FIELD_ONE == node.kind_id() =>
+            {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::FIELD,
+                    name_hint: "field",
+                });
+            },
+
             Work::Node(node) if wast::kind::SCRIPT_MODULE == node.kind_id() => {
                 let mut cursor = node.walk();
                 let children = node
@@ -0,0 +1,335 @@
+//! Provider definitions for LSP `textDocument/documentSymbol` for `.wit` documents.
+
+use crate::{
+    core::{self, language::wit},
+    provider::text_document::document_symbol::{symbol_range, Data, SymbolRange, Work},
+};
+use lsp_text::RopeExt;
+
+/// Provider function for LSP `textDocument/documentSymbol` for `.wit` documents.
+pub async fn document_symbol(
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<lsp::DocumentSymbolResponse>> {
+    let content = &snapshot.text.content;
+
+    // Vector to collect document symbols into as they are constructed.
+    let mut syms: Vec<lsp::DocumentSymbol> = vec![];
+
+    // Prepare the syntax tree.
+    let node = snapshot.tree.root_node();
+
+    // Prepare the stack machine:
+    //   data: contains data for constructing upcoming DocumentSymbols
+    //   work: contains remaining tree_sitter nodes to process
+    let mut data: Vec<Data> = vec![];
+    let mut work: Vec<Work> = vec![Work::Node(node)];
+
+    // The stack machine work loop.
+    while let Some(next) = work.pop() {
+        token.tick()?;
+        match next {
+            // Construct a DocumentSymbol and pop data stack
+            Work::Data => {
+                if let Some(Data {
+                    node,
+                    children_count,
+                    kind,
+                    name_hint,
+                }) = data.pop()
+                {
+                    let SymbolRange {
+                        name,
+                        range,
+                        selection_range,
+                    } = { symbol_range(content, node.clone(), name_hint, wit::field::IDENTIFIER) };
+                    // packages and imports or exports of interfaces are named by their path
+                    let (name, selection_range) = match path_name(content, &node) {
+                        Some(path) => path,
+                        None => (name, selection_range),
+                    };
+
+                    #[allow(deprecated)]
+                    let sym = lsp::DocumentSymbol {
+                        children: if syms.is_empty() {
+                            None
+                        } else {
+                            // Drain the syms array by the number of children nodes we counted for this DocumentSymbol.
+                            // This allows us to properly reconstruct symbol nesting.
+                            let children = syms.drain(syms.len() - children_count ..);
+                            let children = children.rev();
+                            Some(children.collect())
+                        },
+                        deprecated: Default::default(),
+                        detail: signature(content, &node),
+                        kind,
+                        name,
+                        range,
+                        selection_range,
+                        tags: Default::default(),
+                    };
+                    syms.push(sym);
+                }
+            },
+
+            Work::Node(node) if wit::kind::ROOT == node.kind_id() => {
+                let package = node
+                    .children(&mut node.walk())
+                    .any(|it| wit::kind::PACKAGE_DECL == it.kind_id());
+
+                if package {
+                    work.push(Work::Data);
+                }
+
+                let mut children_count = 0;
+                for child in node.children(&mut node.walk()) {
+                    if [wit::kind::INTERFACE_ITEM, wit::kind::WORLD_ITEM].contains(&child.kind_id()) {
+                        work.push(Work::Node(child));
+                        children_count += 1;
+                    }
+                }
+
+                if package {
+                    data.push(Data {
+                        node,
+                        children_count,
+                        kind: lsp::SymbolKind::PACKAGE,
+                        name_hint: "package",
+                    });
+                }
+            },
+
+            Work::Node(node) if wit::kind::INTERFACE_ITEM == node.kind_id() => {
+                work.push(Work::Data);
+                let children_count = push_children(&mut work, &node, INTERFACE_ITEMS);
+                data.push(Data {
+                    node: node.clone(),
+                    children_count,
+                    kind: lsp::SymbolKind::INTERFACE,
+                    name_hint: "interface",
+                });
+            },
+
+            Work::Node(node) if wit::kind::WORLD_ITEM == node.kind_id() => {
+                work.push(Work::Data);
+                let children_count = push_children(&mut work, &node, WORLD_ITEMS);
+                data.push(Data {
+                    node: node.clone(),
+                    children_count,
+                    kind: lsp::SymbolKind::MODULE,
+                    name_hint: "world",
+                });
+            },
+
+            Work::Node(node)
+                if wit::kind::EXPORT_ITEM == node.kind_id() || wit::kind::IMPORT_ITEM == node.kind_id() =>
+            {
+                let func = node
+                    .children(&mut node.walk())
+                    .any(|it| wit::kind::FUNC_TYPE == it.kind_id());
+                work.push(Work::Data);
+                let children_count = push_children(&mut work, &node, INTERFACE_ITEMS);
+                data.push(Data {
+                    node: node.clone(),
+                    children_count,
+                    kind: if func {
+                        lsp::SymbolKind::FUNCTION
+                    } else {
+                        lsp::SymbolKind::INTERFACE
+                    },
+                    name_hint: if wit::kind::EXPORT_ITEM == node.kind_id() {
+                        "export"
+                    } else {
+                        "import"
+                    },
+                });
+            },
+
+            Work::Node(node) if wit::kind::FUNC_ITEM == node.kind_id() => {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::FUNCTION,
+                    name_hint: "func",
+                });
+            },
+
+            Work::Node(node) if wit::kind::RECORD_ITEM == node.kind_id() => {
+                work.push(Work::Data);
+                let children_count = push_children(&mut work, &node, &[wit::kind::RECORD_FIELD]);
+                data.push(Data {
+                    node: node.clone(),
+                    children_count,
+                    kind: lsp::SymbolKind::STRUCT,
+                    name_hint: "record",
+                });
+            },
+
+            Work::Node(node) if wit::kind::RECORD_FIELD == node.kind_id() => {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::FIELD,
+                    name_hint: "field",
+                });
+            },
+
+            Work::Node(node)
+                if wit::kind::ENUM_ITEM == node.kind_id()
+                    || wit::kind::FLAGS_ITEM == node.kind_id()
+                    || wit::kind::VARIANT_ITEM == node.kind_id() =>
+            {
+                work.push(Work::Data);
+                let cases = &[wit::kind::ENUM_CASE, wit::kind::FLAGS_FIELD, wit::kind::VARIANT_CASE];
+                let children_count = push_children(&mut work, &node, cases);
+                data.push(Data {
+                    node: node.clone(),
+                    children_count,
+                    kind: lsp::SymbolKind::ENUM,
+                    name_hint: if wit::kind::ENUM_ITEM == node.kind_id() {
+                        "enum"
+                    } else if wit::kind::FLAGS_ITEM == node.kind_id() {
+                        "flags"
+                    } else {
+                        "variant"
+                    },
+                });
+            },
+
+            Work::Node(node)
+                if wit::kind::ENUM_CASE == node.kind_id()
+                    || wit::kind::FLAGS_FIELD == node.kind_id()
+                    || wit::kind::VARIANT_CASE == node.kind_id() =>
+            {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::ENUM_MEMBER,
+                    name_hint: "case",
+                });
+            },
+
+            Work::Node(node) if wit::kind::RESOURCE_ITEM == node.kind_id() => {
+                work.push(Work::Data);
+                let children_count = push_children(&mut work, &node, &[wit::kind::RESOURCE_METHOD]);
+                data.push(Data {
+                    node: node.clone(),
+                    children_count,
+                    kind: lsp::SymbolKind::CLASS,
+                    name_hint: "resource",
+                });
+            },
+
+            Work::Node(node) if wit::kind::RESOURCE_METHOD == node.kind_id() => {
+                let constructor = node
+                    .children(&mut node.walk())
+                    .any(|it| wit::kind::token::CONSTRUCTOR == it.kind_id());
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: if constructor {
+                        lsp::SymbolKind::CONSTRUCTOR
+                    } else {
+                        lsp::SymbolKind::METHOD
+                    },
+                    name_hint: "method",
+                });
+            },
+
+            Work::Node(node) if wit::kind::TYPE_ALIAS_ITEM == node.kind_id() => {
+                work.push(Work::Data);
+                data.push(Data {
+                    node,
+                    children_count: 0,
+                    kind: lsp::SymbolKind::TYPE_PARAMETER,
+                    name_hint: "type",
+                });
+            },
+
+            _ => {},
+        }
+    }
+    // Reverse the syms vec so that document symbols are returned in the correct order. Note that
+    // children nodes are reversed _as the symbols are nested_.
+    syms.reverse();
+
+    Ok(Some(lsp::DocumentSymbolResponse::Nested(syms)))
+}
+
+/// The kinds of items which are shown as symbols within interfaces.
+const INTERFACE_ITEMS: &[u16] = &[
+    wit::kind::ENUM_ITEM,
+    wit::kind::FLAGS_ITEM,
+    wit::kind::FUNC_ITEM,
+    wit::kind::RECORD_ITEM,
+    wit::kind::RESOURCE_ITEM,
+    wit::kind::TYPE_ALIAS_ITEM,
+    wit::kind::VARIANT_ITEM,
+];
+
+/// The kinds of items which are shown as symbols within worlds.
+const WORLD_ITEMS: &[u16] = &[
+    wit::kind::ENUM_ITEM,
+    wit::kind::EXPORT_ITEM,
+    wit::kind::FLAGS_ITEM,
+    wit::kind::IMPORT_ITEM,
+    wit::kind::RECORD_ITEM,
+    wit::kind::RESOURCE_ITEM,
+    wit::kind::TYPE_ALIAS_ITEM,
+    wit::kind::VARIANT_ITEM,
+];
+
+/// Push the children of a node with one of the given kinds as work, returning their count.
+fn push_children<'tree>(work: &mut Vec<Work<'tree>>, node: &tree_sitter::Node<'tree>, kinds: &[u16]) -> usize {
+    let mut children_count = 0;
+    for child in node.children(&mut node.walk()) {
+        if kinds.contains(&child.kind_id()) {
+            work.push(Work::Node(child));
+            children_count += 1;
+        }
+    }
+    children_count
+}
+
+/// Compute the name and its range for a package, or for an import or export of an interface path.
+fn path_name(content: &ropey::Rope, node: &tree_sitter::Node) -> Option<(String, lsp::Range)> {
+    let kind = node.kind_id();
+    let path = if wit::kind::ROOT == kind {
+        let package = node
+            .children(&mut node.walk())
+            .find(|it| wit::kind::PACKAGE_DECL == it.kind_id())?;
+        package.child_by_field_id(wit::field::NAME)?
+    } else if wit::kind::EXPORT_ITEM == kind || wit::kind::IMPORT_ITEM == kind {
+        node.children(&mut node.walk())
+            .find(|it| wit::kind::USE_PATH == it.kind_id())?
+    } else {
+        return None;
+    };
+    let text = content.utf8_text_for_tree_sitter_node(&path).into();
+    let range = content.tree_sitter_range_to_lsp_range(path.range());
+    Some((text, range))
+}
+
+/// Compute the signature of a function (e.g., `func(a: u32) -> string`) for the symbol detail.
+fn signature(content: &ropey::Rope, node: &tree_sitter::Node) -> Option<String> {
+    let kind = node.kind_id();
+    if ![
+        wit::kind::EXPORT_ITEM,
+        wit::kind::FUNC_ITEM,
+        wit::kind::IMPORT_ITEM,
+        wit::kind::RESOURCE_METHOD,
+    ]
+    .contains(&kind)
+    {
+        return None;
+    }
+    let func = node
+        .children(&mut node.walk())
+        .find(|it| wit::kind::FUNC_TYPE == it.kind_id())?;
+    let text = content.utf8_text_for_tree_sitter_node(&func);
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
@@ -0,0 +1,34 @@
+use crate::core;
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+/// Provider function for LSP `textDocument/formatting`.
+///
+/// Documents are formatted with [`core::format::format`], using the format settings of the
+/// project configuration (rather than the options of the request), so that the editor agrees with
+/// the `format` subcommand. The result is a single edit replacing the entire document, or no edits
+/// when it is already formatted.
+pub async fn formatting(
+    session: Arc<core::Session>,
+    params: lsp::DocumentFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp::TextEdit>>> {
+    let uri = &params.text_document.uri;
+    let snapshot = session.get_snapshot(uri).await?;
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wasm | core::Language::Wit = text.language {
+        return Ok(None);
+    }
+    let content = &text.content;
+
+    let settings = session.document_settings(uri).await.format;
+    let original = content.to_string();
+    let formatted = core::format::format(&original, &settings);
+    if formatted == original {
+        return Ok(Some(vec![]));
+    }
+
+    let start = lsp::Position::new(0, 0);
+    let end = content.byte_to_lsp_position(content.len_bytes());
+    let range = lsp::Range::new(start, end);
+    Ok(Some(vec![lsp::TextEdit::new(range, formatted)]))
+}
@@ -0,0 +1,95 @@
+use crate::core::{self, encoding};
+use std::sync::Arc;
+
+/// Provider function for LSP `textDocument/hover`.
+///
+/// Hovering the keyword of an instruction shows its binary encoding: the opcode (including any
+/// prefix) followed by the immediates (with `$identifier`s resolved to indices), together with
+/// the byte offset of the instruction within the function body. Instructions which address a
+/// memory or table also show the addressed entity and the type of its addresses.
+pub async fn hover(
+    session: Arc<core::Session>,
+    params: lsp::HoverParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<Option<lsp::Hover>> {
+    let params = params.text_document_position_params;
+    let snapshot = session.get_snapshot(&params.text_document.uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wast | core::Language::Wit = text.language {
+        return Ok(None);
+    }
+    let content = &text.content;
+
+    let offset = match encoding::offset(content, params.position) {
+        Some(offset) => offset,
+        None => return Ok(None),
+    };
+    // documents which fail to encode are already covered by diagnostics
+    let encoding = match snapshot.encoding() {
+        Ok(encoding) => encoding,
+        Err(_) => return Ok(None),
+    };
+
+    let found = encoding.source_map.functions.iter().find_map(|function| {
+        function
+            .instructions
+            .iter()
+            .find(|it| it.source <= offset && offset < it.source + encoding::token_len(content, it.source))
+            .map(|instruction| (function, instruction))
+    });
+    let (function, instruction) = match found {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+
+    let bytes = &encoding.bytes[instruction.range.clone()];
+    let (opcode, immediates) = bytes.split_at(encoding::opcode_len(bytes)?);
+    let mut value = format!("```text\n{}", hex(opcode));
+    if !immediates.is_empty() {
+        value.push_str(" | ");
+        value.push_str(&hex(immediates));
+    }
+    value.push_str("\n```\n\n");
+    value.push_str(&format!(
+        "{} {} at offset `{:#x}` of the body of function {}",
+        bytes.len(),
+        if 1 == bytes.len() { "byte" } else { "bytes" },
+        instruction.range.start - function.body.start,
+        function.index,
+    ));
+
+    // memory and table instructions also show the (memory64 dependent) type of their addresses
+    let analysis = snapshot.analysis(&token)?;
+    if let Some(access) = analysis.access_at(instruction.source) {
+        value.push_str(&format!("\n\nAddresses {} ", access.space));
+        match access.target.map(|id| &analysis.definitions[id]) {
+            Some(definition) => {
+                let name = definition.name.clone().unwrap_or_else(|| definition.index.to_string());
+                value.push_str(&format!("`{}`", name));
+                if definition.shared {
+                    value.push_str(" (shared)");
+                }
+            },
+            None => value.push_str("<unknown>"),
+        }
+        value.push_str(&format!(" with `{}` operands", access.address_type));
+    }
+
+    Ok(Some(lsp::Hover {
+        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+            kind: lsp::MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(encoding::token_range(content, instruction.source)),
+    }))
+}
+
+/// Format bytes as space separated hexadecimal pairs.
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
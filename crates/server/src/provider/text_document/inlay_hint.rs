@@ -0,0 +1,58 @@
+use crate::core;
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+/// Provider function for LSP `textDocument/inlayHint`.
+///
+/// The numeric index of each named entity of a `.wat` or `.wast` document is shown after the
+/// identifier of its definition (e.g., `(func $main (;2;) ...)`), which helps to relate the
+/// identifiers to the indices of the binary format and of error messages.
+pub async fn inlay_hint(
+    session: Arc<core::Session>,
+    params: lsp::InlayHintParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<Option<Vec<lsp::InlayHint>>> {
+    let uri = &params.text_document.uri;
+    let settings = session.document_settings(uri).await.inlay_hints;
+    if !settings.enabled {
+        return Ok(None);
+    }
+
+    let snapshot = session.get_snapshot(uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wit = text.language {
+        return Ok(None);
+    }
+    let content = &text.content;
+
+    let mut hints = vec![];
+    if settings.indices {
+        let analysis = snapshot.analysis(&token)?;
+        for definition in &analysis.definitions {
+            let identifier = match &definition.identifier {
+                Some(identifier) => identifier.clone(),
+                None => continue,
+            };
+            let position = content.tree_sitter_range_to_lsp_range(identifier).end;
+            if position < params.range.start || params.range.end < position {
+                continue;
+            }
+            hints.push(lsp::InlayHint {
+                position,
+                label: lsp::InlayHintLabel::String(format!("(;{};)", definition.index)),
+                kind: None,
+                text_edits: None,
+                tooltip: Some(lsp::InlayHintTooltip::String(format!(
+                    "{} index {}",
+                    definition.space, definition.index
+                ))),
+                padding_left: Some(true),
+                padding_right: None,
+                data: None,
+            });
+        }
+    }
+
+    Ok(Some(hints))
+}
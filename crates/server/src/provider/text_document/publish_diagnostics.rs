@@ -1,13 +1,154 @@
+/// Provider definitions for LSP `textDocument/publishDiagnostics` for component model documents.
+pub mod component;
+
 /// Provider definitions for LSP `textDocument/publishDiagnostics` for `.wast` documents.
 pub mod wast;
 
 /// Provider definitions for LSP `textDocument/publishDiagnostics` for `.wat` documents.
 pub mod wat;
 
+/// Provider definitions for LSP `textDocument/publishDiagnostics` for `.wit` documents.
+pub mod wit;
+
 /// Provider function for LSP `textDocument/publishDiagnostics`.
-pub fn diagnostics(tree: &tree_sitter::Tree, text: &crate::core::Text) -> Vec<lsp::Diagnostic> {
-    match text.language {
-        crate::core::Language::Wast => wast::diagnostics(tree, &text.content),
-        crate::core::Language::Wat => wat::diagnostics(tree, &text.content),
+///
+/// Fails with [`crate::core::Error::RequestCancelled`] once `token` is cancelled.
+pub fn diagnostics(
+    tree: &tree_sitter::Tree,
+    text: &crate::core::Text,
+    settings: &crate::core::Settings,
+    token: &crate::core::CancellationToken,
+) -> anyhow::Result<Vec<lsp::Diagnostic>> {
+    let diagnostics = match text.language {
+        crate::core::Language::Component => component::diagnostics(tree, &text.content, token)?,
+        crate::core::Language::Wast => wast::diagnostics(tree, &text.content, token)?,
+        crate::core::Language::Wat | crate::core::Language::Wasm => wat::diagnostics(tree, &text.content, token)?,
+        crate::core::Language::Wit => wit::diagnostics(tree, &text.content, token)?,
+    };
+    let diagnostics = diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+            let rule = crate::core::Rule::Syntax;
+            settings
+                .diagnostics
+                .diagnostic(rule, diagnostic.range, diagnostic.message)
+        })
+        .collect();
+    Ok(diagnostics)
+}
+
+/// Provider function for the semantic (validation) portion of LSP
+/// `textDocument/publishDiagnostics`.
+///
+/// The diagnostics are computed from the `analysis` of the document (e.g., the one cached by its
+/// [`crate::core::Snapshot`]), which is not recomputed here.
+///
+/// Fails with [`crate::core::Error::RequestCancelled`] once `token` is cancelled.
+pub fn semantic_diagnostics(
+    text: &crate::core::Text,
+    analysis: &crate::core::analysis::Analysis,
+    settings: &crate::core::Settings,
+    token: &crate::core::CancellationToken,
+) -> anyhow::Result<Vec<lsp::Diagnostic>> {
+    use lsp_text::RopeExt;
+    token.check()?;
+    let mut diagnostics = vec![];
+    for reference in analysis.references.iter().filter(|it| it.definition.is_none()) {
+        token.tick()?;
+        let rule = crate::core::Rule::UnknownIndex;
+        let range = text.content.tree_sitter_range_to_lsp_range(reference.range.clone());
+        let message = format!("unknown {} `{}`", reference.space, reference.text);
+        diagnostics.extend(settings.diagnostics.diagnostic(rule, range, message));
     }
+    token.check()?;
+    diagnostics.extend(atomic_diagnostics(analysis, &text.content, settings));
+    diagnostics.extend(proposal_diagnostics(analysis, &text.content, settings));
+    diagnostics.extend(import_diagnostics(analysis, &text.content, settings));
+    Ok(diagnostics)
+}
+
+/// Check the atomic instructions of a document against the alignment and sharing of their memory.
+fn atomic_diagnostics(
+    analysis: &crate::core::analysis::Analysis,
+    content: &ropey::Rope,
+    settings: &crate::core::Settings,
+) -> Vec<lsp::Diagnostic> {
+    use lsp_text::RopeExt;
+    let mut diagnostics = vec![];
+    for access in &analysis.accesses {
+        let expected = match crate::core::analysis::atomic_alignment(&access.op) {
+            Some(expected) => expected,
+            None => continue,
+        };
+        if let Some((align, range)) = &access.align {
+            if *align != expected {
+                let rule = crate::core::Rule::AtomicAlignment;
+                let range = content.tree_sitter_range_to_lsp_range(range.clone());
+                let message = format!(
+                    "`{}` requires an alignment of exactly {}, found {}",
+                    access.op, expected, align
+                );
+                diagnostics.extend(settings.diagnostics.diagnostic(rule, range, message));
+            }
+        }
+        if let Some(memory) = access.target.map(|id| &analysis.definitions[id]) {
+            if !memory.shared {
+                let name = memory.name.clone().unwrap_or_else(|| memory.index.to_string());
+                let rule = crate::core::Rule::AtomicNonShared;
+                let range = content.tree_sitter_range_to_lsp_range(access.range.clone());
+                let message = format!("atomic operation `{}` on non-shared memory `{}`", access.op, name);
+                diagnostics.extend(settings.diagnostics.diagnostic(rule, range, message));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Check the constructs of a document against the proposals which are enabled in the settings.
+fn proposal_diagnostics(
+    analysis: &crate::core::analysis::Analysis,
+    content: &ropey::Rope,
+    settings: &crate::core::Settings,
+) -> Vec<lsp::Diagnostic> {
+    use lsp_text::RopeExt;
+    analysis
+        .proposals
+        .iter()
+        .filter(|it| !settings.proposals.enabled(it.proposal))
+        .filter_map(|it| {
+            let rule = crate::core::Rule::DisabledProposal;
+            let range = content.tree_sitter_range_to_lsp_range(it.range.clone());
+            let message = format!("the {} proposal is not enabled", it.proposal);
+            settings.diagnostics.diagnostic(rule, range, message)
+        })
+        .collect()
+}
+
+/// Check the imports of a document against the host API profile selected in the settings.
+fn import_diagnostics(
+    analysis: &crate::core::analysis::Analysis,
+    content: &ropey::Rope,
+    settings: &crate::core::Settings,
+) -> Vec<lsp::Diagnostic> {
+    use lsp_text::RopeExt;
+    let (host, profile) = match settings.host_profile() {
+        Some(profile) => profile,
+        None => return vec![],
+    };
+    analysis
+        .imports
+        .iter()
+        .filter(|it| !profile.provides(&it.module, &it.name))
+        .filter_map(|it| {
+            let rule = crate::core::Rule::UnknownImport;
+            let start = content.tree_sitter_range_to_lsp_range(it.module_range.clone()).start;
+            let end = content.tree_sitter_range_to_lsp_range(it.name_range.clone()).end;
+            let range = lsp::Range::new(start, end);
+            let message = format!(
+                "import `{}` `{}` is not provided by the host profile `{}`",
+                it.module, it.name, host
+            );
+            settings.diagnostics.diagnostic(rule, range, message)
+        })
+        .collect()
 }
@@ -4,7 +4,11 @@ use crate::core::{self, node::TraceNodeWalker, range::RangeExt};
 use lsp_text::RopeExt;
 
 /// Provider function for LSP `textDocument/publishDiagnostics` for `.wast` documents.
-pub fn diagnostics(tree: &tree_sitter::Tree, content: &ropey::Rope) -> Vec<lsp::Diagnostic> {
+pub fn diagnostics(
+    tree: &tree_sitter::Tree,
+    content: &ropey::Rope,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Vec<lsp::Diagnostic>> {
     let mut diagnostics = vec![];
     let mut walker = {
         let language = core::Language::Wast;
@@ -20,6 +24,8 @@ pub fn diagnostics(tree: &tree_sitter::Tree, content: &ropey::Rope) -> Vec<lsp::
             break;
         }
 
+        token.tick()?;
+
         let current = walker.node();
 
         if current.is_error() {
@@ -80,5 +86,5 @@ pub fn diagnostics(tree: &tree_sitter::Tree, content: &ropey::Rope) -> Vec<lsp::
     }
 
     diagnostics.reverse();
-    diagnostics
+    Ok(diagnostics)
 }
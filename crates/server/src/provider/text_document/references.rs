@@ -0,0 +1,46 @@
+use crate::core;
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+/// Provider function for LSP `textDocument/references`.
+///
+/// The references of an entity are the indices (symbolic or numeric) which resolve to its
+/// definition within the `.wat` or `.wast` document, in any index space.
+pub async fn references(
+    session: Arc<core::Session>,
+    params: lsp::ReferenceParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<Option<Vec<lsp::Location>>> {
+    let uri = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+    let snapshot = session.get_snapshot(&uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wasm | core::Language::Wit = text.language {
+        return Ok(None);
+    }
+    let content = &text.content;
+
+    let analysis = snapshot.analysis(&token)?;
+    let id = {
+        let range = content.lsp_range_to_tree_sitter_range(lsp::Range::new(position, position))?;
+        match analysis.target_at(range.start_byte()) {
+            Some(id) => id,
+            None => return Ok(None),
+        }
+    };
+
+    let mut locations = vec![];
+    if params.context.include_declaration {
+        let definition = &analysis.definitions[id];
+        let range = definition.identifier.as_ref().unwrap_or(&definition.range).clone();
+        let range = content.tree_sitter_range_to_lsp_range(range);
+        locations.push(lsp::Location::new(uri.clone(), range));
+    }
+    for reference in analysis.references_to(id) {
+        let range = content.tree_sitter_range_to_lsp_range(reference.range.clone());
+        locations.push(lsp::Location::new(uri.clone(), range));
+    }
+
+    Ok(Some(locations))
+}
@@ -0,0 +1,93 @@
+use crate::core;
+use lsp_text::RopeExt;
+use std::{collections::HashMap, sync::Arc};
+
+/// Provider function for LSP `textDocument/rename`.
+///
+/// Renaming an entity of a `.wat` or `.wast` document replaces its identifier and the symbolic
+/// indices which refer to it. Numeric indices are left untouched since they still refer to the
+/// entity after the rename. Entities without an identifier cannot be renamed, and the new name
+/// must be a (`$`-prefixed) identifier which is not already defined in the same index space.
+pub async fn rename(
+    session: Arc<core::Session>,
+    params: lsp::RenameParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<Option<lsp::WorkspaceEdit>> {
+    let uri = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+    let snapshot = session.get_snapshot(&uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wasm | core::Language::Wit = text.language {
+        return Ok(None);
+    }
+    let content = &text.content;
+
+    let analysis = snapshot.analysis(&token)?;
+    let id = {
+        let range = content.lsp_range_to_tree_sitter_range(lsp::Range::new(position, position))?;
+        match analysis.target_at(range.start_byte()) {
+            Some(id) => id,
+            None => return Ok(None),
+        }
+    };
+    let definition = &analysis.definitions[id];
+
+    let new_name = params.new_name;
+    if !is_identifier(&new_name) {
+        let message = format!("`{}` is not a valid identifier", new_name);
+        return Err(core::Error::InvalidParams(message).into());
+    }
+    let identifier = match &definition.identifier {
+        Some(identifier) => identifier.clone(),
+        None => {
+            let message = format!(
+                "the {} {} has no identifier to rename",
+                definition.space, definition.index
+            );
+            return Err(core::Error::InvalidParams(message).into());
+        },
+    };
+    if let Some(other) = analysis.resolve(definition.module, definition.scope, definition.space, &new_name) {
+        if other != id {
+            let message = format!("the {} `{}` is already defined", definition.space, new_name);
+            return Err(core::Error::InvalidParams(message).into());
+        }
+    }
+
+    let mut edits = vec![lsp::TextEdit::new(
+        content.tree_sitter_range_to_lsp_range(identifier),
+        new_name.clone(),
+    )];
+    for reference in analysis.references_to(id).filter(|it| it.text.starts_with('$')) {
+        let range = content.tree_sitter_range_to_lsp_range(reference.range.clone());
+        edits.push(lsp::TextEdit::new(range, new_name.clone()));
+    }
+
+    let changes = HashMap::from([(uri, edits)]);
+    Ok(Some(lsp::WorkspaceEdit::new(changes)))
+}
+
+/// Determine whether `name` is a valid (`$`-prefixed) identifier of the text format.
+fn is_identifier(name: &str) -> bool {
+    const SYMBOLS: &str = "!#$%&'*+-./:<=>?@\\^_`|~";
+    match name.strip_prefix('$') {
+        Some(rest) => !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || SYMBOLS.contains(c)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers() {
+        assert!(is_identifier("$f"));
+        assert!(is_identifier("$my-func.1"));
+        assert!(!is_identifier("f"));
+        assert!(!is_identifier("$"));
+        assert!(!is_identifier("$a b"));
+        assert!(!is_identifier("$(a)"));
+    }
+}
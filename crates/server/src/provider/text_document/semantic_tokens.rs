@@ -1,4 +1,8 @@
-use crate::core::{self, Language};
+use crate::core::{
+    self,
+    analysis::{Definition, IndexSpace},
+    Language,
+};
 use std::sync::Arc;
 
 /// Definitions for the semantic tokens builder used during tokenization.
@@ -6,17 +10,25 @@ pub mod builder;
 
 pub mod wast;
 pub mod wat;
+pub mod wit;
 
 /// LSP message handler function for `textDocument/semanticTokens/full`.
 pub async fn full(
     session: Arc<core::Session>,
     params: lsp::SemanticTokensParams,
+    token: core::CancellationToken,
 ) -> anyhow::Result<Option<lsp::SemanticTokensResult>> {
     let uri = params.text_document.uri.clone();
-    let text = session.get_text(&uri).await?;
-    let response = match text.language {
-        Language::Wast => wast::full(session.clone(), params, &text.content).await?,
-        Language::Wat => wat::full(session.clone(), params, &text.content).await?,
+    if !session.document_settings(&uri).await.semantic_tokens.enabled {
+        return Ok(None);
+    }
+    let snapshot = session.get_snapshot(&uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let response = match snapshot.text.language {
+        Language::Component => None,
+        Language::Wast => wast::full(session.clone(), params, &snapshot, &token).await?,
+        Language::Wat | Language::Wasm => wat::full(session.clone(), params, &snapshot, &token).await?,
+        Language::Wit => wit::full(session.clone(), params, &snapshot, &token).await?,
     };
     Ok(response)
 }
@@ -25,12 +37,70 @@ pub async fn full(
 pub async fn range(
     session: Arc<core::Session>,
     params: lsp::SemanticTokensRangeParams,
+    token: core::CancellationToken,
 ) -> anyhow::Result<Option<lsp::SemanticTokensRangeResult>> {
     let uri = params.text_document.uri.clone();
-    let text = session.get_text(&uri).await?;
-    let response = match text.language {
-        Language::Wast => wast::range(session.clone(), params, &text.content).await?,
-        Language::Wat => wat::range(session.clone(), params, &text.content).await?,
+    if !session.document_settings(&uri).await.semantic_tokens.enabled {
+        return Ok(None);
+    }
+    let snapshot = session.get_snapshot(&uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let response = match snapshot.text.language {
+        Language::Component => None,
+        Language::Wast => wast::range(session.clone(), params, &snapshot, &token).await?,
+        Language::Wat | Language::Wasm => wat::range(session.clone(), params, &snapshot, &token).await?,
+        Language::Wit => wit::range(session.clone(), params, &snapshot, &token).await?,
     };
     Ok(response)
 }
+
+/// Custom semantic token modifiers provided by the server.
+pub mod modifier {
+    /// Modifier for entities which are exported from their module.
+    pub const EXPORTED: lsp::SemanticTokenModifier = lsp::SemanticTokenModifier::new("exported");
+    /// Modifier for instructions which can never be executed.
+    pub const UNREACHABLE: lsp::SemanticTokenModifier = lsp::SemanticTokenModifier::new("unreachable");
+}
+
+static DECLARATION: lsp::SemanticTokenModifier = lsp::SemanticTokenModifier::DECLARATION;
+static DEFAULT_LIBRARY: lsp::SemanticTokenModifier = lsp::SemanticTokenModifier::DEFAULT_LIBRARY;
+static EXPORTED: lsp::SemanticTokenModifier = modifier::EXPORTED;
+static READONLY: lsp::SemanticTokenModifier = lsp::SemanticTokenModifier::READONLY;
+pub(crate) static UNREACHABLE: lsp::SemanticTokenModifier = modifier::UNREACHABLE;
+
+static EVENT: lsp::SemanticTokenType = lsp::SemanticTokenType::EVENT;
+static FUNCTION: lsp::SemanticTokenType = lsp::SemanticTokenType::FUNCTION;
+static NAMESPACE: lsp::SemanticTokenType = lsp::SemanticTokenType::NAMESPACE;
+static PARAMETER: lsp::SemanticTokenType = lsp::SemanticTokenType::PARAMETER;
+static PROPERTY: lsp::SemanticTokenType = lsp::SemanticTokenType::PROPERTY;
+static TYPE: lsp::SemanticTokenType = lsp::SemanticTokenType::TYPE;
+static VARIABLE: lsp::SemanticTokenType = lsp::SemanticTokenType::VARIABLE;
+
+/// Compute the semantic token type for entities of an index space.
+pub(crate) fn token_type(space: IndexSpace) -> &'static lsp::SemanticTokenType {
+    match space {
+        IndexSpace::Field => &PROPERTY,
+        IndexSpace::Func => &FUNCTION,
+        IndexSpace::Tag => &EVENT,
+        IndexSpace::Type => &TYPE,
+        _ => &VARIABLE,
+    }
+}
+
+/// Compute the semantic token modifiers for a (declaration or use of a) defined entity.
+pub(crate) fn token_modifiers(definition: &Definition, declaration: bool) -> Vec<&'static lsp::SemanticTokenModifier> {
+    let mut modifiers = vec![];
+    if declaration {
+        modifiers.push(&DECLARATION);
+    }
+    if matches!(definition.space, IndexSpace::Field | IndexSpace::Global) && !definition.mutable {
+        modifiers.push(&READONLY);
+    }
+    if IndexSpace::Func == definition.space && definition.imported {
+        modifiers.push(&DEFAULT_LIBRARY);
+    }
+    if definition.exported {
+        modifiers.push(&EXPORTED);
+    }
+    modifiers
+}
@@ -3,7 +3,7 @@
 use anyhow::anyhow;
 use lsp::SemanticTokensFullDeltaResult;
 use lsp_text::RopeExt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Manages tokenization state for encoding semantic token data.
 #[derive(Clone, Debug)]
@@ -16,12 +16,23 @@ pub struct SemanticTokensBuilder<'text, 'tree> {
     data: Vec<lsp::SemanticToken>,
     token_modifier_map: HashMap<&'tree lsp::SemanticTokenModifier, u32>,
     token_type_map: HashMap<&'tree lsp::SemanticTokenType, u32>,
+    disabled_token_modifiers: HashSet<&'tree lsp::SemanticTokenModifier>,
+    disabled_token_types: HashSet<&'tree lsp::SemanticTokenType>,
     has_legend: bool,
+    multiline: bool,
 }
 
 impl<'text, 'tree> SemanticTokensBuilder<'text, 'tree> {
     /// Create a new [`SemanticTokensBuilder`].
-    pub fn new(content: &'text ropey::Rope, legend: Option<&'tree lsp::SemanticTokensLegend>) -> anyhow::Result<Self> {
+    ///
+    /// If `multiline` is set, tokens spanning several lines are encoded as a single token (which
+    /// requires the client to advertise `multilineTokenSupport`). Otherwise they are split into
+    /// one token per line.
+    pub fn new(
+        content: &'text ropey::Rope,
+        legend: Option<&'tree lsp::SemanticTokensLegend>,
+        multiline: bool,
+    ) -> anyhow::Result<Self> {
         use std::time::{SystemTime, UNIX_EPOCH};
 
         let mut token_modifier_map = HashMap::new();
@@ -49,10 +60,31 @@ impl<'text, 'tree> SemanticTokensBuilder<'text, 'tree> {
             data: Default::default(),
             token_modifier_map,
             token_type_map,
+            disabled_token_modifiers: Default::default(),
+            disabled_token_types: Default::default(),
             has_legend,
+            multiline,
         })
     }
 
+    /// Restrict the tokens to the token types and modifiers (of the legend) which are enabled in
+    /// the settings. Tokens of other types are skipped, and other modifiers are dropped.
+    pub fn restrict(mut self, settings: &crate::core::SemanticTokensSettings) -> Self {
+        self.disabled_token_types = self
+            .token_type_map
+            .keys()
+            .filter(|it| !settings.token_type_enabled(it))
+            .copied()
+            .collect();
+        self.disabled_token_modifiers = self
+            .token_modifier_map
+            .keys()
+            .filter(|it| !settings.token_modifier_enabled(it))
+            .copied()
+            .collect();
+        self
+    }
+
     /// Build the [`lsp::SemanticTokens`] data from the tokenization state.
     pub fn build(&mut self) -> lsp::SemanticTokens {
         self.prev_data = None;
@@ -182,20 +214,35 @@ impl<'text, 'tree> SemanticTokensBuilder<'text, 'tree> {
         }
 
         let range = self.content.tree_sitter_range_to_lsp_range(node.range());
+        self.push_range(range, token_type, token_modifiers)
+    }
 
-        if range.start.line != range.end.line {
-            return Err(anyhow!("`range` cannot span multiple lines"));
+    /// Push and encode a token for an [`lsp::Range`] into the tokenization state.
+    ///
+    /// Ranges spanning multiple lines are either encoded as a single token or split into one token
+    /// per line, depending on whether the client supports multiline tokens.
+    pub fn push_range(
+        &mut self,
+        range: lsp::Range,
+        token_type: &lsp::SemanticTokenType,
+        token_modifiers: Option<Vec<&lsp::SemanticTokenModifier>>,
+    ) -> anyhow::Result<()> {
+        if !self.has_legend {
+            return Err(anyhow!("Legend must be provided in constructor"));
         }
 
-        if let Some(&n_token_type) = self.token_type_map.get(token_type) {
-            let line = range.start.line;
-            let char = range.start.character;
-            let length = range.end.character - range.start.character;
+        if self.disabled_token_types.contains(token_type) {
+            return Ok(());
+        }
 
+        if let Some(&n_token_type) = self.token_type_map.get(token_type) {
             let mut n_token_modifiers = 0;
 
             if let Some(token_modifiers) = token_modifiers {
                 for token_modifier in token_modifiers {
+                    if self.disabled_token_modifiers.contains(token_modifier) {
+                        continue;
+                    }
                     if let Some(n_token_modifier) = self.token_modifier_map.get(token_modifier) {
                         n_token_modifiers |= 1 << n_token_modifier;
                     } else {
@@ -204,7 +251,34 @@ impl<'text, 'tree> SemanticTokensBuilder<'text, 'tree> {
                 }
             }
 
-            self.push_encoded(line, char, length, n_token_type, n_token_modifiers);
+            if range.start.line == range.end.line {
+                let line = range.start.line;
+                let char = range.start.character;
+                let length = range.end.character - range.start.character;
+                self.push_encoded(line, char, length, n_token_type, n_token_modifiers);
+            } else if self.multiline {
+                let line = range.start.line;
+                let char = range.start.character;
+                let length = self.utf16_offset(range.end)? - self.utf16_offset(range.start)?;
+                self.push_encoded(line, char, length, n_token_type, n_token_modifiers);
+            } else {
+                for line in range.start.line ..= range.end.line {
+                    let start = if line == range.start.line {
+                        range.start.character
+                    } else {
+                        0
+                    };
+                    let end = if line == range.end.line {
+                        range.end.character
+                    } else {
+                        self.line_len_utf16(line)?
+                    };
+                    // Skip empty segments (e.g., when a token ends at the start of a line).
+                    if start < end {
+                        self.push_encoded(line, start, end - start, n_token_type, n_token_modifiers);
+                    }
+                }
+            }
         } else {
             return Err(anyhow!("`token_type` is not in the provided legend"));
         }
@@ -212,6 +286,36 @@ impl<'text, 'tree> SemanticTokensBuilder<'text, 'tree> {
         Ok(())
     }
 
+    /// Compute the length of a line (excluding the line break) in UTF-16 code units.
+    fn line_len_utf16(&self, line: u32) -> anyhow::Result<u32> {
+        let line = line as usize;
+        if line >= self.content.len_lines() {
+            return Err(anyhow!("`line` is out of bounds"));
+        }
+        let line = self.content.line(line);
+        let mut len = line.len_utf16_cu();
+        let mut chars = line.chars_at(line.len_chars());
+        while let Some(c) = chars.prev() {
+            if c == '\n' || c == '\r' {
+                len -= c.len_utf16();
+            } else {
+                break;
+            }
+        }
+        Ok(u32::try_from(len)?)
+    }
+
+    /// Compute the absolute offset of an [`lsp::Position`] in UTF-16 code units.
+    fn utf16_offset(&self, position: lsp::Position) -> anyhow::Result<u32> {
+        let line = position.line as usize;
+        if line > self.content.len_lines() {
+            return Err(anyhow!("`position` is out of bounds"));
+        }
+        let line_char = self.content.line_to_char(line);
+        let line_utf16_cu = self.content.char_to_utf16_cu(line_char);
+        Ok(u32::try_from(line_utf16_cu)? + position.character)
+    }
+
     /// Push a token in encoded form into the tokenization state.
     pub fn push_encoded(&mut self, row: u32, col: u32, len: u32, token_type: u32, token_mods: u32) {
         let mut push_row = row;
@@ -249,3 +353,88 @@ impl<'text, 'tree> SemanticTokensBuilder<'text, 'tree> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SemanticTokensBuilder;
+
+    fn legend() -> lsp::SemanticTokensLegend {
+        lsp::SemanticTokensLegend {
+            token_types: vec![lsp::SemanticTokenType::COMMENT],
+            token_modifiers: vec![],
+        }
+    }
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> lsp::Range {
+        lsp::Range::new(lsp::Position::new(start.0, start.1), lsp::Position::new(end.0, end.1))
+    }
+
+    #[test]
+    fn push_range_split() -> anyhow::Result<()> {
+        let content = ropey::Rope::from_str("(;a\r\nbc\n;)");
+        let legend = legend();
+        let mut builder = SemanticTokensBuilder::new(&content, Some(&legend), false)?;
+        builder.push_range(range((0, 0), (2, 2)), &lsp::SemanticTokenType::COMMENT, None)?;
+        let lengths = builder
+            .build()
+            .data
+            .iter()
+            .map(|token| (token.delta_line, token.delta_start, token.length))
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![(0, 0, 3), (1, 0, 2), (1, 0, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn push_range_multiline() -> anyhow::Result<()> {
+        let content = ropey::Rope::from_str("(;a\r\nbc\n;)");
+        let legend = legend();
+        let mut builder = SemanticTokensBuilder::new(&content, Some(&legend), true)?;
+        builder.push_range(range((0, 0), (2, 2)), &lsp::SemanticTokenType::COMMENT, None)?;
+        let lengths = builder
+            .build()
+            .data
+            .iter()
+            .map(|token| (token.delta_line, token.delta_start, token.length))
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![(0, 0, 10)]);
+        Ok(())
+    }
+
+    #[test]
+    fn push_range_restricted() -> anyhow::Result<()> {
+        let content = ropey::Rope::from_str("(;a;)");
+        let legend = lsp::SemanticTokensLegend {
+            token_types: vec![lsp::SemanticTokenType::COMMENT, lsp::SemanticTokenType::KEYWORD],
+            token_modifiers: vec![lsp::SemanticTokenModifier::DECLARATION],
+        };
+        let settings = crate::core::SemanticTokensSettings {
+            token_types: Some(vec![String::from("comment")]),
+            token_modifiers: Some(vec![]),
+            ..Default::default()
+        };
+        let mut builder = SemanticTokensBuilder::new(&content, Some(&legend), false)?.restrict(&settings);
+        let declaration = &lsp::SemanticTokenModifier::DECLARATION;
+        builder.push_range(range((0, 0), (0, 2)), &lsp::SemanticTokenType::KEYWORD, None)?;
+        builder.push_range(
+            range((0, 2), (0, 5)),
+            &lsp::SemanticTokenType::COMMENT,
+            Some(vec![declaration]),
+        )?;
+        let tokens = builder
+            .build()
+            .data
+            .iter()
+            .map(|token| {
+                (
+                    token.delta_start,
+                    token.length,
+                    token.token_type,
+                    token.token_modifiers_bitset,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, vec![(2, 3, 0, 0)]);
+        Ok(())
+    }
+}
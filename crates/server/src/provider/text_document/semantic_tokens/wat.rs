@@ -1,7 +1,7 @@
 //! Semantic tokens provider definitions for ".wat" files.
 
 use super::builder::SemanticTokensBuilder;
-use crate::core::{self, language::wat, node::BasicNodeWalker, Language};
+use crate::core::{self, analysis::Analysis, language::wat, node::BasicNodeWalker, Language};
 use anyhow::anyhow;
 use lsp_text::RopeExt;
 use std::sync::Arc;
@@ -9,27 +9,26 @@ use std::sync::Arc;
 pub(crate) async fn full(
     session: Arc<core::Session>,
     params: lsp::SemanticTokensParams,
-    content: &ropey::Rope,
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
 ) -> anyhow::Result<Option<lsp::SemanticTokensResult>> {
-    let params = {
-        let uri = params.text_document.uri.clone();
-        let tree = session.get_tree(&uri).await?;
-        lsp::SemanticTokensRangeParams {
-            work_done_progress_params: params.work_done_progress_params,
-            partial_result_params: params.partial_result_params,
-            text_document: params.text_document,
-            range: {
-                let tree = tree.lock().await;
-                let node = tree.root_node();
-                content.tree_sitter_range_to_lsp_range(node.range())
-            },
-        }
+    let content = &snapshot.text.content;
+    let params = lsp::SemanticTokensRangeParams {
+        work_done_progress_params: params.work_done_progress_params,
+        partial_result_params: params.partial_result_params,
+        text_document: params.text_document,
+        range: {
+            let node = snapshot.tree.root_node();
+            content.tree_sitter_range_to_lsp_range(node.range())
+        },
     };
 
-    let result = range(session, params, content).await?.map(|result| match result {
-        lsp::SemanticTokensRangeResult::Tokens(tokens) => lsp::SemanticTokensResult::Tokens(tokens),
-        lsp::SemanticTokensRangeResult::Partial(partial) => lsp::SemanticTokensResult::Partial(partial),
-    });
+    let result = range(session, params, snapshot, token)
+        .await?
+        .map(|result| match result {
+            lsp::SemanticTokensRangeResult::Tokens(tokens) => lsp::SemanticTokensResult::Tokens(tokens),
+            lsp::SemanticTokensRangeResult::Partial(partial) => lsp::SemanticTokensResult::Partial(partial),
+        });
 
     Ok(result)
 }
@@ -37,27 +36,34 @@ pub(crate) async fn full(
 pub(crate) async fn range(
     session: Arc<core::Session>,
     params: lsp::SemanticTokensRangeParams,
-    content: &ropey::Rope,
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
 ) -> anyhow::Result<Option<lsp::SemanticTokensRangeResult>> {
+    let content = &snapshot.text.content;
     let legend = session.semantic_tokens_legend().await;
     let legend = legend.as_ref();
-
-    let tree = session.get_tree(&params.text_document.uri).await?;
-    let tree = tree.lock().await;
+    let multiline = session.semantic_tokens_multiline_support().await;
+    let settings = session
+        .document_settings(&params.text_document.uri)
+        .await
+        .semantic_tokens;
 
     if let Some(node) = {
         let range = content.lsp_range_to_tree_sitter_range(params.range)?;
         let start = range.start_point();
         let end = range.end_point();
-        tree.root_node().descendant_for_point_range(start, end)
+        snapshot.tree.root_node().descendant_for_point_range(start, end)
     } {
-        let mut handler = Handler::new(content, legend, node)?;
+        let analysis = snapshot.analysis(token)?;
+        let mut handler = Handler::new(content, legend, multiline, &settings, analysis, node)?;
 
         loop {
             if handler.walker.done {
                 break;
             }
 
+            token.tick()?;
+
             // handle "root"
             if wat::kind::ROOT == handler.walker.kind() {
                 handler.root();
@@ -66,12 +72,10 @@ pub(crate) async fn range(
 
             // handle {"comment_block", "comment_block_annot", "comment_line", "comment_line_annot"}
             if wat::kind::COMMENT_BLOCK == handler.walker.kind() {
-                // NOTE: We ignore these for now since we can't highlight multiline tokens.
-                // handler.comment_block()?;
+                handler.comment_block()?;
                 continue;
             } else if wat::kind::COMMENT_BLOCK_ANNOT == handler.walker.kind() {
-                // NOTE: We ignore these for now since we can't highlight multiline tokens.
-                // handler.comment_block_annot()?;
+                handler.comment_block_annot()?;
                 continue;
             } else if wat::kind::COMMENT_LINE == handler.walker.kind() {
                 handler.comment_line()?;
@@ -109,17 +113,77 @@ pub(crate) async fn range(
             } else if wat::kind::MODULE_FIELD_MEMORY == handler.walker.kind() {
                 handler.module_field_memory()?;
                 continue;
+            } else if wat::kind::MODULE_FIELD_REC == handler.walker.kind() {
+                handler.module_field_rec()?;
+                continue;
             } else if wat::kind::MODULE_FIELD_START == handler.walker.kind() {
                 handler.module_field_start()?;
                 continue;
             } else if wat::kind::MODULE_FIELD_TABLE == handler.walker.kind() {
                 handler.module_field_table()?;
                 continue;
+            } else if wat::kind::MODULE_FIELD_TAG == handler.walker.kind() {
+                handler.module_field_tag()?;
+                continue;
             } else if wat::kind::MODULE_FIELD_TYPE == handler.walker.kind() {
                 handler.module_field_type()?;
                 continue;
             }
 
+            // handle {"array_type", "struct_field_many", "struct_field_one", "struct_type", "sub_type"}
+            if wat::kind::ARRAY_TYPE == handler.walker.kind() {
+                handler.array_type()?;
+                continue;
+            } else if wat::kind::STRUCT_FIELD_MANY == handler.walker.kind() {
+                handler.struct_field()?;
+                continue;
+            } else if wat::kind::STRUCT_FIELD_ONE == handler.walker.kind() {
+                handler.struct_field()?;
+                continue;
+            } else if wat::kind::STRUCT_TYPE == handler.walker.kind() {
+                handler.struct_type()?;
+                continue;
+            } else if wat::kind::SUB_TYPE == handler.walker.kind() {
+                handler.sub_type()?;
+                continue;
+            }
+
+            // handle {"block_try_table", "catch_clause", "expr1_try_table"}
+            if wat::kind::BLOCK_TRY_TABLE == handler.walker.kind() {
+                handler.try_table()?;
+                continue;
+            } else if wat::kind::CATCH_CLAUSE == handler.walker.kind() {
+                handler.catch_clause()?;
+                continue;
+            } else if wat::kind::EXPR1_TRY_TABLE == handler.walker.kind() {
+                handler.try_table()?;
+                continue;
+            }
+
+            // handle "identifier"
+            if wat::kind::IDENTIFIER == handler.walker.kind() {
+                handler.identifier()?;
+                continue;
+            }
+
+            // handle "index"
+            if wat::kind::INDEX == handler.walker.kind() {
+                handler.index()?;
+                continue;
+            }
+
+            // handle "instr_plain"
+            if wat::kind::INSTR_PLAIN == handler.walker.kind() {
+                handler.instr_plain()?;
+                continue;
+            }
+
+            // handle "string" (e.g., in data segments or quoted modules)
+            if wat::kind::STRING == handler.walker.kind() {
+                handler.string()?;
+                continue;
+            }
+
             // FIXME: catch all case
             handler.walker.goto_next();
         }
@@ -137,41 +201,73 @@ pub(crate) async fn range(
 struct Handler<'text, 'tree> {
     builder: SemanticTokensBuilder<'text, 'tree>,
     walker: BasicNodeWalker<'tree>,
+    analysis: Arc<Analysis>,
 }
 
 impl<'text, 'tree> Handler<'text, 'tree> {
     fn new(
         content: &'text ropey::Rope,
         legend: Option<&'tree lsp::SemanticTokensLegend>,
+        multiline: bool,
+        settings: &crate::core::SemanticTokensSettings,
+        analysis: Arc<Analysis>,
         node: tree_sitter::Node<'tree>,
     ) -> anyhow::Result<Self> {
         let language = Language::Wat;
-        let builder = SemanticTokensBuilder::new(content, legend)?;
+        let builder = SemanticTokensBuilder::new(content, legend, multiline)?.restrict(settings);
         let walker = BasicNodeWalker::new(language, node);
-        Ok(Self { builder, walker })
+        Ok(Self {
+            builder,
+            walker,
+            analysis,
+        })
+    }
+
+    fn array_type(&mut self) -> anyhow::Result<()> {
+        if let Some(node) = self.walker.node().child(1) {
+            let token_type = &lsp::SemanticTokenType::KEYWORD;
+            let token_modifiers = Default::default();
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn catch_clause(&mut self) -> anyhow::Result<()> {
+        if let Some(node) = self.walker.node().child(1) {
+            let token_type = &lsp::SemanticTokenType::KEYWORD;
+            let token_modifiers = Default::default();
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
     }
 
-    // fn comment_block(&mut self) -> anyhow::Result<()> {
-    //     let node = self.walker.node();
-    //     let token_type = &lsp::SemanticTokenType::COMMENT;
-    //     let token_modifiers = Default::default();
-    //     self.builder.push(node, token_type, token_modifiers)?;
+    fn comment_block(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        let token_type = &lsp::SemanticTokenType::COMMENT;
+        let token_modifiers = Default::default();
+        self.builder.push(node, token_type, token_modifiers)?;
 
-    //     self.walker.goto_next();
+        self.walker.goto_next();
 
-    //     Ok(())
-    // }
+        Ok(())
+    }
 
-    // fn comment_block_annot(&mut self) -> anyhow::Result<()> {
-    //     let node = self.walker.node();
-    //     let token_type = &lsp::SemanticTokenType::COMMENT;
-    //     let token_modifiers = Default::default();
-    //     self.builder.push(node, token_type, token_modifiers)?;
+    fn comment_block_annot(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        let token_type = &lsp::SemanticTokenType::COMMENT;
+        let token_modifiers = Default::default();
+        self.builder.push(node, token_type, token_modifiers)?;
 
-    //     self.walker.goto_next();
+        self.walker.goto_next();
 
-    //     Ok(())
-    // }
+        Ok(())
+    }
 
     fn comment_line(&mut self) -> anyhow::Result<()> {
         let node = self.walker.node();
@@ -330,7 +426,10 @@ impl<'text, 'tree> Handler<'text, 'tree> {
         if wat::kind::IDENTIFIER == self.walker.kind() {
             let node = self.walker.node();
             let token_type = &lsp::SemanticTokenType::FUNCTION;
-            let token_modifiers = Default::default();
+            let token_modifiers = self
+                .analysis
+                .definition_at(&node)
+                .map(|definition| super::token_modifiers(definition, true));
             self.builder.push(node, token_type, token_modifiers)?;
             self.walker.goto_next_sibling();
         }
@@ -372,8 +471,11 @@ impl<'text, 'tree> Handler<'text, 'tree> {
         // optional($.identifier)
         if wat::kind::IDENTIFIER == self.walker.kind() {
             let node = self.walker.node();
-            let token_type = &lsp::SemanticTokenType::FUNCTION;
-            let token_modifiers = Default::default();
+            let token_type = &lsp::SemanticTokenType::VARIABLE;
+            let token_modifiers = self
+                .analysis
+                .definition_at(&node)
+                .map(|definition| super::token_modifiers(definition, true));
             self.builder.push(node, token_type, token_modifiers)?;
             self.walker.goto_next_sibling();
         }
@@ -417,6 +519,18 @@ impl<'text, 'tree> Handler<'text, 'tree> {
         Ok(())
     }
 
+    fn module_field_rec(&mut self) -> anyhow::Result<()> {
+        if let Some(node) = self.walker.node().child(1) {
+            let token_type = &lsp::SemanticTokenType::KEYWORD;
+            let token_modifiers = Default::default();
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
     fn module_field_start(&mut self) -> anyhow::Result<()> {
         if let Some(node) = self.walker.node().child(1) {
             let token_type = &lsp::SemanticTokenType::KEYWORD;
@@ -441,6 +555,18 @@ impl<'text, 'tree> Handler<'text, 'tree> {
         Ok(())
     }
 
+    fn module_field_tag(&mut self) -> anyhow::Result<()> {
+        if let Some(node) = self.walker.node().child(1) {
+            let token_type = &lsp::SemanticTokenType::KEYWORD;
+            let token_modifiers = Default::default();
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
     fn module_field_type(&mut self) -> anyhow::Result<()> {
         if let Some(node) = self.walker.node().child(1) {
             let token_type = &lsp::SemanticTokenType::KEYWORD;
@@ -457,6 +583,129 @@ impl<'text, 'tree> Handler<'text, 'tree> {
         self.walker.goto_next();
     }
 
+    fn identifier(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        if let Some(definition) = self.analysis.definition_at(&node) {
+            let token_type = super::token_type(definition.space);
+            let token_modifiers = Some(super::token_modifiers(definition, true));
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn index(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        if let Some(reference) = self.analysis.reference_at(&node) {
+            let token_type = super::token_type(reference.space);
+            let mut token_modifiers = reference
+                .definition
+                .map(|id| super::token_modifiers(&self.analysis.definitions[id], false))
+                .unwrap_or_default();
+            if self.analysis.is_unreachable(node.start_byte()) {
+                token_modifiers.push(&super::UNREACHABLE);
+            }
+            self.builder.push(node, token_type, Some(token_modifiers))?;
+        }
+
+        // skip the content of the index
+        self.skip();
+
+        Ok(())
+    }
+
+    fn instr_plain(&mut self) -> anyhow::Result<()> {
+        // the mnemonic is the leading token of the operator node
+        if let Some(node) = self.walker.node().named_child(0).and_then(|op| op.child(0)) {
+            let token_type = &lsp::SemanticTokenType::OPERATOR;
+            let mut token_modifiers = vec![];
+            if self.analysis.is_unreachable(node.start_byte()) {
+                token_modifiers.push(&super::UNREACHABLE);
+            }
+            self.builder.push(node, token_type, Some(token_modifiers))?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    // Move past the current node without visiting its children.
+    fn skip(&mut self) {
+        if !self.walker.goto_next_sibling() {
+            self.walker.goto_next_ancestor_sibling();
+        }
+    }
+
+    fn string(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        let token_type = &lsp::SemanticTokenType::STRING;
+        let token_modifiers = Default::default();
+        self.builder.push(node, token_type, token_modifiers)?;
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn struct_field(&mut self) -> anyhow::Result<()> {
+        if let Some(node) = self.walker.node().child(1) {
+            let token_type = &lsp::SemanticTokenType::KEYWORD;
+            let token_modifiers = Default::default();
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn struct_type(&mut self) -> anyhow::Result<()> {
+        if let Some(node) = self.walker.node().child(1) {
+            let token_type = &lsp::SemanticTokenType::KEYWORD;
+            let token_modifiers = Default::default();
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn sub_type(&mut self) -> anyhow::Result<()> {
+        // "sub" optional("final")
+        let node = self.walker.node();
+        for child in [node.child(1), node.child(2)].into_iter().flatten() {
+            if child.kind_id() == wat::kind::token::SUB || child.kind_id() == wat::kind::token::FINAL {
+                let token_type = &lsp::SemanticTokenType::KEYWORD;
+                let token_modifiers = Default::default();
+                self.builder.push(child, token_type, token_modifiers)?;
+            }
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn try_table(&mut self) -> anyhow::Result<()> {
+        // "try_table" is preceded by "(" in the folded form
+        let node = self.walker.node();
+        for child in [node.child(0), node.child(1)].into_iter().flatten() {
+            if child.kind_id() == wat::kind::token::TRY_TABLE {
+                let token_type = &lsp::SemanticTokenType::KEYWORD;
+                let token_modifiers = Default::default();
+                self.builder.push(child, token_type, token_modifiers)?;
+            }
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
     fn type_use(&mut self) -> anyhow::Result<()> {
         // "("
         self.walker.goto_first_child();
@@ -474,13 +723,17 @@ impl<'text, 'tree> Handler<'text, 'tree> {
         self.walker.goto_next_sibling();
         {
             let node = self.walker.node();
-            let token_type = &lsp::SemanticTokenType::VARIABLE;
-            let token_modifiers = Default::default();
+            let token_type = &lsp::SemanticTokenType::TYPE;
+            let token_modifiers = self
+                .analysis
+                .reference_at(&node)
+                .and_then(|reference| reference.definition)
+                .map(|id| super::token_modifiers(&self.analysis.definitions[id], false));
             self.builder.push(node, token_type, token_modifiers)?;
         }
 
         // skip ")"
-        self.walker.goto_parent();
+        self.walker.goto_next_sibling();
 
         self.walker.goto_next();
 
@@ -0,0 +1,202 @@
+//! Semantic tokens provider definitions for ".wit" files.
+
+use super::builder::SemanticTokensBuilder;
+use crate::core::{self, language::wit, node::BasicNodeWalker, Language};
+use anyhow::anyhow;
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+pub(crate) async fn full(
+    session: Arc<core::Session>,
+    params: lsp::SemanticTokensParams,
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<lsp::SemanticTokensResult>> {
+    let content = &snapshot.text.content;
+    let params = lsp::SemanticTokensRangeParams {
+        work_done_progress_params: params.work_done_progress_params,
+        partial_result_params: params.partial_result_params,
+        text_document: params.text_document,
+        range: {
+            let node = snapshot.tree.root_node();
+            content.tree_sitter_range_to_lsp_range(node.range())
+        },
+    };
+
+    let result = range(session, params, snapshot, token)
+        .await?
+        .map(|result| match result {
+            lsp::SemanticTokensRangeResult::Tokens(tokens) => lsp::SemanticTokensResult::Tokens(tokens),
+            lsp::SemanticTokensRangeResult::Partial(partial) => lsp::SemanticTokensResult::Partial(partial),
+        });
+
+    Ok(result)
+}
+
+pub(crate) async fn range(
+    session: Arc<core::Session>,
+    params: lsp::SemanticTokensRangeParams,
+    snapshot: &core::Snapshot,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<lsp::SemanticTokensRangeResult>> {
+    let content = &snapshot.text.content;
+    let legend = session.semantic_tokens_legend().await;
+    let legend = legend.as_ref();
+    let multiline = session.semantic_tokens_multiline_support().await;
+    let settings = session
+        .document_settings(&params.text_document.uri)
+        .await
+        .semantic_tokens;
+
+    if let Some(node) = {
+        let range = content.lsp_range_to_tree_sitter_range(params.range)?;
+        let start = range.start_point();
+        let end = range.end_point();
+        snapshot.tree.root_node().descendant_for_point_range(start, end)
+    } {
+        let mut handler = Handler::new(content, legend, multiline, &settings, node)?;
+
+        loop {
+            if handler.walker.done {
+                break;
+            }
+
+            token.tick()?;
+
+            // handle {"comment_block", "comment_line"}
+            if wit::kind::COMMENT_BLOCK == handler.walker.kind() || wit::kind::COMMENT_LINE == handler.walker.kind() {
+                handler.comment()?;
+                continue;
+            }
+
+            // handle "identifier"
+            if wit::kind::IDENTIFIER == handler.walker.kind() {
+                handler.identifier()?;
+                continue;
+            }
+
+            // handle "primitive_type"
+            if wit::kind::PRIMITIVE_TYPE == handler.walker.kind() {
+                handler.primitive_type()?;
+                continue;
+            }
+
+            // handle keywords (e.g., "interface", "record", "func")
+            if wit::grouped::KEYWORDS.contains(&handler.walker.kind()) {
+                handler.keyword()?;
+                continue;
+            }
+
+            handler.walker.goto_next();
+        }
+
+        let tokens = handler.builder.build();
+        let result = lsp::SemanticTokensRangeResult::Tokens(tokens);
+
+        Ok(Some(result))
+    } else {
+        Err(anyhow!("Could not obtain tree node for given range"))
+    }
+}
+
+// Move to the next appropriate node in the syntax tree.
+struct Handler<'text, 'tree> {
+    builder: SemanticTokensBuilder<'text, 'tree>,
+    walker: BasicNodeWalker<'tree>,
+}
+
+impl<'text, 'tree> Handler<'text, 'tree> {
+    fn new(
+        content: &'text ropey::Rope,
+        legend: Option<&'tree lsp::SemanticTokensLegend>,
+        multiline: bool,
+        settings: &crate::core::SemanticTokensSettings,
+        node: tree_sitter::Node<'tree>,
+    ) -> anyhow::Result<Self> {
+        let language = Language::Wit;
+        let builder = SemanticTokensBuilder::new(content, legend, multiline)?.restrict(settings);
+        let walker = BasicNodeWalker::new(language, node);
+        Ok(Self { builder, walker })
+    }
+
+    fn comment(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        let token_type = &lsp::SemanticTokenType::COMMENT;
+        let token_modifiers = Default::default();
+        self.builder.push(node, token_type, token_modifiers)?;
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        if let Some(parent) = node.parent() {
+            let kind = parent.kind_id();
+            // only the name of a definition (and not e.g. the alias of a use) declares an entity
+            let declaration = parent.child_by_field_id(wit::field::IDENTIFIER).map(|it| it.id()) == Some(node.id());
+            let token_type = if wit::kind::PACKAGE_NAME == kind
+                || wit::kind::USE_PATH == kind
+                || wit::kind::TOPLEVEL_USE == kind
+                || wit::kind::INTERFACE_ITEM == kind
+                || wit::kind::WORLD_ITEM == kind
+            {
+                &super::NAMESPACE
+            } else if wit::kind::FUNC_ITEM == kind || wit::kind::RESOURCE_METHOD == kind {
+                &super::FUNCTION
+            } else if wit::kind::EXPORT_ITEM == kind || wit::kind::IMPORT_ITEM == kind {
+                let func = parent
+                    .children(&mut parent.walk())
+                    .any(|it| wit::kind::FUNC_TYPE == it.kind_id());
+                if func {
+                    &super::FUNCTION
+                } else {
+                    &super::NAMESPACE
+                }
+            } else if wit::kind::PARAM == kind {
+                &super::PARAMETER
+            } else if wit::kind::ENUM_CASE == kind
+                || wit::kind::FLAGS_FIELD == kind
+                || wit::kind::RECORD_FIELD == kind
+                || wit::kind::VARIANT_CASE == kind
+            {
+                &super::PROPERTY
+            } else {
+                &super::TYPE
+            };
+            let token_modifiers = if declaration {
+                Some(vec![&super::DECLARATION])
+            } else {
+                Default::default()
+            };
+            self.builder.push(node, token_type, token_modifiers)?;
+        }
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn keyword(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        let token_type = &lsp::SemanticTokenType::KEYWORD;
+        let token_modifiers = Default::default();
+        self.builder.push(node, token_type, token_modifiers)?;
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+
+    fn primitive_type(&mut self) -> anyhow::Result<()> {
+        let node = self.walker.node();
+        let token_type = &lsp::SemanticTokenType::TYPE;
+        let token_modifiers = Some(vec![&super::DEFAULT_LIBRARY]);
+        self.builder.push(node, token_type, token_modifiers)?;
+
+        self.walker.goto_next();
+
+        Ok(())
+    }
+}
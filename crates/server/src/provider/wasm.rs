@@ -0,0 +1,16 @@
+/// Provider definitions for the custom `wasm/assemble` request.
+pub mod assemble;
+
+/// Provider definitions for the custom `wasm/disassemble` request.
+pub mod disassemble;
+
+/// Provider definitions for the custom `wasm/sizeReport` request.
+pub mod size_report;
+
+/// Provider definitions for the custom `wasm/sourceLocation` request.
+pub mod source_location;
+
+pub use assemble::assemble;
+pub use disassemble::disassemble;
+pub use size_report::size_report;
+pub use source_location::source_location;
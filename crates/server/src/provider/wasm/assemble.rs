@@ -0,0 +1,61 @@
+use crate::{core, ext};
+use anyhow::anyhow;
+use std::sync::Arc;
+
+/// Provider function for the custom `wasm/assemble` request.
+pub async fn assemble(session: Arc<core::Session>, params: ext::AssembleParams) -> anyhow::Result<ext::AssembleResult> {
+    let uri = &params.text_document.uri;
+    let options = core::encoding::EncodeOptions {
+        name_section: params.name_section.unwrap_or(true),
+        custom_sections: params.custom_sections.unwrap_or(true),
+    };
+
+    let snapshot = session.get_snapshot(uri).await?;
+    let encoding = {
+        let text = &snapshot.text;
+        if let core::Language::Component | core::Language::Wast | core::Language::Wit = text.language {
+            return Err(anyhow!("only .wat documents can be assembled :: uri: {}", uri));
+        }
+        if core::encoding::EncodeOptions::default() == options {
+            snapshot.encoding()
+        } else {
+            core::encoding::encode(&text.content, &options).map(Arc::new)
+        }
+    };
+
+    let bytes = match encoding {
+        Ok(encoding) => encoding.bytes.clone(),
+        Err(diagnostic) => {
+            let diagnostics = vec![*diagnostic];
+            // also surface the failure in the editor when diagnostics are published by the server
+            if !session.diagnostic_pull_support().await {
+                let extra = diagnostics.clone();
+                session
+                    .diagnostics
+                    .publish(session.clone(), uri.clone(), snapshot.version(), extra)
+                    .await?;
+            }
+            return Ok(ext::AssembleResult {
+                diagnostics,
+                ..Default::default()
+            });
+        },
+    };
+
+    let result = match params.output {
+        ext::AssembleOutput::Base64 => ext::AssembleResult {
+            contents: Some(base64::encode(&bytes)),
+            ..Default::default()
+        },
+        ext::AssembleOutput::File => {
+            let uri = core::workspace::sibling(uri, "wasm")?;
+            core::workspace::write_bytes(&uri, &bytes)?;
+            ext::AssembleResult {
+                uri: Some(uri),
+                ..Default::default()
+            }
+        },
+    };
+
+    Ok(result)
+}
@@ -0,0 +1,28 @@
+use crate::{core, ext};
+use std::sync::Arc;
+
+/// Provider function for the custom `wasm/disassemble` request.
+pub async fn disassemble(
+    session: Arc<core::Session>,
+    params: ext::DisassembleParams,
+) -> anyhow::Result<ext::DisassembleResult> {
+    let bytes = match params.contents {
+        Some(contents) => base64::decode(contents)?,
+        None => core::workspace::read_bytes(&params.uri)?,
+    };
+    let text = core::disassembly::disassemble(&bytes)?;
+    let uri = core::disassembly::uri(&params.uri)?;
+
+    let text_document = {
+        let language_id = core::Language::Wasm.id().into();
+        let version = Default::default();
+        lsp::TextDocumentItem::new(uri.clone(), language_id, version, text.clone())
+    };
+    let params = lsp::DidOpenTextDocumentParams { text_document };
+    if let Some(document) = core::Document::open(session.clone(), params)? {
+        // replace any previous disassembly of the module
+        session.insert_document(uri.clone(), document).await;
+    }
+
+    Ok(ext::DisassembleResult { uri, text })
+}
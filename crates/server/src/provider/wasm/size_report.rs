@@ -0,0 +1,64 @@
+use crate::{
+    core::{self, analysis::IndexSpace, encoding},
+    ext,
+};
+use anyhow::anyhow;
+use lsp_text::RopeExt;
+use std::sync::Arc;
+
+/// Provider function for the custom `wasm/sizeReport` request.
+pub async fn size_report(
+    session: Arc<core::Session>,
+    params: ext::SizeReportParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<ext::SizeReportResult> {
+    let uri = &params.text_document.uri;
+    let snapshot = session.get_snapshot(uri).await?;
+    let token = token.with_snapshot(snapshot.clone());
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wast | core::Language::Wit = text.language {
+        return Err(anyhow!("only .wat documents can be encoded :: uri: {}", uri));
+    }
+    let content = &text.content;
+
+    let encoding = snapshot
+        .encoding()
+        .map_err(|diagnostic| anyhow!("document could not be encoded :: {}", diagnostic.message))?;
+
+    let sections = encoding::sections(&encoding.bytes)?
+        .into_iter()
+        .map(|section| {
+            Ok(ext::SectionSize {
+                kind: section.kind().into(),
+                size: u32::try_from(section.range.len())?,
+                name: section.name,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let analysis = snapshot.analysis(&token)?;
+    let functions = encoding
+        .source_map
+        .functions
+        .iter()
+        .map(|function| {
+            let definition = analysis.definitions.iter().find(|it| {
+                IndexSpace::Func == it.space
+                    && (it.range.start_byte() as usize .. it.range.end_byte() as usize).contains(&function.source)
+            });
+            Ok(ext::FunctionSize {
+                index: function.index,
+                name: definition.and_then(|it| it.name.clone()),
+                size: u32::try_from(function.body.len())?,
+                instructions: u32::try_from(function.instructions.len())?,
+                range: definition.map(|it| content.tree_sitter_range_to_lsp_range(it.range.clone())),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(ext::SizeReportResult {
+        size: u32::try_from(encoding.bytes.len())?,
+        sections,
+        functions,
+    })
+}
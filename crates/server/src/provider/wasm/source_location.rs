@@ -0,0 +1,58 @@
+use crate::{core, ext};
+use anyhow::anyhow;
+use std::sync::Arc;
+
+/// Provider function for the custom `wasm/sourceLocation` request.
+pub async fn source_location(
+    session: Arc<core::Session>,
+    params: ext::SourceLocationParams,
+) -> anyhow::Result<Option<lsp::Location>> {
+    let uri = params.text_document.uri;
+    let snapshot = session.get_snapshot(&uri).await?;
+    let text = &snapshot.text;
+    if let core::Language::Component | core::Language::Wast | core::Language::Wit = text.language {
+        return Err(anyhow!("only .wat documents can be encoded :: uri: {}", uri));
+    }
+
+    let encoding = snapshot
+        .encoding()
+        .map_err(|diagnostic| anyhow!("document could not be encoded :: {}", diagnostic.message))?;
+    let source_map = &encoding.source_map;
+
+    let source = match (params.function, params.offset, params.module_offset) {
+        (Some(function), offset, None) => source_map
+            .function(function)
+            .and_then(|it| it.lookup(usize::try_from(offset.unwrap_or_default()).ok()?)),
+        (Some(function), None, Some(offset)) => {
+            let offset = usize::try_from(offset)?;
+            match source_map.function(function) {
+                Some(mapping) if mapping.body.contains(&offset) => mapping.lookup(offset - mapping.body.start),
+                Some(_) => {
+                    let message = format!("`moduleOffset` does not lie within the body of function {}", function);
+                    return Err(core::Error::InvalidParams(message).into());
+                },
+                None => None,
+            }
+        },
+        (None, None, Some(offset)) => source_map.lookup(usize::try_from(offset)?),
+        (_, Some(_), Some(_)) => {
+            let message = String::from("`offset` and `moduleOffset` are mutually exclusive");
+            return Err(core::Error::InvalidParams(message).into());
+        },
+        (None, Some(_), _) => {
+            let message = String::from("`offset` requires `function`");
+            return Err(core::Error::InvalidParams(message).into());
+        },
+        (None, None, None) => {
+            let message = String::from("either `function` or `moduleOffset` must be given");
+            return Err(core::Error::InvalidParams(message).into());
+        },
+    };
+
+    let location = source.map(|offset| {
+        let range = core::encoding::token_range(&text.content, offset);
+        lsp::Location::new(uri.clone(), range)
+    });
+
+    Ok(location)
+}
@@ -0,0 +1,4 @@
+/// Provider definitions for LSP `workspace/diagnostic`.
+pub mod diagnostic;
+
+pub use diagnostic::diagnostic;
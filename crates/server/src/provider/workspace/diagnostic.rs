@@ -0,0 +1,152 @@
+use crate::core;
+use std::{collections::HashMap, sync::Arc};
+
+/// Provider function for LSP `workspace/diagnostic`.
+///
+/// The diagnostics of each file are cached (see [`core::workspace::Cache`]), so that the files
+/// which did not change since the previous request are neither read nor parsed again.
+pub async fn diagnostic(
+    session: Arc<core::Session>,
+    params: crate::ext::WorkspaceDiagnosticParams,
+    token: core::CancellationToken,
+) -> anyhow::Result<crate::ext::WorkspaceDiagnosticReport> {
+    let previous = params
+        .previous_result_ids
+        .into_iter()
+        .map(|previous| (previous.uri, previous.value))
+        .collect::<HashMap<_, _>>();
+
+    let mut items = vec![];
+
+    // report diagnostics for the documents which are currently open
+    let opened = session.document_uris().await;
+    for uri in opened.iter().cloned() {
+        let snapshot = match session.get_snapshot(&uri).await {
+            Ok(snapshot) => snapshot,
+            // the document was closed in the meantime
+            Err(_) => continue,
+        };
+        let version = Some(snapshot.version());
+        let report = match cached(&session, &uri, version).await {
+            Some(report) => report,
+            None => {
+                let settings = session.document_settings(&uri).await;
+                let (tree, text) = (&snapshot.tree, &snapshot.text);
+                let token = token.with_snapshot(snapshot.clone());
+                let mut diagnostics = crate::provider::text_document::diagnostics(tree, text, &settings, &token)?;
+                let analysis = snapshot.analysis(&token)?;
+                diagnostics.extend(crate::provider::text_document::semantic_diagnostics(
+                    text, &analysis, &settings, &token,
+                )?);
+                cache(&session, &uri, version, diagnostics).await
+            },
+        };
+        items.push(self::report(uri, report, &previous));
+    }
+
+    // report diagnostics for the indexed documents which are not currently open
+    for uri in session.workspace_files().await? {
+        token.tick()?;
+        if opened.contains(&uri) {
+            continue;
+        }
+        let report = match cached(&session, &uri, None).await {
+            Some(report) => report,
+            None => match compute(&session, &uri, &token).await {
+                Ok(Some(diagnostics)) => cache(&session, &uri, None, diagnostics).await,
+                Ok(None) => continue,
+                Err(error) => {
+                    // a single unreadable (or unsupported) file must not fail the whole request
+                    if token.is_cancelled() {
+                        return Err(error);
+                    }
+                    log::warn!("skipping workspace file :: uri: {}, error: {}", uri, error);
+                    continue;
+                },
+            },
+        };
+        items.push(self::report(uri, report, &previous));
+    }
+
+    Ok(crate::ext::WorkspaceDiagnosticReport { items })
+}
+
+/// Retrieve the cached diagnostics of the workspace file at `uri`, if they are still valid.
+async fn cached(session: &core::Session, uri: &lsp::Url, version: Option<i32>) -> Option<core::workspace::Report> {
+    session.workspace.read().await.report(uri, version).cloned()
+}
+
+/// Cache the diagnostics computed for the workspace file at `uri`.
+async fn cache(
+    session: &core::Session,
+    uri: &lsp::Url,
+    version: Option<i32>,
+    items: Vec<lsp::Diagnostic>,
+) -> core::workspace::Report {
+    let result_id = crate::provider::text_document::diagnostic::result_id(&items);
+    let report = core::workspace::Report {
+        version,
+        result_id,
+        items,
+    };
+    session
+        .workspace
+        .write()
+        .await
+        .insert_report(uri.clone(), report.clone());
+    report
+}
+
+/// Compute the diagnostics of the workspace file at `uri` (which is not open) from its content on
+/// disk.
+async fn compute(
+    session: &Arc<core::Session>,
+    uri: &lsp::Url,
+    token: &core::CancellationToken,
+) -> anyhow::Result<Option<Vec<lsp::Diagnostic>>> {
+    let text_document = {
+        let language_id = core::workspace::language(uri)?.id().into();
+        let version = Default::default();
+        let text = session.read_workspace_file(uri).await?;
+        lsp::TextDocumentItem::new(uri.clone(), language_id, version, text)
+    };
+    let params = lsp::DidOpenTextDocumentParams { text_document };
+    if let Some(document) = core::Document::open(session.clone(), params)? {
+        let settings = session.document_settings(uri).await;
+        let text = document.text();
+        let mut diagnostics = crate::provider::text_document::diagnostics(&document.tree, &text, &settings, token)?;
+        let analysis = core::analysis::Analysis::new(text.language, &document.tree, &text.content, token)?;
+        diagnostics.extend(crate::provider::text_document::semantic_diagnostics(
+            &text, &analysis, &settings, token,
+        )?);
+        return Ok(Some(diagnostics));
+    }
+    Ok(None)
+}
+
+fn report(
+    uri: lsp::Url,
+    report: core::workspace::Report,
+    previous: &HashMap<lsp::Url, String>,
+) -> crate::ext::WorkspaceDocumentDiagnosticReport {
+    let version = report.version.map(Into::into);
+    let result_id = report.result_id;
+    if previous.get(&uri) == Some(&result_id) {
+        crate::ext::WorkspaceDocumentDiagnosticReport::Unchanged(
+            crate::ext::WorkspaceUnchangedDocumentDiagnosticReport {
+                uri,
+                version,
+                unchanged_document_diagnostic_report: crate::ext::UnchangedDocumentDiagnosticReport { result_id },
+            },
+        )
+    } else {
+        crate::ext::WorkspaceDocumentDiagnosticReport::Full(crate::ext::WorkspaceFullDocumentDiagnosticReport {
+            uri,
+            version,
+            full_document_diagnostic_report: crate::ext::FullDocumentDiagnosticReport {
+                result_id: Some(result_id),
+                items: report.items,
+            },
+        })
+    }
+}
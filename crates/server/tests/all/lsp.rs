@@ -144,14 +144,14 @@ async fn shutdown() -> anyhow::Result<()> {
 }
 
 mod text_document {
-    use futures::stream::StreamExt;
-    use serde_json::Value;
+    use futures::{future::FutureExt, stream::StreamExt};
+    use serde_json::{json, Value};
 
     #[futures_test::test]
-    async fn did_close() -> anyhow::Result<()> {
+    async fn code_lens() -> anyhow::Result<()> {
         let uri = lsp::Url::parse("inmemory:///test")?;
-        let language_id = "wasm.wast";
-        let text = String::from("(module)");
+        let language_id = "wasm.wat";
+        let text = String::from("(module\n  (func $f\n    i32.const 42\n    drop))");
 
         let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
@@ -176,17 +176,128 @@ mod text_document {
         testing::assert_exchange!(service, notification, Ok(status));
 
         // receive "textDocument/publishDiagnostics" notification for `uri`
-        let message = messages.next().await.unwrap();
-        let actual = serde_json::to_value(&message)?;
-        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[]);
-        assert_eq!(actual, expected);
+        messages.next().await.unwrap();
 
-        // send "textDocument/didClose" notification for `uri`
+        // send "textDocument/codeLens" request for `uri`; the body is `00 41 2a 1a 0b`
         testing::assert_status!(service, Ok(()));
-        let notification = &testing::lsp::text_document::did_close::notification(&uri);
+        let request = &testing::lsp::text_document::code_lens::request(&uri);
+        let lens = lsp::CodeLens {
+            range: lsp::Range::new(lsp::Position::new(1, 2), lsp::Position::new(3, 9)),
+            command: Some(lsp::Command::new(
+                String::from("5 bytes, 2 instructions"),
+                String::from(wasm_lsp_server::ext::SIZE_REPORT_COMMAND),
+                Some(vec![json!({ "textDocument": { "uri": uri } })]),
+            )),
+            data: None,
+        };
+        let response = Some(testing::lsp::text_document::code_lens::response(Some(vec![lens])));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn definition() -> anyhow::Result<()> {
+        let types_uri = lsp::Url::parse("inmemory:///types.wit")?;
+        let world_uri = lsp::Url::parse("inmemory:///world.wit")?;
+        let language_id = "wasm.wit";
+        #[rustfmt::skip]
+        let types_text = String::from(indoc::indoc! {r#"
+            package example:app;
+
+            interface types {
+              record point {
+                x: u32,
+                y: u32,
+              }
+            }
+        "#});
+        #[rustfmt::skip]
+        let world_text = String::from(indoc::indoc! {r#"
+            package example:app;
+
+            world app {
+              use types.{point};
+              import draw: func(p: point);
+            }
+        "#});
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `types_uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&types_uri, language_id, 1, types_text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `types_uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `world_uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&world_uri, language_id, 1, world_text);
         let status = None::<Value>;
         testing::assert_exchange!(service, notification, Ok(status));
 
+        // receive "textDocument/publishDiagnostics" notification for `world_uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/definition" request for the interface of `use types.{point}`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::definition::request(&world_uri, lsp::Position::new(3, 6));
+        let location = lsp::Location::new(
+            types_uri.clone(),
+            lsp::Range::new(lsp::Position::new(2, 10), lsp::Position::new(2, 15)),
+        );
+        let response = Some(testing::lsp::text_document::definition::response(Some(
+            lsp::GotoDefinitionResponse::Scalar(location),
+        )));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/definition" request for the name of `use types.{point}`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::definition::request(&world_uri, lsp::Position::new(3, 13));
+        let location = lsp::Location::new(
+            types_uri.clone(),
+            lsp::Range::new(lsp::Position::new(3, 9), lsp::Position::new(3, 14)),
+        );
+        let response = Some(testing::lsp::text_document::definition::response(Some(
+            lsp::GotoDefinitionResponse::Scalar(location),
+        )));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/definition" request for the (unresolved) name of `import draw`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::definition::request(&world_uri, lsp::Position::new(4, 10));
+        let response = Some(testing::lsp::text_document::definition::response(None));
+        testing::assert_exchange!(service, request, Ok(response));
+
         // send "shutdown" request
         testing::assert_status!(service, Ok(()));
         let request = &testing::lsp::shutdown::request();
@@ -203,10 +314,19 @@ mod text_document {
     }
 
     #[futures_test::test]
-    async fn did_open() -> anyhow::Result<()> {
+    async fn definition_index() -> anyhow::Result<()> {
         let uri = lsp::Url::parse("inmemory:///test")?;
-        let language_id = "wasm.wast";
-        let text = String::from("(module)");
+        let language_id = "wasm.wat";
+        #[rustfmt::skip]
+        let text = String::from(indoc::indoc! {r#"
+            (module
+              (memory $m 1)
+              (func $f
+                i32.const 0
+                i32.load $m
+                drop
+                call $f))
+        "#});
 
         let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
@@ -231,10 +351,37 @@ mod text_document {
         testing::assert_exchange!(service, notification, Ok(status));
 
         // receive "textDocument/publishDiagnostics" notification for `uri`
-        let message = messages.next().await.unwrap();
-        let actual = serde_json::to_value(&message)?;
-        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[]);
-        assert_eq!(actual, expected);
+        messages.next().await.unwrap();
+
+        // send "textDocument/definition" request for the memory index of `i32.load $m`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::definition::request(&uri, lsp::Position::new(4, 13));
+        let location = lsp::Location::new(
+            uri.clone(),
+            lsp::Range::new(lsp::Position::new(1, 10), lsp::Position::new(1, 12)),
+        );
+        let response = Some(testing::lsp::text_document::definition::response(Some(
+            lsp::GotoDefinitionResponse::Scalar(location),
+        )));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/definition" request for the function index of `call $f`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::definition::request(&uri, lsp::Position::new(6, 10));
+        let location = lsp::Location::new(
+            uri.clone(),
+            lsp::Range::new(lsp::Position::new(2, 8), lsp::Position::new(2, 10)),
+        );
+        let response = Some(testing::lsp::text_document::definition::response(Some(
+            lsp::GotoDefinitionResponse::Scalar(location),
+        )));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/definition" request for the mnemonic of `drop`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::definition::request(&uri, lsp::Position::new(5, 6));
+        let response = Some(testing::lsp::text_document::definition::response(None));
+        testing::assert_exchange!(service, request, Ok(response));
 
         // send "shutdown" request
         testing::assert_status!(service, Ok(()));
@@ -251,585 +398,2080 @@ mod text_document {
         Ok(())
     }
 
-    mod document_symbol {
-        use futures::stream::StreamExt;
-        use serde_json::{json, Value};
+    #[futures_test::test]
+    async fn diagnostic() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module)");
 
-        #[futures_test::test]
-        async fn wat() -> anyhow::Result<()> {
-            let uri = lsp::Url::parse("inmemory:///test")?;
-            let language_id = "wasm.wat";
-            #[rustfmt::skip]
-            let text = String::from(indoc::indoc! {r#"
-                (type $a (func))
-                (global $g i32 (i32.const 0))
-                (memory $m 1)
-                (data (i32.const 0))
-                (table $t 10 funcref)
-                (func $f)
-                (elem (i32.const 0))
-            "#});
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
-            let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+        // send "initialize" request; the client supports the pull model for diagnostics
+        testing::assert_status!(service, Ok(()));
+        let capabilities = json!({ "textDocument": { "diagnostic": { "dynamicRegistration": false } } });
+        let request = &testing::lsp::initialize::request_with_capabilities(capabilities);
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-            // send "initialize" request
-            testing::assert_status!(service, Ok(()));
-            let request = &testing::lsp::initialize::request();
-            let response = Some(testing::lsp::initialize::response());
-            testing::assert_exchange!(service, request, Ok(response));
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
 
-            // send "initialized" notification
-            testing::assert_status!(service, Ok(()));
-            let notification = &testing::lsp::initialized::notification();
-            let status = None::<Value>;
-            testing::assert_exchange!(service, notification, Ok(status));
-            // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
-            messages.next().await.unwrap();
+        // send "textDocument/didOpen" notification for `uri`; no diagnostics are published since the
+        // client pulls them
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text.clone());
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        assert!(messages.next().now_or_never().is_none());
 
-            // send "textDocument/didOpen" notification for `uri`
-            testing::assert_status!(service, Ok(()));
-            let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
-            let status = None::<Value>;
-            testing::assert_exchange!(service, notification, Ok(status));
+        let result_id = wasm_lsp_server::provider::text_document::diagnostic::result_id(&[]);
 
-            // receive "textDocument/publishDiagnostics" notification for `uri`
-            let message = messages.next().await.unwrap();
-            let actual = serde_json::to_value(&message)?;
-            let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[]);
-            assert_eq!(actual, expected);
+        // send "textDocument/diagnostic" request for `uri`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::diagnostic::request(&uri, None);
+        let response = Some(testing::lsp::text_document::diagnostic::response(
+            wasm_lsp_server::ext::DocumentDiagnosticReport::Full(wasm_lsp_server::ext::FullDocumentDiagnosticReport {
+                result_id: Some(result_id.clone()),
+                items: vec![],
+            }),
+        ));
+        testing::assert_exchange!(service, request, Ok(response));
 
-            // send "textDocument/documentSymbol" request for `uri`
-            testing::assert_status!(service, Ok(()));
-            let request = &testing::lsp::text_document::document_symbol::request(&uri);
-            #[rustfmt::skip]
-            let response = Some(json!({
-                "jsonrpc": "2.0",
-                "result": [
-                    {
-                        "name": "$a",
-                        "kind": lsp::SymbolKind::TYPE_PARAMETER,
-                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 16 } },
-                        "selectionRange": { "start": { "line": 0, "character": 6 }, "end": { "line": 0, "character": 8 } },
-                        "children": [],
-                    },
-                    {
-                        "name": "$g",
-                        "kind": lsp::SymbolKind::EVENT,
-                        "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 29 } },
-                        "selectionRange": { "start": { "line": 1, "character": 8 }, "end": { "line": 1, "character": 10 } },
-                        "children": [],
-                    },
-                    {
-                        "name": "$m",
-                        "kind": lsp::SymbolKind::ARRAY,
-                        "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 13 } },
-                        "selectionRange": { "start": { "line": 2, "character": 8 }, "end": { "line": 2, "character": 10 } },
-                        "children": [],
-                    },
-                    {
-                        "name": "<data@4:1>",
-                        "kind": lsp::SymbolKind::KEY,
-                        "range": { "start": { "line": 3, "character": 0 }, "end": { "line": 3, "character": 20 } },
-                        "selectionRange": { "start": { "line": 3, "character": 0 }, "end": { "line": 3, "character": 20 } },
-                        "children": [],
-                    },
-                    {
-                        "name": "$t",
-                        "kind": lsp::SymbolKind::INTERFACE,
-                        "range": { "start": { "line": 4, "character": 0 }, "end": { "line": 4, "character": 21 } },
-                        "selectionRange": { "start": { "line": 4, "character": 7 }, "end": { "line": 4, "character": 9 } },
-                        "children": [],
-                    },
-                    {
-                        "name": "$f",
-                        "kind": lsp::SymbolKind::FUNCTION,
-                        "range": { "start": { "line": 5, "character": 0 }, "end": { "line": 5, "character": 9 } },
-                        "selectionRange": { "start": { "line": 5, "character": 6 }, "end": { "line": 5, "character": 8 } },
-                        "children": [],
-                    },
-                    {
-                        "name": "<elem@7:1>",
-                        "kind": lsp::SymbolKind::FIELD,
-                        "range": { "start": { "line": 6, "character": 0 }, "end": { "line": 6, "character": 20 } },
-                        "selectionRange": { "start": { "line": 6, "character": 0 }, "end": { "line": 6, "character": 20 } },
-                    },
-                ],
-                "id": 1,
-            }));
-            testing::assert_exchange!(service, request, Ok(response));
+        // send "textDocument/diagnostic" request for `uri` (again); should be unchanged
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::diagnostic::request(&uri, Some(&result_id));
+        let response = Some(testing::lsp::text_document::diagnostic::response(
+            wasm_lsp_server::ext::DocumentDiagnosticReport::Unchanged(
+                wasm_lsp_server::ext::UnchangedDocumentDiagnosticReport { result_id },
+            ),
+        ));
+        testing::assert_exchange!(service, request, Ok(response));
 
-            // send "shutdown" request
-            testing::assert_status!(service, Ok(()));
-            let request = &testing::lsp::shutdown::request();
-            let response = Some(testing::lsp::shutdown::response());
-            testing::assert_exchange!(service, request, Ok(response));
+        // send "textDocument/didChange" notification for `uri`; no diagnostics are published either
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 2, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        assert!(messages.next().now_or_never().is_none());
 
-            // send "exit" notification
-            testing::assert_status!(service, Ok(()));
-            let notification = &testing::lsp::exit::notification();
-            let status = None::<Value>;
-            testing::assert_exchange!(service, notification, Ok(status));
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-            Ok(())
-        }
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-        #[futures_test::test]
-        async fn wast() -> anyhow::Result<()> {
-            let uri = lsp::Url::parse("inmemory:///test")?;
-            let language_id = "wasm.wast";
-            #[rustfmt::skip]
-            let text = String::from(indoc::indoc! {r#"
-                (module $m
-                  (type $a (func))
-                  (global $g i32 (i32.const 0))
-                  (memory $m 1)
-                  (data (i32.const 0))
-                  (table $t 10 funcref)
-                  (func $f)
-                  (elem (i32.const 0)))
-                (assert_return (invoke "empty"))
-            "#});
+        Ok(())
+    }
 
-            let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+    #[futures_test::test]
+    async fn did_change() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module)");
 
-            // send "initialize" request
-            testing::assert_status!(service, Ok(()));
-            let request = &testing::lsp::initialize::request();
-            let response = Some(testing::lsp::initialize::response());
-            testing::assert_exchange!(service, request, Ok(response));
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didChange" notification for `uri` before it is opened; should be rejected
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 1, "(module");
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+        assert_eq!(actual, expected);
+
+        // send "textDocument/didChange" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 2, "(module (func))");
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(2));
+        assert_eq!(actual, expected);
+
+        // send stale "textDocument/didChange" notification for `uri`; should be rejected
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 2, "(module");
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // send "textDocument/didChange" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 3, "(module)");
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri` (only for the newer version)
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(3));
+        assert_eq!(actual, expected);
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn did_change_debounced() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module)");
+
+        // compute diagnostics in the background (on a thread per task)
+        let spawner: wasm_lsp_server::core::Spawner = std::sync::Arc::new(|task| {
+            std::thread::spawn(move || futures::executor::block_on(task));
+        });
+        let (ref mut service, ref mut messages) = testing::service::spawn_with(Some(spawner)).await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`; diagnostics are not published yet
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        assert!(messages.next().now_or_never().is_none());
+
+        // send "textDocument/didChange" notification for `uri` within the debounce delay
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 2, "(module (func))");
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri` (only for the newer version)
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(2));
+        assert_eq!(actual, expected);
+
+        // send "textDocument/didChange" notification for `uri`, then close it within the debounce delay
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_change::notification::entire(&uri, 3, "(module)");
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_close::notification(&uri);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri` (clearing the diagnostics)
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], None);
+        assert_eq!(actual, expected);
+
+        // the cancelled computation for the closed document publishes nothing
+        let debounce = wasm_lsp_server::core::DEFAULT_DIAGNOSTICS_DEBOUNCE * 2;
+        futures_timer::Delay::new(debounce).await;
+        assert!(messages.next().now_or_never().is_none());
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn did_close() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wast";
+        let text = String::from("(module)");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+        assert_eq!(actual, expected);
+
+        // send "textDocument/didClose" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_close::notification(&uri);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn did_open() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wast";
+        let text = String::from("(module)");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+        assert_eq!(actual, expected);
+
+        // send "textDocument/didOpen" notification for `uri` again; should replace the document
+        testing::assert_status!(service, Ok(()));
+        let text = String::from("(module (func))");
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 2, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(2));
+        assert_eq!(actual, expected);
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn did_open_invalid_language_id() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.bogus";
+        let text = String::from("(module)");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`; should be reported instead of panicking
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "window/showMessage" and "window/logMessage" notifications for the failure
+        let typ = lsp::MessageType::ERROR;
+        let message = "'textDocument/didOpen' failed :: error: InvalidLanguageId: wasm.bogus";
+        let actual = serde_json::to_value(&messages.next().await.unwrap())?;
+        let expected = testing::lsp::window::show_message::notification(typ, message);
+        assert_eq!(actual, expected);
+        let actual = serde_json::to_value(&messages.next().await.unwrap())?;
+        let expected = testing::lsp::window::log_message::notification(typ, message);
+        assert_eq!(actual, expected);
+
+        // send "textDocument/hover" request for `uri`; should fail since the document was not opened
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::hover::request(&uri, lsp::Position::new(0, 1));
+        let response = testing::service::send(service, request).await.unwrap().unwrap();
+        let code = tower_lsp::jsonrpc::ErrorCode::InvalidParams.code();
+        assert_eq!(response["error"]["code"], code);
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn did_open_semantic() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wast";
+        let text = String::from(indoc::indoc! {r#"
+            (module
+              (type $t (func (param i32)))
+              (func (type $t)
+                local.get 0
+                drop))
+            (assert_invalid
+              (module (func local.get 0 drop))
+              "unknown local")
+        "#});
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`; the parameter is given by the
+        // type use and the module of `assert_invalid` is not analyzed
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+        assert_eq!(actual, expected);
+        assert!(messages.next().now_or_never().is_none());
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn formatting() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module\n(func))");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/formatting" request for `uri`; the settings (not the options) apply
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::formatting::request(&uri);
+        let range = lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(1, 6));
+        let edit = lsp::TextEdit::new(range, String::from("(module\n  (func))\n"));
+        let response = Some(testing::lsp::text_document::formatting::response(Some(vec![edit])));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn hover() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module\n  (func\n    i32.const 42\n    drop))");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/hover" request for `i32.const`; the body is `0x00` (no locals) first
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::hover::request(&uri, lsp::Position::new(2, 6));
+        let hover = lsp::Hover {
+            contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                kind: lsp::MarkupKind::Markdown,
+                value: String::from("```text\n41 | 2a\n```\n\n2 bytes at offset `0x1` of the body of function 0"),
+            }),
+            range: Some(lsp::Range::new(lsp::Position::new(2, 4), lsp::Position::new(2, 13))),
+        };
+        let response = Some(testing::lsp::text_document::hover::response(Some(hover)));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/hover" request for the immediate of `i32.const`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::hover::request(&uri, lsp::Position::new(2, 14));
+        let response = Some(testing::lsp::text_document::hover::response(None));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn inlay_hint() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module\n  (func $f)\n  (func $g))");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/inlayHint" request for the entire document
+        testing::assert_status!(service, Ok(()));
+        let range = lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(3, 0));
+        let request = &testing::lsp::text_document::inlay_hint::request(&uri, range);
+        let hint = |line, index| lsp::InlayHint {
+            position: lsp::Position::new(line, 10),
+            label: lsp::InlayHintLabel::String(format!("(;{};)", index)),
+            kind: None,
+            text_edits: None,
+            tooltip: Some(lsp::InlayHintTooltip::String(format!("func index {}", index))),
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        };
+        let response = Some(testing::lsp::text_document::inlay_hint::response(Some(vec![
+            hint(1, 0),
+            hint(2, 1),
+        ])));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/inlayHint" request for the first line only
+        testing::assert_status!(service, Ok(()));
+        let range = lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(1, 20));
+        let request = &testing::lsp::text_document::inlay_hint::request(&uri, range);
+        let response = Some(testing::lsp::text_document::inlay_hint::response(Some(vec![hint(
+            1, 0,
+        )])));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn references() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        #[rustfmt::skip]
+        let text = String::from(indoc::indoc! {r#"
+            (module
+              (memory $m 1)
+              (func $f
+                i32.const 0
+                i32.load $m
+                drop
+                call $f))
+        "#});
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/references" request for the identifier of `(func $f)`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::references::request(&uri, lsp::Position::new(2, 9), true);
+        let locations = vec![
+            lsp::Location::new(
+                uri.clone(),
+                lsp::Range::new(lsp::Position::new(2, 8), lsp::Position::new(2, 10)),
+            ),
+            lsp::Location::new(
+                uri.clone(),
+                lsp::Range::new(lsp::Position::new(6, 9), lsp::Position::new(6, 11)),
+            ),
+        ];
+        let response = Some(testing::lsp::text_document::references::response(Some(locations)));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/references" request for the memory index of `i32.load $m`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::references::request(&uri, lsp::Position::new(4, 14), false);
+        let locations = vec![lsp::Location::new(
+            uri.clone(),
+            lsp::Range::new(lsp::Position::new(4, 13), lsp::Position::new(4, 15)),
+        )];
+        let response = Some(testing::lsp::text_document::references::response(Some(locations)));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn rename() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        #[rustfmt::skip]
+        let text = String::from(indoc::indoc! {r#"
+            (module
+              (memory $m 1)
+              (func $f
+                i32.const 0
+                i32.load $m
+                drop
+                call $f))
+        "#});
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "textDocument/rename" request for the identifier of `(memory $m 1)`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::text_document::rename::request(&uri, lsp::Position::new(1, 11), "$mem");
+        let edits = vec![
+            lsp::TextEdit::new(
+                lsp::Range::new(lsp::Position::new(1, 10), lsp::Position::new(1, 12)),
+                String::from("$mem"),
+            ),
+            lsp::TextEdit::new(
+                lsp::Range::new(lsp::Position::new(4, 13), lsp::Position::new(4, 15)),
+                String::from("$mem"),
+            ),
+        ];
+        let edit = lsp::WorkspaceEdit::new(std::collections::HashMap::from([(uri.clone(), edits)]));
+        let response = Some(testing::lsp::text_document::rename::response(Some(edit)));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "textDocument/rename" request with a name which is not an identifier; should be
+        // rejected
+        testing::assert_status!(service, Ok(()));
+        let request = testing::lsp::text_document::rename::request(&uri, lsp::Position::new(2, 9), "g");
+        let response = testing::service::send(service, &request).await?.unwrap();
+        assert_eq!(response["error"]["code"], json!(-32602));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
+    }
+
+    mod document_symbol {
+        use futures::stream::StreamExt;
+        use serde_json::{json, Value};
+
+        #[futures_test::test]
+        async fn component() -> anyhow::Result<()> {
+            let uri = lsp::Url::parse("inmemory:///test")?;
+            let language_id = "wasm.component";
+            #[rustfmt::skip]
+            let text = String::from(indoc::indoc! {r#"
+                (component $c
+                  (core module $m
+                    (func $f))
+                  (core instance $i (instantiate $m))
+                  (import "wasi:cli/stdout@0.2.0" (instance $stdout))
+                  (export "run" (func $f)))
+            "#});
+
+            let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+            // send "initialize" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::initialize::request();
+            let response = Some(testing::lsp::initialize::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "initialized" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::initialized::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+            // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+            messages.next().await.unwrap();
+
+            // send "textDocument/didOpen" notification for `uri`
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            // receive "textDocument/publishDiagnostics" notification for `uri`
+            let message = messages.next().await.unwrap();
+            let actual = serde_json::to_value(&message)?;
+            let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+            assert_eq!(actual, expected);
+
+            // send "textDocument/documentSymbol" request for `uri`
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::text_document::document_symbol::request(&uri);
+            #[rustfmt::skip]
+            let response = Some(json!({
+                "jsonrpc": "2.0",
+                "result": [
+                    {
+                        "name": "$c",
+                        "kind": lsp::SymbolKind::PACKAGE,
+                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 5, "character": 27 } },
+                        "selectionRange": { "start": { "line": 0, "character": 11 }, "end": { "line": 0, "character": 13 } },
+                        "children": [
+                            {
+                                "name": "$m",
+                                "kind": lsp::SymbolKind::MODULE,
+                                "range": { "start": { "line": 1, "character": 2 }, "end": { "line": 2, "character": 14 } },
+                                "selectionRange": { "start": { "line": 1, "character": 15 }, "end": { "line": 1, "character": 17 } },
+                                "children": [
+                                    {
+                                        "name": "$f",
+                                        "kind": lsp::SymbolKind::FUNCTION,
+                                        "range": { "start": { "line": 2, "character": 4 }, "end": { "line": 2, "character": 13 } },
+                                        "selectionRange": { "start": { "line": 2, "character": 10 }, "end": { "line": 2, "character": 12 } },
+                                        "children": [],
+                                    },
+                                ],
+                            },
+                            {
+                                "name": "$i",
+                                "kind": lsp::SymbolKind::OBJECT,
+                                "range": { "start": { "line": 3, "character": 2 }, "end": { "line": 3, "character": 37 } },
+                                "selectionRange": { "start": { "line": 3, "character": 17 }, "end": { "line": 3, "character": 19 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "wasi:cli/stdout@0.2.0",
+                                "kind": lsp::SymbolKind::INTERFACE,
+                                "range": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 53 } },
+                                "selectionRange": { "start": { "line": 4, "character": 10 }, "end": { "line": 4, "character": 33 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "run",
+                                "kind": lsp::SymbolKind::INTERFACE,
+                                "range": { "start": { "line": 5, "character": 2 }, "end": { "line": 5, "character": 26 } },
+                                "selectionRange": { "start": { "line": 5, "character": 10 }, "end": { "line": 5, "character": 15 } },
+                            },
+                        ],
+                    },
+                ],
+                "id": 1,
+            }));
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "shutdown" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::shutdown::request();
+            let response = Some(testing::lsp::shutdown::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "exit" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::exit::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            Ok(())
+        }
+
+        #[futures_test::test]
+        async fn wat() -> anyhow::Result<()> {
+            let uri = lsp::Url::parse("inmemory:///test")?;
+            let language_id = "wasm.wat";
+            #[rustfmt::skip]
+            let text = String::from(indoc::indoc! {r#"
+                (type $a (func))
+                (global $g i32 (i32.const 0))
+                (memory $m 1)
+                (data (i32.const 0))
+                (table $t 10 funcref)
+                (func $f)
+                (elem (i32.const 0))
+            "#});
+
+            let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+            // send "initialize" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::initialize::request();
+            let response = Some(testing::lsp::initialize::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "initialized" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::initialized::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+            // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+            messages.next().await.unwrap();
+
+            // send "textDocument/didOpen" notification for `uri`
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            // receive "textDocument/publishDiagnostics" notification for `uri`
+            let message = messages.next().await.unwrap();
+            let actual = serde_json::to_value(&message)?;
+            let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+            assert_eq!(actual, expected);
+
+            // send "textDocument/documentSymbol" request for `uri`
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::text_document::document_symbol::request(&uri);
+            #[rustfmt::skip]
+            let response = Some(json!({
+                "jsonrpc": "2.0",
+                "result": [
+                    {
+                        "name": "$a",
+                        "kind": lsp::SymbolKind::TYPE_PARAMETER,
+                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 16 } },
+                        "selectionRange": { "start": { "line": 0, "character": 6 }, "end": { "line": 0, "character": 8 } },
+                        "children": [],
+                    },
+                    {
+                        "name": "$g",
+                        "kind": lsp::SymbolKind::EVENT,
+                        "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 29 } },
+                        "selectionRange": { "start": { "line": 1, "character": 8 }, "end": { "line": 1, "character": 10 } },
+                        "children": [],
+                    },
+                    {
+                        "name": "$m",
+                        "kind": lsp::SymbolKind::ARRAY,
+                        "detail": "i32 1",
+                        "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 13 } },
+                        "selectionRange": { "start": { "line": 2, "character": 8 }, "end": { "line": 2, "character": 10 } },
+                        "children": [],
+                    },
+                    {
+                        "name": "<data@4:1>",
+                        "kind": lsp::SymbolKind::KEY,
+                        "range": { "start": { "line": 3, "character": 0 }, "end": { "line": 3, "character": 20 } },
+                        "selectionRange": { "start": { "line": 3, "character": 0 }, "end": { "line": 3, "character": 20 } },
+                        "children": [],
+                    },
+                    {
+                        "name": "$t",
+                        "kind": lsp::SymbolKind::INTERFACE,
+                        "detail": "i32 10 funcref",
+                        "range": { "start": { "line": 4, "character": 0 }, "end": { "line": 4, "character": 21 } },
+                        "selectionRange": { "start": { "line": 4, "character": 7 }, "end": { "line": 4, "character": 9 } },
+                        "children": [],
+                    },
+                    {
+                        "name": "$f",
+                        "kind": lsp::SymbolKind::FUNCTION,
+                        "range": { "start": { "line": 5, "character": 0 }, "end": { "line": 5, "character": 9 } },
+                        "selectionRange": { "start": { "line": 5, "character": 6 }, "end": { "line": 5, "character": 8 } },
+                        "children": [],
+                    },
+                    {
+                        "name": "<elem@7:1>",
+                        "kind": lsp::SymbolKind::FIELD,
+                        "range": { "start": { "line": 6, "character": 0 }, "end": { "line": 6, "character": 20 } },
+                        "selectionRange": { "start": { "line": 6, "character": 0 }, "end": { "line": 6, "character": 20 } },
+                    },
+                ],
+                "id": 1,
+            }));
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "shutdown" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::shutdown::request();
+            let response = Some(testing::lsp::shutdown::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "exit" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::exit::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            Ok(())
+        }
+
+        #[futures_test::test]
+        async fn wast() -> anyhow::Result<()> {
+            let uri = lsp::Url::parse("inmemory:///test")?;
+            let language_id = "wasm.wast";
+            #[rustfmt::skip]
+            let text = String::from(indoc::indoc! {r#"
+                (module $m
+                  (type $a (func))
+                  (global $g i32 (i32.const 0))
+                  (memory $m 1)
+                  (data (i32.const 0))
+                  (table $t 10 funcref)
+                  (func $f)
+                  (elem (i32.const 0)))
+                (assert_return (invoke "empty"))
+            "#});
+
+            let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+            // send "initialize" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::initialize::request();
+            let response = Some(testing::lsp::initialize::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "initialized" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::initialized::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+            // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+            messages.next().await.unwrap();
+
+            // send "textDocument/didOpen" notification for `uri`
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            // receive "textDocument/publishDiagnostics" notification for `uri`
+            let message = messages.next().await.unwrap();
+            let actual = serde_json::to_value(&message)?;
+            let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+            assert_eq!(actual, expected);
+
+            // send "textDocument/documentSymbol" request for `uri`
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::text_document::document_symbol::request(&uri);
+            #[rustfmt::skip]
+            let response = Some(json!({
+                "jsonrpc": "2.0",
+                "result": [
+                    {
+                        "name": "$m",
+                        "kind": lsp::SymbolKind::MODULE,
+                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 7, "character": 23 } },
+                        "selectionRange": { "start": { "line": 0, "character": 8 }, "end": { "line": 0, "character": 10 } },
+                        "children": [
+                            {
+                                "name": "$a",
+                                "kind": lsp::SymbolKind::TYPE_PARAMETER,
+                                "range": { "start": { "line": 1, "character": 2 }, "end": { "line": 1, "character": 18 } },
+                                "selectionRange": { "start": { "line": 1, "character": 8 }, "end": { "line": 1, "character": 10 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "$g",
+                                "kind": lsp::SymbolKind::EVENT,
+                                "range": { "start": { "line": 2, "character": 2 }, "end": { "line": 2, "character": 31 } },
+                                "selectionRange": { "start": { "line": 2, "character": 10 }, "end": { "line": 2, "character": 12 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "$m",
+                                "kind": lsp::SymbolKind::ARRAY,
+                                "detail": "i32 1",
+                                "range": { "start": { "line": 3, "character": 2 }, "end": { "line": 3, "character": 15 } },
+                                "selectionRange": { "start": { "line": 3, "character": 10 }, "end": { "line": 3, "character": 12 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "<data@5:3>",
+                                "kind": lsp::SymbolKind::KEY,
+                                "range": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 22 } },
+                                "selectionRange": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 22 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "$t",
+                                "kind": lsp::SymbolKind::INTERFACE,
+                                "detail": "i32 10 funcref",
+                                "range": { "start": { "line": 5, "character": 2 }, "end": { "line": 5, "character": 23 } },
+                                "selectionRange": { "start": { "line": 5, "character": 9 }, "end": { "line": 5, "character": 11 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "$f",
+                                "kind": lsp::SymbolKind::FUNCTION,
+                                "range": { "start": { "line": 6, "character": 2 }, "end": { "line": 6, "character": 11 } },
+                                "selectionRange": { "start": { "line": 6, "character": 8 }, "end": { "line": 6, "character": 10 } },
+                                "children": [],
+                            },
+                            {
+                                "name": "<elem@8:3>",
+                                "kind": lsp::SymbolKind::FIELD,
+                                "range": { "start": { "line": 7, "character": 2 }, "end": { "line": 7, "character": 22 } },
+                                "selectionRange": { "start": { "line": 7, "character": 2 }, "end": { "line": 7, "character": 22 } },
+                            },
+                        ],
+                    },
+                ],
+                "id": 1,
+            }));
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "shutdown" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::shutdown::request();
+            let response = Some(testing::lsp::shutdown::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "exit" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::exit::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            Ok(())
+        }
+
+        #[cfg(feature = "goldenfiles")]
+        #[futures_test::test]
+        async fn wit() -> anyhow::Result<()> {
+            let uri = lsp::Url::parse("inmemory:///test")?;
+            let language_id = "wasm.wit";
+            #[rustfmt::skip]
+            let text = String::from(indoc::indoc! {r#"
+                package example:app;
+
+                interface types {
+                  record point {
+                    x: u32,
+                  }
+                  area: func(p: point) -> u32;
+                }
+
+                world app {
+                  export run: func();
+                }
+            "#});
+
+            let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+            // send "initialize" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::initialize::request();
+            let response = Some(testing::lsp::initialize::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "initialized" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::initialized::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+            // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+            messages.next().await.unwrap();
+
+            // send "textDocument/didOpen" notification for `uri`
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            // receive "textDocument/publishDiagnostics" notification for `uri`
+            let message = messages.next().await.unwrap();
+            let actual = serde_json::to_value(&message)?;
+            let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+            assert_eq!(actual, expected);
+
+            // send "textDocument/documentSymbol" request for `uri`
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::text_document::document_symbol::request(&uri);
+            #[rustfmt::skip]
+            let response = Some(json!({
+                "jsonrpc": "2.0",
+                "result": [
+                    {
+                        "name": "example:app",
+                        "kind": lsp::SymbolKind::PACKAGE,
+                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 12, "character": 0 } },
+                        "selectionRange": { "start": { "line": 0, "character": 8 }, "end": { "line": 0, "character": 19 } },
+                        "children": [
+                            {
+                                "name": "types",
+                                "kind": lsp::SymbolKind::INTERFACE,
+                                "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 7, "character": 1 } },
+                                "selectionRange": { "start": { "line": 2, "character": 10 }, "end": { "line": 2, "character": 15 } },
+                                "children": [
+                                    {
+                                        "name": "point",
+                                        "kind": lsp::SymbolKind::STRUCT,
+                                        "range": { "start": { "line": 3, "character": 2 }, "end": { "line": 5, "character": 3 } },
+                                        "selectionRange": { "start": { "line": 3, "character": 9 }, "end": { "line": 3, "character": 14 } },
+                                        "children": [
+                                            {
+                                                "name": "x",
+                                                "kind": lsp::SymbolKind::FIELD,
+                                                "range": { "start": { "line": 4, "character": 4 }, "end": { "line": 4, "character": 10 } },
+                                                "selectionRange": { "start": { "line": 4, "character": 4 }, "end": { "line": 4, "character": 5 } },
+                                                "children": [],
+                                            },
+                                        ],
+                                    },
+                                    {
+                                        "name": "area",
+                                        "detail": "func(p: point) -> u32",
+                                        "kind": lsp::SymbolKind::FUNCTION,
+                                        "range": { "start": { "line": 6, "character": 2 }, "end": { "line": 6, "character": 30 } },
+                                        "selectionRange": { "start": { "line": 6, "character": 2 }, "end": { "line": 6, "character": 6 } },
+                                        "children": [],
+                                    },
+                                ],
+                            },
+                            {
+                                "name": "app",
+                                "kind": lsp::SymbolKind::MODULE,
+                                "range": { "start": { "line": 9, "character": 0 }, "end": { "line": 11, "character": 1 } },
+                                "selectionRange": { "start": { "line": 9, "character": 6 }, "end": { "line": 9, "character": 9 } },
+                                "children": [
+                                    {
+                                        "name": "run",
+                                        "detail": "func()",
+                                        "kind": lsp::SymbolKind::FUNCTION,
+                                        "range": { "start": { "line": 10, "character": 2 }, "end": { "line": 10, "character": 21 } },
+                                        "selectionRange": { "start": { "line": 10, "character": 9 }, "end": { "line": 10, "character": 12 } },
+                                    },
+                                ],
+                            },
+                        ],
+                    },
+                ],
+                "id": 1,
+            }));
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "shutdown" request
+            testing::assert_status!(service, Ok(()));
+            let request = &testing::lsp::shutdown::request();
+            let response = Some(testing::lsp::shutdown::response());
+            testing::assert_exchange!(service, request, Ok(response));
+
+            // send "exit" notification
+            testing::assert_status!(service, Ok(()));
+            let notification = &testing::lsp::exit::notification();
+            let status = None::<Value>;
+            testing::assert_exchange!(service, notification, Ok(status));
+
+            Ok(())
+        }
+
+        mod corpus {
+            use wasm_language_server_macros::corpus_tests;
+
+            fn handler(corpus: &str, path: &str) {
+                use futures::stream::StreamExt;
+                use serde_json::Value;
+                use std::io::Write;
+                use wasm_lsp_syntax::core::language::Language;
+
+                async fn handler(corpus: &str, path: &str) -> anyhow::Result<()> {
+                    let path = std::path::Path::new(path);
+
+                    let mut mint = {
+                        let file_dir = format!("tests/goldenfiles/lsp/textDocument/documentSymbol/corpus/{}", corpus);
+                        goldenfile::Mint::new(file_dir)
+                    };
+                    let mut goldenfile = {
+                        let path = path.with_extension("json");
+                        let file_name = path.file_name().unwrap().to_str().unwrap();
+                        mint.new_goldenfile(file_name)?
+                    };
+
+                    let uri = lsp::Url::from_file_path(path).unwrap();
+                    let text = std::fs::read_to_string(path)?;
+                    let language = Language::try_from(path)?;
+                    let language_id = language.id();
+
+                    let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+                    // send "initialize" request
+                    testing::assert_status!(service, Ok(()));
+                    let request = &testing::lsp::initialize::request();
+                    let response = Some(testing::lsp::initialize::response());
+                    testing::assert_exchange!(service, request, Ok(response));
+
+                    // send "initialized" notification
+                    testing::assert_status!(service, Ok(()));
+                    let notification = &testing::lsp::initialized::notification();
+                    let status = None::<Value>;
+                    testing::assert_exchange!(service, notification, Ok(status));
+                    // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+                    messages.next().await.unwrap();
+
+                    // send "textDocument/didOpen" notification for `uri`
+                    testing::assert_status!(service, Ok(()));
+                    let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+                    let status = None::<Value>;
+                    testing::assert_exchange!(service, notification, Ok(status));
+
+                    // receive "textDocument/publishDiagnostics" notification for `uri`
+                    let message = messages.next().await.unwrap();
+                    let actual = serde_json::to_value(&message)?;
+                    let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+                    assert_eq!(actual, expected);
+
+                    // send "textDocument/documentSymbol" request for `uri`
+                    testing::assert_status!(service, Ok(()));
+                    let request = &testing::lsp::text_document::document_symbol::request(&uri);
+                    let response = wasm_language_server_testing::service::send(service, request).await?;
+                    // convert the response (wrapped in an outer `Option`) to a JSON value
+                    let value = serde_json::to_value(response)?;
+                    // write the value to the goldenfile
+                    write!(goldenfile, "{}", value)?;
+
+                    // send "shutdown" request
+                    testing::assert_status!(service, Ok(()));
+                    let request = &testing::lsp::shutdown::request();
+                    let response = Some(testing::lsp::shutdown::response());
+                    testing::assert_exchange!(service, request, Ok(response));
+
+                    // send "exit" notification
+                    testing::assert_status!(service, Ok(()));
+                    let notification = &testing::lsp::exit::notification();
+                    let status = None::<Value>;
+                    testing::assert_exchange!(service, notification, Ok(status));
+
+                    Ok(())
+                }
+                futures::executor::block_on(handler(corpus, path)).unwrap();
+            }
+
+            corpus_tests! {
+                corpus: annotations,
+                include: "vendor/corpus/vendor/WebAssembly/annotations/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: bulk_memory_operations,
+                include: "vendor/corpus/vendor/WebAssembly/bulk-memory-operations/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: exception_handling,
+                include: "vendor/corpus/vendor/WebAssembly/exception-handling/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: function_references,
+                include: "vendor/corpus/vendor/WebAssembly/function-references/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: interface_types,
+                include: "vendor/corpus/vendor/bytecodealliance/wasm-interface-types/tests/*.wat",
+                exclude: [
+                    // NOTE: fails because language id should be wasm.wast not wasm.wat
+                    "bad-schema.wat",
+                    // NOTE: fails because language id should be wasm.wast not wasm.wat
+                    "bad-section.wat",
+                    // NOTE: true positive; fails due to syntax error
+                    "not-interface.wat",
+                    // NOTE: fails because language id should be wasm.wast not wasm.wat
+                    "two-sections.wat",
+                ],
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: multi_memory,
+                include: "vendor/corpus/vendor/WebAssembly/multi-memory/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: reference_types,
+                include: "vendor/corpus/vendor/WebAssembly/reference-types/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: simd,
+                include: "vendor/corpus/vendor/WebAssembly/simd/test/core/**/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: spec,
+                include: "vendor/corpus/vendor/WebAssembly/spec/test/core/address.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+
+            corpus_tests! {
+                corpus: threads,
+                include: "vendor/corpus/vendor/WebAssembly/threads/test/core/*.wast",
+                handler: crate::lsp::text_document::document_symbol::corpus::handler,
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    mod did_open {
+        use wasm_lsp_macros::corpus_tests;
+
+        fn handler(_corpus: &str, path: &str) {
+            use futures::stream::StreamExt;
+            use serde_json::Value;
+            use wasm_lsp_server::core::Language;
+
+            async fn handler(path: &str) -> anyhow::Result<()> {
+                let uri = lsp::Url::from_file_path(path).unwrap();
+                let text = std::fs::read_to_string(path).unwrap();
+                let language = Language::try_from(std::path::Path::new(path))?;
+                let language_id = language.id();
+
+                let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+                // send "initialize" request
+                testing::assert_status!(service, Ok(()));
+                let request = &testing::lsp::initialize::request();
+                let response = Some(testing::lsp::initialize::response());
+                testing::assert_exchange!(service, request, Ok(response));
+
+                // send "initialized" notification
+                testing::assert_status!(service, Ok(()));
+                let notification = &testing::lsp::initialized::notification();
+                let status = None::<Value>;
+                testing::assert_exchange!(service, notification, Ok(status));
+                // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+                messages.next().await.unwrap();
+
+                // send "textDocument/didOpen" notification for `uri`
+                testing::assert_status!(service, Ok(()));
+                let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+                let status = None::<Value>;
+                testing::assert_exchange!(service, notification, Ok(status));
 
-            // send "initialized" notification
-            testing::assert_status!(service, Ok(()));
-            let notification = &testing::lsp::initialized::notification();
-            let status = None::<Value>;
-            testing::assert_exchange!(service, notification, Ok(status));
-            // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
-            messages.next().await.unwrap();
+                // receive "textDocument/publishDiagnostics" notification for `uri`
+                let message = messages.next().await.unwrap();
+                let actual = serde_json::to_value(&message)?;
+                let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+                assert_eq!(actual, expected);
 
-            // send "textDocument/didOpen" notification for `uri`
-            testing::assert_status!(service, Ok(()));
-            let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
-            let status = None::<Value>;
-            testing::assert_exchange!(service, notification, Ok(status));
+                // send "shutdown" request
+                testing::assert_status!(service, Ok(()));
+                let request = &testing::lsp::shutdown::request();
+                let response = Some(testing::lsp::shutdown::response());
+                testing::assert_exchange!(service, request, Ok(response));
 
-            // receive "textDocument/publishDiagnostics" notification for `uri`
-            let message = messages.next().await.unwrap();
-            let actual = serde_json::to_value(&message)?;
-            let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[]);
-            assert_eq!(actual, expected);
+                // send "exit" notification
+                testing::assert_status!(service, Ok(()));
+                let notification = &testing::lsp::exit::notification();
+                let status = None::<Value>;
+                testing::assert_exchange!(service, notification, Ok(status));
 
-            // send "textDocument/documentSymbol" request for `uri`
-            testing::assert_status!(service, Ok(()));
-            let request = &testing::lsp::text_document::document_symbol::request(&uri);
-            #[rustfmt::skip]
-            let response = Some(json!({
-                "jsonrpc": "2.0",
-                "result": [
-                    {
-                        "name": "$m",
-                        "kind": lsp::SymbolKind::MODULE,
-                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 7, "character": 23 } },
-                        "selectionRange": { "start": { "line": 0, "character": 8 }, "end": { "line": 0, "character": 10 } },
-                        "children": [
-                            {
-                                "name": "$a",
-                                "kind": lsp::SymbolKind::TYPE_PARAMETER,
-                                "range": { "start": { "line": 1, "character": 2 }, "end": { "line": 1, "character": 18 } },
-                                "selectionRange": { "start": { "line": 1, "character": 8 }, "end": { "line": 1, "character": 10 } },
-                                "children": [],
-                            },
-                            {
-                                "name": "$g",
-                                "kind": lsp::SymbolKind::EVENT,
-                                "range": { "start": { "line": 2, "character": 2 }, "end": { "line": 2, "character": 31 } },
-                                "selectionRange": { "start": { "line": 2, "character": 10 }, "end": { "line": 2, "character": 12 } },
-                                "children": [],
-                            },
-                            {
-                                "name": "$m",
-                                "kind": lsp::SymbolKind::ARRAY,
-                                "range": { "start": { "line": 3, "character": 2 }, "end": { "line": 3, "character": 15 } },
-                                "selectionRange": { "start": { "line": 3, "character": 10 }, "end": { "line": 3, "character": 12 } },
-                                "children": [],
-                            },
-                            {
-                                "name": "<data@5:3>",
-                                "kind": lsp::SymbolKind::KEY,
-                                "range": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 22 } },
-                                "selectionRange": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 22 } },
-                                "children": [],
-                            },
-                            {
-                                "name": "$t",
-                                "kind": lsp::SymbolKind::INTERFACE,
-                                "range": { "start": { "line": 5, "character": 2 }, "end": { "line": 5, "character": 23 } },
-                                "selectionRange": { "start": { "line": 5, "character": 9 }, "end": { "line": 5, "character": 11 } },
-                                "children": [],
-                            },
-                            {
-                                "name": "$f",
-                                "kind": lsp::SymbolKind::FUNCTION,
-                                "range": { "start": { "line": 6, "character": 2 }, "end": { "line": 6, "character": 11 } },
-                                "selectionRange": { "start": { "line": 6, "character": 8 }, "end": { "line": 6, "character": 10 } },
-                                "children": [],
-                            },
-                            {
-                                "name": "<elem@8:3>",
-                                "kind": lsp::SymbolKind::FIELD,
-                                "range": { "start": { "line": 7, "character": 2 }, "end": { "line": 7, "character": 22 } },
-                                "selectionRange": { "start": { "line": 7, "character": 2 }, "end": { "line": 7, "character": 22 } },
-                            },
-                        ],
-                    },
-                ],
-                "id": 1,
-            }));
-            testing::assert_exchange!(service, request, Ok(response));
+                Ok(())
+            }
+            futures::executor::block_on(handler(path)).unwrap();
+        }
+
+        corpus_tests! {
+            corpus: annotations,
+            include: "vendor/corpus/vendor/WebAssembly/annotations/test/core/*.wast",
+            exclude: [
+                "annotations.wast",
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: bulk_memory_operations,
+            include: "vendor/corpus/vendor/WebAssembly/bulk-memory-operations/test/core/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: exception_handling,
+            include: "vendor/corpus/vendor/WebAssembly/exception-handling/test/core/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: function_references,
+            include: "vendor/corpus/vendor/WebAssembly/function-references/test/core/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "let.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: interface_types,
+            include: "vendor/corpus/vendor/bytecodealliance/wasm-interface-types/tests/*.wat",
+            exclude: [
+                // NOTE: fails because language id should be wasm.wast not wasm.wat
+                "bad-schema.wat",
+                // NOTE: fails because language id should be wasm.wast not wasm.wat
+                "bad-section.wat",
+                // NOTE: true positive; fails due to syntax error
+                "not-interface.wat",
+                // NOTE: fails because language id should be wasm.wast not wasm.wat
+                "two-sections.wat",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: multi_memory,
+            include: "vendor/corpus/vendor/WebAssembly/multi-memory/test/core/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+                "memory_grow.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: reference_types,
+            include: "vendor/corpus/vendor/WebAssembly/reference-types/test/core/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: simd,
+            include: "vendor/corpus/vendor/WebAssembly/simd/test/core/**/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: spec,
+            include: "vendor/corpus/vendor/WebAssembly/spec/test/core/*.wast",
+            exclude: [
+                "br.wast",
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+
+        corpus_tests! {
+            corpus: threads,
+            include: "vendor/corpus/vendor/WebAssembly/threads/test/core/*.wast",
+            exclude: [
+                "comments.wast",
+                "local_set.wast",
+            ],
+            handler: crate::lsp::text_document::did_open::handler,
+        }
+    }
+}
+
+mod wasm {
+    use futures::stream::StreamExt;
+    use serde_json::{json, Value};
+
+    #[futures_test::test]
+    async fn assemble() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module)");
+
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "wasm/assemble" request for `uri`; the result is the empty module
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::wasm::assemble::request(&uri);
+        let response = Some(testing::lsp::wasm::assemble::response("AGFzbQEAAAA="));
+        testing::assert_exchange!(service, request, Ok(response));
 
-            // send "shutdown" request
-            testing::assert_status!(service, Ok(()));
-            let request = &testing::lsp::shutdown::request();
-            let response = Some(testing::lsp::shutdown::response());
-            testing::assert_exchange!(service, request, Ok(response));
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-            // send "exit" notification
-            testing::assert_status!(service, Ok(()));
-            let notification = &testing::lsp::exit::notification();
-            let status = None::<Value>;
-            testing::assert_exchange!(service, notification, Ok(status));
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-            Ok(())
-        }
+        Ok(())
+    }
 
-        #[cfg(feature = "goldenfiles")]
-        mod corpus {
-            use wasm_language_server_macros::corpus_tests;
+    #[futures_test::test]
+    async fn assemble_failure() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module (func call $g))");
 
-            fn handler(corpus: &str, path: &str) {
-                use futures::stream::StreamExt;
-                use serde_json::Value;
-                use std::io::Write;
-                use wasm_lsp_syntax::core::language::Language;
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
-                async fn handler(corpus: &str, path: &str) -> anyhow::Result<()> {
-                    let path = std::path::Path::new(path);
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-                    let mut mint = {
-                        let file_dir = format!("tests/goldenfiles/lsp/textDocument/documentSymbol/corpus/{}", corpus);
-                        goldenfile::Mint::new(file_dir)
-                    };
-                    let mut goldenfile = {
-                        let path = path.with_extension("json");
-                        let file_name = path.file_name().unwrap().to_str().unwrap();
-                        mint.new_goldenfile(file_name)?
-                    };
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
 
-                    let uri = lsp::Url::from_file_path(path).unwrap();
-                    let text = std::fs::read_to_string(path)?;
-                    let language = Language::try_from(path)?;
-                    let language_id = language.id();
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-                    let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+        // receive "textDocument/publishDiagnostics" notifications for `uri` (syntax, then semantics)
+        messages.next().await.unwrap();
+        messages.next().await.unwrap();
 
-                    // send "initialize" request
-                    testing::assert_status!(service, Ok(()));
-                    let request = &testing::lsp::initialize::request();
-                    let response = Some(testing::lsp::initialize::response());
-                    testing::assert_exchange!(service, request, Ok(response));
+        // send "wasm/assemble" request for `uri`; the result is the failure to encode the module
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::wasm::assemble::request(&uri);
+        let response = testing::service::send(service, request).await?.unwrap();
+        assert_eq!(response["result"]["diagnostics"].as_array().map(Vec::len), Some(1));
 
-                    // send "initialized" notification
-                    testing::assert_status!(service, Ok(()));
-                    let notification = &testing::lsp::initialized::notification();
-                    let status = None::<Value>;
-                    testing::assert_exchange!(service, notification, Ok(status));
-                    // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
-                    messages.next().await.unwrap();
+        // receive "textDocument/publishDiagnostics" notifications for `uri`; the semantic diagnostics
+        // are published along with the failure
+        messages.next().await.unwrap();
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let diagnostics = actual["params"]["diagnostics"].as_array().cloned().unwrap_or_default();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic["message"] == json!("unknown func `$g`")));
 
-                    // send "textDocument/didOpen" notification for `uri`
-                    testing::assert_status!(service, Ok(()));
-                    let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
-                    let status = None::<Value>;
-                    testing::assert_exchange!(service, notification, Ok(status));
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-                    // receive "textDocument/publishDiagnostics" notification for `uri`
-                    let message = messages.next().await.unwrap();
-                    let actual = serde_json::to_value(&message)?;
-                    let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[]);
-                    assert_eq!(actual, expected);
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-                    // send "textDocument/documentSymbol" request for `uri`
-                    testing::assert_status!(service, Ok(()));
-                    let request = &testing::lsp::text_document::document_symbol::request(&uri);
-                    let response = wasm_language_server_testing::service::send(service, request).await?;
-                    // convert the response (wrapped in an outer `Option`) to a JSON value
-                    let value = serde_json::to_value(response)?;
-                    // write the value to the goldenfile
-                    write!(goldenfile, "{}", value)?;
+        Ok(())
+    }
 
-                    // send "shutdown" request
-                    testing::assert_status!(service, Ok(()));
-                    let request = &testing::lsp::shutdown::request();
-                    let response = Some(testing::lsp::shutdown::response());
-                    testing::assert_exchange!(service, request, Ok(response));
+    #[futures_test::test]
+    async fn disassemble() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("file:///test.wasm")?;
+        // the empty module: "\0asm" followed by version 1
+        let contents = "AGFzbQEAAAA=";
 
-                    // send "exit" notification
-                    testing::assert_status!(service, Ok(()));
-                    let notification = &testing::lsp::exit::notification();
-                    let status = None::<Value>;
-                    testing::assert_exchange!(service, notification, Ok(status));
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
-                    Ok(())
-                }
-                futures::executor::block_on(handler(corpus, path)).unwrap();
-            }
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-            corpus_tests! {
-                corpus: annotations,
-                include: "vendor/corpus/vendor/WebAssembly/annotations/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
 
-            corpus_tests! {
-                corpus: bulk_memory_operations,
-                include: "vendor/corpus/vendor/WebAssembly/bulk-memory-operations/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        // send "wasm/disassemble" request for `uri`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::wasm::disassemble::request(&uri, contents);
+        let response = Some(testing::lsp::wasm::disassemble::response(
+            &lsp::Url::parse("wasm-disassembly:///test.wasm")?,
+            &wasm_lsp_server::core::disassembly::disassemble(b"\0asm\x01\0\0\0")?,
+        ));
+        testing::assert_exchange!(service, request, Ok(response));
 
-            corpus_tests! {
-                corpus: exception_handling,
-                include: "vendor/corpus/vendor/WebAssembly/exception-handling/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-            corpus_tests! {
-                corpus: function_references,
-                include: "vendor/corpus/vendor/WebAssembly/function-references/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-            corpus_tests! {
-                corpus: interface_types,
-                include: "vendor/corpus/vendor/bytecodealliance/wasm-interface-types/tests/*.wat",
-                exclude: [
-                    // NOTE: fails because language id should be wasm.wast not wasm.wat
-                    "bad-schema.wat",
-                    // NOTE: fails because language id should be wasm.wast not wasm.wat
-                    "bad-section.wat",
-                    // NOTE: true positive; fails due to syntax error
-                    "not-interface.wat",
-                    // NOTE: fails because language id should be wasm.wast not wasm.wat
-                    "two-sections.wat",
-                ],
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        Ok(())
+    }
 
-            corpus_tests! {
-                corpus: multi_memory,
-                include: "vendor/corpus/vendor/WebAssembly/multi-memory/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+    #[futures_test::test]
+    async fn size_report() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module\n  (func $f\n    i32.const 42\n    drop))");
 
-            corpus_tests! {
-                corpus: reference_types,
-                include: "vendor/corpus/vendor/WebAssembly/reference-types/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
-            corpus_tests! {
-                corpus: simd,
-                include: "vendor/corpus/vendor/WebAssembly/simd/test/core/**/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-            corpus_tests! {
-                corpus: spec,
-                include: "vendor/corpus/vendor/WebAssembly/spec/test/core/address.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
+
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
+
+        // send "wasm/sizeReport" request for `uri`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::wasm::size_report::request(&uri);
+        #[rustfmt::skip]
+        let response = Some(testing::lsp::wasm::size_report::response(json!({
+            "size": 40,
+            "sections": [
+                { "kind": "type", "size": 6 },
+                { "kind": "func", "size": 4 },
+                { "kind": "code", "size": 9 },
+                { "kind": "names", "name": "name", "size": 13 },
+            ],
+            "functions": [
+                {
+                    "index": 0,
+                    "name": "$f",
+                    "size": 5,
+                    "instructions": 2,
+                    "range": { "start": { "line": 1, "character": 2 }, "end": { "line": 3, "character": 9 } },
+                },
+            ],
+        })));
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-            corpus_tests! {
-                corpus: threads,
-                include: "vendor/corpus/vendor/WebAssembly/threads/test/core/*.wast",
-                handler: crate::lsp::text_document::document_symbol::corpus::handler,
-            }
-        }
+        Ok(())
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    mod did_open {
-        use wasm_lsp_macros::corpus_tests;
+    #[futures_test::test]
+    async fn source_location() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module\n  (func\n    nop))");
 
-        fn handler(_corpus: &str, path: &str) {
-            use futures::stream::StreamExt;
-            use serde_json::Value;
-            use wasm_lsp_server::core::Language;
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
-            async fn handler(path: &str) -> anyhow::Result<()> {
-                let uri = lsp::Url::from_file_path(path).unwrap();
-                let text = std::fs::read_to_string(path).unwrap();
-                let language = Language::try_from(std::path::Path::new(path))?;
-                let language_id = language.id();
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-                let (ref mut service, ref mut messages) = testing::service::spawn().await?;
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
 
-                // send "initialize" request
-                testing::assert_status!(service, Ok(()));
-                let request = &testing::lsp::initialize::request();
-                let response = Some(testing::lsp::initialize::response());
-                testing::assert_exchange!(service, request, Ok(response));
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-                // send "initialized" notification
-                testing::assert_status!(service, Ok(()));
-                let notification = &testing::lsp::initialized::notification();
-                let status = None::<Value>;
-                testing::assert_exchange!(service, notification, Ok(status));
-                // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
-                messages.next().await.unwrap();
+        // receive "textDocument/publishDiagnostics" notification for `uri`
+        messages.next().await.unwrap();
 
-                // send "textDocument/didOpen" notification for `uri`
-                testing::assert_status!(service, Ok(()));
-                let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
-                let status = None::<Value>;
-                testing::assert_exchange!(service, notification, Ok(status));
+        // send "wasm/sourceLocation" request for `uri`; the body is `0x00` (no locals), `nop`, `end`
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::wasm::source_location::request(&uri, 0, 1);
+        let range = lsp::Range::new(lsp::Position::new(2, 4), lsp::Position::new(2, 7));
+        let response = Some(testing::lsp::wasm::source_location::response(Some(lsp::Location::new(
+            uri.clone(),
+            range,
+        ))));
+        testing::assert_exchange!(service, request, Ok(response));
 
-                // receive "textDocument/publishDiagnostics" notification for `uri`
-                let message = messages.next().await.unwrap();
-                let actual = serde_json::to_value(&message)?;
-                let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[]);
-                assert_eq!(actual, expected);
+        // send "wasm/sourceLocation" request for `uri` with an offset past the end of the body
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::wasm::source_location::request(&uri, 0, 3);
+        let response = Some(testing::lsp::wasm::source_location::response(None));
+        testing::assert_exchange!(service, request, Ok(response));
 
-                // send "shutdown" request
-                testing::assert_status!(service, Ok(()));
-                let request = &testing::lsp::shutdown::request();
-                let response = Some(testing::lsp::shutdown::response());
-                testing::assert_exchange!(service, request, Ok(response));
+        // send "wasm/sourceLocation" request for `uri` with a function and a module offset, like in
+        // `wasm-function[0]:0x17`; the body starts at `0x16` (after the header, the type and function
+        // sections, and the prefixes of the code section and of the body)
+        testing::assert_status!(service, Ok(()));
+        let mut request = testing::lsp::wasm::source_location::request(&uri, 0, 0);
+        request["params"].as_object_mut().unwrap().remove("offset");
+        request["params"]["moduleOffset"] = json!(0x17);
+        let response = Some(testing::lsp::wasm::source_location::response(Some(lsp::Location::new(
+            uri.clone(),
+            range,
+        ))));
+        testing::assert_exchange!(service, &request, Ok(response));
+
+        // send "wasm/sourceLocation" request for `uri` with a module offset outside of the function;
+        // should be rejected
+        testing::assert_status!(service, Ok(()));
+        let mut request = testing::lsp::wasm::source_location::request(&uri, 0, 0);
+        request["params"].as_object_mut().unwrap().remove("offset");
+        request["params"]["moduleOffset"] = json!(8);
+        let response = testing::service::send(service, &request).await?.unwrap();
+        assert_eq!(response["error"]["code"], json!(-32602));
+
+        // send "wasm/sourceLocation" request for `uri` with both a body and a module offset; should be
+        // rejected
+        testing::assert_status!(service, Ok(()));
+        let mut request = testing::lsp::wasm::source_location::request(&uri, 0, 1);
+        request["params"]["moduleOffset"] = json!(0x17);
+        let response = testing::service::send(service, &request).await?.unwrap();
+        assert_eq!(response["error"]["code"], json!(-32602));
 
-                // send "exit" notification
-                testing::assert_status!(service, Ok(()));
-                let notification = &testing::lsp::exit::notification();
-                let status = None::<Value>;
-                testing::assert_exchange!(service, notification, Ok(status));
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-                Ok(())
-            }
-            futures::executor::block_on(handler(path)).unwrap();
-        }
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-        corpus_tests! {
-            corpus: annotations,
-            include: "vendor/corpus/vendor/WebAssembly/annotations/test/core/*.wast",
-            exclude: [
-                "annotations.wast",
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        Ok(())
+    }
+}
 
-        corpus_tests! {
-            corpus: bulk_memory_operations,
-            include: "vendor/corpus/vendor/WebAssembly/bulk-memory-operations/test/core/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+mod workspace {
+    use futures::stream::StreamExt;
+    use serde_json::{json, Value};
 
-        corpus_tests! {
-            corpus: exception_handling,
-            include: "vendor/corpus/vendor/WebAssembly/exception-handling/test/core/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+    #[futures_test::test]
+    async fn did_change_configuration() -> anyhow::Result<()> {
+        let uri = lsp::Url::parse("inmemory:///test")?;
+        let language_id = "wasm.wat";
+        let text = String::from("(module (func (call $f)))");
 
-        corpus_tests! {
-            corpus: function_references,
-            include: "vendor/corpus/vendor/WebAssembly/function-references/test/core/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "let.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        let (ref mut service, ref mut messages) = testing::service::spawn().await?;
 
-        corpus_tests! {
-            corpus: interface_types,
-            include: "vendor/corpus/vendor/bytecodealliance/wasm-interface-types/tests/*.wat",
-            exclude: [
-                // NOTE: fails because language id should be wasm.wast not wasm.wat
-                "bad-schema.wat",
-                // NOTE: fails because language id should be wasm.wast not wasm.wat
-                "bad-section.wat",
-                // NOTE: true positive; fails due to syntax error
-                "not-interface.wat",
-                // NOTE: fails because language id should be wasm.wast not wasm.wat
-                "two-sections.wat",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        // send "initialize" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::initialize::request();
+        let response = Some(testing::lsp::initialize::response());
+        testing::assert_exchange!(service, request, Ok(response));
 
-        corpus_tests! {
-            corpus: multi_memory,
-            include: "vendor/corpus/vendor/WebAssembly/multi-memory/test/core/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-                "memory_grow.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        // send "initialized" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::initialized::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+        // ignore the "window/logMessage" notification: "WebAssembly language server initialized!"
+        messages.next().await.unwrap();
 
-        corpus_tests! {
-            corpus: reference_types,
-            include: "vendor/corpus/vendor/WebAssembly/reference-types/test/core/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        // send "textDocument/didOpen" notification for `uri`
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::text_document::did_open::notification(&uri, language_id, 1, text);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
 
-        corpus_tests! {
-            corpus: simd,
-            include: "vendor/corpus/vendor/WebAssembly/simd/test/core/**/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        // receive "textDocument/publishDiagnostics" notification for `uri` (syntax)
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+        assert_eq!(actual, expected);
 
-        corpus_tests! {
-            corpus: spec,
-            include: "vendor/corpus/vendor/WebAssembly/spec/test/core/*.wast",
-            exclude: [
-                "br.wast",
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        // receive "textDocument/publishDiagnostics" notification for `uri` (semantics)
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let diagnostic = lsp::Diagnostic {
+            range: lsp::Range::new(lsp::Position::new(0, 20), lsp::Position::new(0, 22)),
+            severity: Some(lsp::DiagnosticSeverity::ERROR),
+            code: Some(lsp::NumberOrString::String(String::from("unknown-index"))),
+            message: String::from("unknown func `$f`"),
+            ..Default::default()
+        };
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[diagnostic], Some(1));
+        assert_eq!(actual, expected);
 
-        corpus_tests! {
-            corpus: threads,
-            include: "vendor/corpus/vendor/WebAssembly/threads/test/core/*.wast",
-            exclude: [
-                "comments.wast",
-                "local_set.wast",
-            ],
-            handler: crate::lsp::text_document::did_open::handler,
-        }
+        // send "workspace/didChangeConfiguration" notification turning off the rule
+        testing::assert_status!(service, Ok(()));
+        let settings = json!({
+            "wasm-lsp": {
+                "diagnostics": {
+                    "severities": {
+                        "unknown-index": "off",
+                    },
+                },
+            },
+        });
+        let notification = &testing::lsp::workspace::did_change_configuration::notification(settings);
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        // receive "textDocument/publishDiagnostics" notification for `uri` (without the diagnostic)
+        let message = messages.next().await.unwrap();
+        let actual = serde_json::to_value(&message)?;
+        let expected = testing::lsp::text_document::publish_diagnostics::notification(&uri, &[], Some(1));
+        assert_eq!(actual, expected);
+
+        // send "shutdown" request
+        testing::assert_status!(service, Ok(()));
+        let request = &testing::lsp::shutdown::request();
+        let response = Some(testing::lsp::shutdown::response());
+        testing::assert_exchange!(service, request, Ok(response));
+
+        // send "exit" notification
+        testing::assert_status!(service, Ok(()));
+        let notification = &testing::lsp::exit::notification();
+        let status = None::<Value>;
+        testing::assert_exchange!(service, notification, Ok(status));
+
+        Ok(())
     }
 }
@@ -1,13 +1,23 @@
 //! Functionality related to [`tree_sitter::Language`].
 
+/// Functions for working with the component model grammar.
+pub mod component;
+
 /// Functions for working with the `.wast` grammar.
 pub mod wast;
 
 /// Functions for working with the `.wat` grammar.
 pub mod wat;
 
-/// Comment nodes for both the `.wast` and `.wat` grammar.
+/// Functions for working with the `.wit` grammar.
+pub mod wit;
+
+/// Comment nodes for the component model, `.wast`, `.wat` and `.wit` grammars.
 pub const COMMENT_NODES: &[u16] = &[
+    component::kind::COMMENT_BLOCK_ANNOT,
+    component::kind::COMMENT_BLOCK,
+    component::kind::COMMENT_LINE_ANNOT,
+    component::kind::COMMENT_LINE,
     wast::kind::COMMENT_BLOCK_ANNOT,
     wast::kind::COMMENT_BLOCK,
     wast::kind::COMMENT_LINE_ANNOT,
@@ -16,4 +26,6 @@ pub const COMMENT_NODES: &[u16] = &[
     wat::kind::COMMENT_BLOCK,
     wat::kind::COMMENT_LINE_ANNOT,
     wat::kind::COMMENT_LINE,
+    wit::kind::COMMENT_BLOCK,
+    wit::kind::COMMENT_LINE,
 ];
@@ -0,0 +1,142 @@
+//! Functions for working with the component model grammar.
+
+pub mod field {
+    #![allow(missing_docs)]
+
+    wasm_lsp_macros::field_ids! {
+        language: "wasm.component",
+        fields: [
+            (IDENTIFIER, "identifier"),
+        ],
+    }
+}
+
+pub mod kind {
+    #![allow(missing_docs)]
+
+    wasm_lsp_macros::node_kind_ids! {
+        language: "wasm.component",
+        node_kinds: [
+            (ALIAS_TARGET_INLINE, "alias_target_inline", true),
+            (ALIAS_TARGET, "alias_target", true),
+            (ALIAS, "alias", true),
+            (CANON_OPT, "canon_opt", true),
+            (CANON, "canon", true),
+            (COMMENT_BLOCK_ANNOT, "comment_block_annot", true),
+            (COMMENT_BLOCK, "comment_block", true),
+            (COMMENT_LINE_ANNOT, "comment_line_annot", true),
+            (COMMENT_LINE, "comment_line", true),
+            (COMPONENT_EXPORT, "component_export", true),
+            (COMPONENT_FUNC_TYPE, "component_func_type", true),
+            (COMPONENT_FUNC, "component_func", true),
+            (COMPONENT_IMPORT, "component_import", true),
+            (COMPONENT_PARAM, "component_param", true),
+            (COMPONENT_RESULT, "component_result", true),
+            (COMPONENT_START, "component_start", true),
+            (COMPONENT_TYPE_USE, "component_type_use", true),
+            (COMPONENT_TYPE, "component_type", true),
+            (COMPONENT, "component", true),
+            (CORE_EXPORT_DECL, "core_export_decl", true),
+            (CORE_FUNC, "core_func", true),
+            (CORE_INLINE_EXPORT, "core_inline_export", true),
+            (CORE_INSTANCE, "core_instance", true),
+            (CORE_INSTANTIATE_ARG, "core_instantiate_arg", true),
+            (CORE_INSTANTIATE, "core_instantiate", true),
+            (CORE_MODULE_TYPE, "core_module_type", true),
+            (CORE_MODULE, "core_module", true),
+            (CORE_SORT_INDEX, "core_sort_index", true),
+            (CORE_SORT, "core_sort", true),
+            (CORE_TYPE, "core_type", true),
+            (EXPORT_DECL, "export_decl", true),
+            (EXPORT_NAME, "export_name", true),
+            (EXTERN_DESC, "extern_desc", true),
+            (IDENTIFIER, "identifier", true),
+            (IMPORT_NAME, "import_name", true),
+            (INDEX, "index", true),
+            (INLINE_EXPORT, "inline_export", true),
+            (INSTANCE_TYPE, "instance_type", true),
+            (INSTANCE, "instance", true),
+            (INSTANTIATE_ARG, "instantiate_arg", true),
+            (INSTANTIATE, "instantiate", true),
+            (INTERFACE_NAME, "interface_name", true),
+            (MODULE_FIELD_DATA, "module_field_data", true),
+            (MODULE_FIELD_ELEM, "module_field_elem", true),
+            (MODULE_FIELD_EXPORT, "module_field_export", true),
+            (MODULE_FIELD_FUNC, "module_field_func", true),
+            (MODULE_FIELD_GLOBAL, "module_field_global", true),
+            (MODULE_FIELD_IMPORT, "module_field_import", true),
+            (MODULE_FIELD_MEMORY, "module_field_memory", true),
+            (MODULE_FIELD_REC, "module_field_rec", true),
+            (MODULE_FIELD_START, "module_field_start", true),
+            (MODULE_FIELD_TABLE, "module_field_table", true),
+            (MODULE_FIELD_TAG, "module_field_tag", true),
+            (MODULE_FIELD_TYPE, "module_field_type", true),
+            (MODULE_FIELD, "module_field", true),
+            (PRIMITIVE_VAL_TYPE, "primitive_val_type", true),
+            (RECORD_FIELD, "record_field", true),
+            (RESOURCE_TYPE, "resource_type", true),
+            (ROOT, "ROOT", true),
+            (SORT_INDEX, "sort_index", true),
+            (SORT, "sort", true),
+            (STRING, "string", true),
+            (TYPE_BOUND, "type_bound", true),
+            (TYPE_DEFINITION, "type_definition", true),
+            (VAL_TYPE, "val_type", true),
+            (VARIANT_CASE, "variant_case", true),
+        ],
+    }
+
+    pub mod token {
+        #![allow(missing_docs)]
+
+        wasm_lsp_macros::node_kind_ids! {
+            language: "wasm.component",
+            node_kinds: [
+                (ALIAS, "alias", false),
+                (CANON, "canon", false),
+                (COMPONENT, "component", false),
+                (CORE, "core", false),
+                (EXPORT, "export", false),
+                (FUNC, "func", false),
+                (IMPORT, "import", false),
+                (INSTANCE, "instance", false),
+                (INSTANTIATE, "instantiate", false),
+                (MODULE, "module", false),
+                (OUTER, "outer", false),
+                (TYPE, "type", false),
+                (WITH, "with", false),
+            ],
+        }
+    }
+}
+
+pub mod grouped {
+    #![allow(missing_docs)]
+
+    pub const DEFINITIONS: &[u16] = &[
+        super::kind::ALIAS,
+        super::kind::CANON,
+        super::kind::COMPONENT,
+        super::kind::COMPONENT_EXPORT,
+        super::kind::COMPONENT_FUNC,
+        super::kind::COMPONENT_IMPORT,
+        super::kind::CORE_FUNC,
+        super::kind::CORE_INSTANCE,
+        super::kind::CORE_MODULE,
+        super::kind::CORE_TYPE,
+        super::kind::INSTANCE,
+        super::kind::TYPE_DEFINITION,
+    ];
+
+    pub const MODULE_FIELDS: &[u16] = &[
+        super::kind::MODULE_FIELD_DATA,
+        super::kind::MODULE_FIELD_ELEM,
+        super::kind::MODULE_FIELD_FUNC,
+        super::kind::MODULE_FIELD_GLOBAL,
+        super::kind::MODULE_FIELD_MEMORY,
+        super::kind::MODULE_FIELD_REC,
+        super::kind::MODULE_FIELD_TABLE,
+        super::kind::MODULE_FIELD_TAG,
+        super::kind::MODULE_FIELD_TYPE,
+    ];
+}
@@ -20,11 +20,13 @@ pub mod kind {
             (ACTION_GET, "action_get", true),
             (ACTION_INVOKE, "action_invoke", true),
             (ACTION, "action", true),
+            (ADDRESS_TYPE, "address_type", true),
             (ALIGN_OFFSET_VALUE, "align_offset_value", true),
             (ALIGN_VALUE, "align_value", true),
             (ANNOTATION_PARENS, "annotation_parens", true),
             (ANNOTATION_PART, "annotation_part", true),
             (ANNOTATION, "annotation", true),
+            (ARRAY_TYPE, "array_type", true),
             (ASSERT_EXHAUSTION, "assert_exhaustion", true),
             (ASSERT_INVALID, "assert_invalid", true),
             (ASSERT_MALFORMED, "assert_malformed", true),
@@ -38,6 +40,8 @@ pub mod kind {
             (BLOCK_BLOCK, "block_block", true),
             (BLOCK_IF, "block_if", true),
             (BLOCK_LOOP, "block_loop", true),
+            (BLOCK_TRY_TABLE, "block_try_table", true),
+            (CATCH_CLAUSE, "catch_clause", true),
             (COMMAND, "command", true),
             (COMMENT_BLOCK_ANNOT, "comment_block_annot", true),
             (COMMENT_BLOCK, "comment_block", true),
@@ -55,6 +59,7 @@ pub mod kind {
             (EXPORT_DESC_GLOBAL, "export_desc_global", true),
             (EXPORT_DESC_MEMORY, "export_desc_memory", true),
             (EXPORT_DESC_TABLE, "export_desc_table", true),
+            (EXPORT_DESC_TAG, "export_desc_tag", true),
             (EXPORT_DESC, "export_desc", true),
             (EXPORT, "export", true),
             (EXPR_PLAIN_CONST, "expr_plain_const", true),
@@ -64,7 +69,9 @@ pub mod kind {
             (EXPR1_IF, "expr1_if", true),
             (EXPR1_LOOP, "expr1_loop", true),
             (EXPR1_PLAIN, "expr1_plain", true),
+            (EXPR1_TRY_TABLE, "expr1_try_table", true),
             (EXPR1, "expr1", true),
+            (FIELD_TYPE, "field_type", true),
             (FLOAT, "float", true),
             (FUNC_LOCALS_MANY, "func_locals_many", true),
             (FUNC_LOCALS_ONE, "func_locals_one", true),
@@ -77,6 +84,7 @@ pub mod kind {
             (GLOBAL_TYPE_IMM, "global_type_imm", true),
             (GLOBAL_TYPE_MUT, "global_type_mut", true),
             (GLOBAL_TYPE, "global_type", true),
+            (HEAP_TYPE, "heap_type", true),
             (HEX_FLOAT, "hex_float", true),
             (HEX_NAT, "hex_nat", true),
             (IDENTIFIER, "identifier", true),
@@ -85,6 +93,7 @@ pub mod kind {
             (IMPORT_DESC_GLOBAL_TYPE, "import_desc_global_type", true),
             (IMPORT_DESC_MEMORY_TYPE, "import_desc_memory_type", true),
             (IMPORT_DESC_TABLE_TYPE, "import_desc_table_type", true),
+            (IMPORT_DESC_TAG, "import_desc_tag", true),
             (IMPORT_DESC_TYPE_USE, "import_desc_type_use", true),
             (IMPORT_DESC, "import_desc", true),
             (IMPORT, "import", true),
@@ -114,8 +123,10 @@ pub mod kind {
             (MODULE_FIELD_GLOBAL, "module_field_global", true),
             (MODULE_FIELD_IMPORT, "module_field_import", true),
             (MODULE_FIELD_MEMORY, "module_field_memory", true),
+            (MODULE_FIELD_REC, "module_field_rec", true),
             (MODULE_FIELD_START, "module_field_start", true),
             (MODULE_FIELD_TABLE, "module_field_table", true),
+            (MODULE_FIELD_TAG, "module_field_tag", true),
             (MODULE_FIELD_TYPE, "module_field_type", true),
             (MODULE_FIELD, "module_field", true),
             (MODULE, "module", true),
@@ -132,6 +143,10 @@ pub mod kind {
             (OFFSET_EXPR, "offset_expr", true),
             (OFFSET_VALUE, "offset_value", true),
             (OFFSET, "offset", true),
+            (OP_ARRAY_COPY, "op_array_copy", true),
+            (OP_ARRAY_NEW_FIXED, "op_array_new_fixed", true),
+            (OP_ARRAY_SEGMENT, "op_array_segment", true),
+            (OP_BR_ON_CAST, "op_br_on_cast", true),
             (OP_CONST_REF, "op_const_ref", true),
             (OP_CONST, "op_const", true),
             (OP_FUNC_BIND, "op_func_bind", true),
@@ -140,16 +155,28 @@ pub mod kind {
             (OP_INDEX, "op_index", true),
             (OP_LET, "op_let", true),
             (OP_NULLARY, "op_nullary", true),
+            (OP_REF_TYPE, "op_ref_type", true),
             (OP_SELECT, "op_select", true),
             (OP_SIMD_CONST, "op_simd_const", true),
             (OP_SIMD_LANE, "op_simd_lane", true),
             (OP_SIMD_OFFSET_OPT_ALIGN_OPT, "opt_simd_offset_opt_align_opt", true),
+            (OP_STRUCT_FIELD, "op_struct_field", true),
             (OP_TABLE_COPY, "op_table_copy", true),
             (OP_TABLE_INIT, "op_table_init", true),
+            (PACKED_TYPE_I16, "packed_type_i16", true),
+            (PACKED_TYPE_I8, "packed_type_i8", true),
             (REF_KIND, "ref_kind", true),
+            (REF_TYPE_ANYREF, "ref_type_anyref", true),
+            (REF_TYPE_ARRAYREF, "ref_type_arrayref", true),
+            (REF_TYPE_EQREF, "ref_type_eqref", true),
             (REF_TYPE_EXTERNREF, "ref_type_externref", true),
             (REF_TYPE_FUNCREF, "ref_type_funcref", true),
+            (REF_TYPE_I31REF, "ref_type_i31ref", true),
+            (REF_TYPE_NULLEXTERNREF, "ref_type_nullexternref", true),
+            (REF_TYPE_NULLFUNCREF, "ref_type_nullfuncref", true),
+            (REF_TYPE_NULLREF, "ref_type_nullref", true),
             (REF_TYPE_REF, "ref_type_ref", true),
+            (REF_TYPE_STRUCTREF, "ref_type_structref", true),
             (REF_TYPE, "ref_type", true),
             (REGISTER, "register", true),
             (RESERVED, "reserved", true),
@@ -164,7 +191,13 @@ pub mod kind {
             (SCRIPT_MODULE_QUOTE, "script_module_quote", true),
             (SCRIPT_MODULE, "script_module", true),
             (SHARE, "share", true),
+            (STORAGE_TYPE, "storage_type", true),
             (STRING, "string", true),
+            (STRUCT_FIELD_MANY, "struct_field_many", true),
+            (STRUCT_FIELD_ONE, "struct_field_one", true),
+            (STRUCT_FIELD, "struct_field", true),
+            (STRUCT_TYPE, "struct_type", true),
+            (SUB_TYPE, "sub_type", true),
             (TABLE_FIELDS_ELEM, "table_fields_elem", true),
             (TABLE_FIELDS_TYPE, "table_fields_type", true),
             (TABLE_TYPE, "table_type", true),
@@ -184,6 +217,7 @@ pub mod kind {
             language: "wasm.wast",
             node_kinds: [
                 (ALIGN, "align", false),
+                (ARRAY, "array", false),
                 (ASSERT_EXHAUSTION, "assert_exhaustion", false),
                 (ASSERT_INVALID, "assert_invalid", false),
                 (ASSERT_MALFORMED, "assert_malformed", false),
@@ -196,6 +230,10 @@ pub mod kind {
                 (BLOCK, "block", false),
                 (BR_TABLE, "br_table", false),
                 (CALL_INDIRECT, "call_indirect", false),
+                (CATCH_ALL_REF, "catch_all_ref", false),
+                (CATCH_ALL, "catch_all", false),
+                (CATCH_REF, "catch_ref", false),
+                (CATCH, "catch", false),
                 (DATA, "data", false),
                 (DECLARE, "declare", false),
                 (DOLLAR_SIGN, "$", false),
@@ -207,13 +245,17 @@ pub mod kind {
                 (EXTERNREF, "externref", false),
                 (F32, "f32", false),
                 (F64, "f64", false),
+                (FIELD, "field", false),
+                (FINAL, "final", false),
                 (FULL_STOP, ".", false),
                 (FUNC, "func", false),
                 (FUNCREF, "funcref", false),
                 (GET, "get", false),
                 (GLOBAL, "global", false),
+                (I16, "i16", false),
                 (I32, "i32", false),
                 (I64, "i64", false),
+                (I8, "i8", false),
                 (IF, "if", false),
                 (IMPORT, "import", false),
                 (INF, "inf", false),
@@ -231,15 +273,21 @@ pub mod kind {
                 (OUTPUT, "output", false),
                 (PARAM, "param", false),
                 (QUOTE, "quote", false),
+                (REC, "rec", false),
                 (REF, "ref", false),
                 (REGISTER, "register", false),
                 (RESULT, "result", false),
+                (RETURN_CALL_INDIRECT, "return_call_indirect", false),
                 (REVERSE_SOLIDUS_REVERSE_SOLIDUS, "\\", false),
                 (RPAREN, ")", false),
                 (SCRIPT, "script", false),
                 (SEMICOLON_SEMICOLON, ";;", false),
+                (STRUCT, "struct", false),
+                (SUB, "sub", false),
                 (TABLE, "table", false),
+                (TAG, "tag", false),
                 (THEN, "then", false),
+                (TRY_TABLE, "try_table", false),
                 (TYPE, "type", false),
                 (V128, "v128", false),
             ],
@@ -256,7 +304,9 @@ pub mod grouped {
         super::kind::MODULE_FIELD_FUNC,
         super::kind::MODULE_FIELD_GLOBAL,
         super::kind::MODULE_FIELD_MEMORY,
+        super::kind::MODULE_FIELD_REC,
         super::kind::MODULE_FIELD_TABLE,
+        super::kind::MODULE_FIELD_TAG,
         super::kind::MODULE_FIELD_TYPE,
     ];
 }
@@ -0,0 +1,154 @@
+//! Functions for working with the `.wit` grammar.
+
+pub mod field {
+    #![allow(missing_docs)]
+
+    wasm_lsp_macros::field_ids! {
+        language: "wasm.wit",
+        fields: [
+            (ALIAS, "alias"),
+            (IDENTIFIER, "identifier"),
+            (INTERFACE, "interface"),
+            (NAMESPACE, "namespace"),
+            (NAME, "name"),
+            (PACKAGE, "package"),
+            (VERSION, "version"),
+        ],
+    }
+}
+
+pub mod kind {
+    #![allow(missing_docs)]
+
+    wasm_lsp_macros::node_kind_ids! {
+        language: "wasm.wit",
+        node_kinds: [
+            (COMMENT_BLOCK, "comment_block", true),
+            (COMMENT_LINE, "comment_line", true),
+            (ENUM_CASE, "enum_case", true),
+            (ENUM_ITEM, "enum_item", true),
+            (EXPORT_ITEM, "export_item", true),
+            (FLAGS_FIELD, "flags_field", true),
+            (FLAGS_ITEM, "flags_item", true),
+            (FUNC_ITEM, "func_item", true),
+            (FUNC_TYPE, "func_type", true),
+            (FUTURE_TYPE, "future_type", true),
+            (HANDLE_TYPE, "handle_type", true),
+            (IDENTIFIER, "identifier", true),
+            (IMPORT_ITEM, "import_item", true),
+            (INCLUDE_ITEM, "include_item", true),
+            (INTEGER, "integer", true),
+            (INTERFACE_ITEM, "interface_item", true),
+            (LIST_TYPE, "list_type", true),
+            (OPTION_TYPE, "option_type", true),
+            (PACKAGE_DECL, "package_decl", true),
+            (PACKAGE_NAME, "package_name", true),
+            (PARAM_LIST, "param_list", true),
+            (PARAM, "param", true),
+            (PRIMITIVE_TYPE, "primitive_type", true),
+            (RECORD_FIELD, "record_field", true),
+            (RECORD_ITEM, "record_item", true),
+            (RESOURCE_ITEM, "resource_item", true),
+            (RESOURCE_METHOD, "resource_method", true),
+            (RESULT_LIST, "result_list", true),
+            (RESULT_TYPE, "result_type", true),
+            (ROOT, "ROOT", true),
+            (STREAM_TYPE, "stream_type", true),
+            (TOPLEVEL_USE, "toplevel_use", true),
+            (TUPLE_TYPE, "tuple_type", true),
+            (TYPE_ALIAS_ITEM, "type_alias_item", true),
+            (TYPE_REF, "type_ref", true),
+            (USE_ITEM, "use_item", true),
+            (USE_NAME, "use_name", true),
+            (USE_PATH, "use_path", true),
+            (VARIANT_CASE, "variant_case", true),
+            (VARIANT_ITEM, "variant_item", true),
+            (VERSION, "version", true),
+            (WORLD_ITEM, "world_item", true),
+        ],
+    }
+
+    pub mod token {
+        #![allow(missing_docs)]
+
+        wasm_lsp_macros::node_kind_ids! {
+            language: "wasm.wit",
+            node_kinds: [
+                (ASYNC, "async", false),
+                (AS, "as", false),
+                (BORROW, "borrow", false),
+                (CONSTRUCTOR, "constructor", false),
+                (ENUM, "enum", false),
+                (EXPORT, "export", false),
+                (FLAGS, "flags", false),
+                (FUNC, "func", false),
+                (FUTURE, "future", false),
+                (IMPORT, "import", false),
+                (INCLUDE, "include", false),
+                (INTERFACE, "interface", false),
+                (LIST, "list", false),
+                (OPTION, "option", false),
+                (OWN, "own", false),
+                (PACKAGE, "package", false),
+                (RECORD, "record", false),
+                (RESOURCE, "resource", false),
+                (RESULT, "result", false),
+                (STATIC, "static", false),
+                (STREAM, "stream", false),
+                (TUPLE, "tuple", false),
+                (TYPE, "type", false),
+                (USE, "use", false),
+                (VARIANT, "variant", false),
+                (WORLD, "world", false),
+            ],
+        }
+    }
+}
+
+pub mod grouped {
+    #![allow(missing_docs)]
+
+    pub const ITEMS: &[u16] = &[
+        super::kind::INTERFACE_ITEM,
+        super::kind::TOPLEVEL_USE,
+        super::kind::WORLD_ITEM,
+    ];
+
+    pub const KEYWORDS: &[u16] = &[
+        super::kind::token::ASYNC,
+        super::kind::token::AS,
+        super::kind::token::BORROW,
+        super::kind::token::CONSTRUCTOR,
+        super::kind::token::ENUM,
+        super::kind::token::EXPORT,
+        super::kind::token::FLAGS,
+        super::kind::token::FUNC,
+        super::kind::token::FUTURE,
+        super::kind::token::IMPORT,
+        super::kind::token::INCLUDE,
+        super::kind::token::INTERFACE,
+        super::kind::token::LIST,
+        super::kind::token::OPTION,
+        super::kind::token::OWN,
+        super::kind::token::PACKAGE,
+        super::kind::token::RECORD,
+        super::kind::token::RESOURCE,
+        super::kind::token::RESULT,
+        super::kind::token::STATIC,
+        super::kind::token::STREAM,
+        super::kind::token::TUPLE,
+        super::kind::token::TYPE,
+        super::kind::token::USE,
+        super::kind::token::VARIANT,
+        super::kind::token::WORLD,
+    ];
+
+    pub const TYPE_ITEMS: &[u16] = &[
+        super::kind::ENUM_ITEM,
+        super::kind::FLAGS_ITEM,
+        super::kind::RECORD_ITEM,
+        super::kind::RESOURCE_ITEM,
+        super::kind::TYPE_ALIAS_ITEM,
+        super::kind::VARIANT_ITEM,
+    ];
+}
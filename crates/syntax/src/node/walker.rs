@@ -247,15 +247,19 @@ impl<'tree, C: Context<'tree>> NodeWalker<'tree, C> {
     /// Reconstruct the context stack from the current node position.
     #[inline]
     fn reconstruct_stack(&mut self) {
-        use crate::language::{wast, wat};
-        use Language::{Wast, Wat};
+        use crate::language::{component, wast, wat, wit};
+        use Language::{Component, Wasm, Wast, Wat, Wit};
 
         let language = self.language;
         let node = self.node();
         let kind = node.kind_id();
 
         // Reconstruct the stack by traversing upward if the current node isn't ROOT.
-        if (language == Wast && wast::kind::ROOT != kind) || (language == Wat && wat::kind::ROOT != kind) {
+        if (language == Component && component::kind::ROOT != kind)
+            || (language == Wast && wast::kind::ROOT != kind)
+            || ((language == Wat || language == Wasm) && wat::kind::ROOT != kind)
+            || (language == Wit && wit::kind::ROOT != kind)
+        {
             let cursor = &mut node.walk();
             loop {
                 let previous = self.node();
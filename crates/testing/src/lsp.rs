@@ -23,11 +23,25 @@ pub mod initialize {
         })
     }
 
+    pub fn request_with_capabilities(capabilities: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "initialize",
+            "params": {
+                "capabilities": capabilities,
+            },
+            "id": 1,
+        })
+    }
+
     pub fn response() -> Value {
+        let mut capabilities = serde_json::to_value(wasm_lsp_server::Server::capabilities()).unwrap();
+        capabilities["diagnosticProvider"] =
+            serde_json::to_value(wasm_lsp_server::Server::diagnostic_capabilities()).unwrap();
         json!({
             "jsonrpc": "2.0",
             "result": {
-                "capabilities": wasm_lsp_server::Server::capabilities(),
+                "capabilities": capabilities,
             },
             "id": 1,
         })
@@ -67,19 +81,100 @@ pub mod shutdown {
 }
 
 pub mod text_document {
+    pub mod code_lens {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/codeLens",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(lenses: Option<Vec<CodeLens>>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": lenses,
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod definition {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url, position: Position) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/definition",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "position": position,
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(response: Option<GotoDefinitionResponse>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": response,
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod diagnostic {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url, previous_result_id: Option<&str>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/diagnostic",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "previousResultId": previous_result_id,
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(result: wasm_lsp_server::ext::DocumentDiagnosticReport) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": result,
+                "id": 1,
+            })
+        }
+    }
+
     pub mod did_change {
 
         pub mod notification {
             use serde_json::{json, Value};
             use tower_lsp::lsp_types::*;
 
-            pub fn entire<S: AsRef<str>>(uri: &Url, text: S) -> Value {
+            pub fn entire<S: AsRef<str>>(uri: &Url, version: i64, text: S) -> Value {
                 json!({
                     "jsonrpc": "2.0",
                     "method": "textDocument/didChange",
                     "params": {
                         "textDocument": {
                             "uri": uri,
+                            "version": version,
                         },
                         "contentChanges": [
                             {
@@ -155,6 +250,36 @@ pub mod text_document {
         }
     }
 
+    pub mod formatting {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/formatting",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "options": {
+                        "tabSize": 4,
+                        "insertSpaces": true,
+                    },
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(edits: Option<Vec<TextEdit>>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": edits,
+                "id": 1,
+            })
+        }
+    }
+
     pub mod hover {
         use serde_json::{json, Value};
         use tower_lsp::lsp_types::*;
@@ -173,27 +298,270 @@ pub mod text_document {
             })
         }
 
-        pub fn response() -> Value {
+        pub fn response(hover: Option<Hover>) -> Value {
             json!({
                 "jsonrpc": "2.0",
-                "result": {
+                "result": hover,
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod inlay_hint {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url, range: Range) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/inlayHint",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "range": range,
                 },
                 "id": 1,
             })
         }
+
+        pub fn response(hints: Option<Vec<InlayHint>>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": hints,
+                "id": 1,
+            })
+        }
     }
 
     pub mod publish_diagnostics {
         use serde_json::{json, Value};
         use tower_lsp::lsp_types::*;
 
-        pub fn notification(uri: &Url, diagnostics: &[Diagnostic]) -> Value {
+        pub fn notification(uri: &Url, diagnostics: &[Diagnostic], version: Option<i32>) -> Value {
             json!({
                 "jsonrpc": "2.0",
                 "method": "textDocument/publishDiagnostics",
+                "params": PublishDiagnosticsParams::new(uri.clone(), diagnostics.to_vec(), version),
+            })
+        }
+    }
+
+    pub mod references {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url, position: Position, include_declaration: bool) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/references",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "position": position,
+                    "context": {
+                        "includeDeclaration": include_declaration,
+                    },
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(locations: Option<Vec<Location>>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": locations,
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod rename {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request<S: AsRef<str>>(uri: &Url, position: Position, new_name: S) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/rename",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "position": position,
+                    "newName": new_name.as_ref(),
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(edit: Option<WorkspaceEdit>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": edit,
+                "id": 1,
+            })
+        }
+    }
+}
+
+pub mod wasm {
+    pub mod assemble {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "wasm/assemble",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(contents: &str) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": {
+                    "contents": contents,
+                },
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod disassemble {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url, contents: &str) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "wasm/disassemble",
                 "params": {
                     "uri": uri,
-                    "diagnostics": diagnostics,
+                    "contents": contents,
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(uri: &Url, text: &str) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": {
+                    "uri": uri,
+                    "text": text,
+                },
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod size_report {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "wasm/sizeReport",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(result: Value) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": result,
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod source_location {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn request(uri: &Url, function: u32, offset: u32) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "wasm/sourceLocation",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                    "function": function,
+                    "offset": offset,
+                },
+                "id": 1,
+            })
+        }
+
+        pub fn response(location: Option<Location>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "result": location,
+                "id": 1,
+            })
+        }
+    }
+}
+
+pub mod window {
+    pub mod log_message {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn notification<S: AsRef<str>>(typ: MessageType, message: S) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "window/logMessage",
+                "params": LogMessageParams {
+                    typ,
+                    message: message.as_ref().into(),
+                },
+            })
+        }
+    }
+
+    pub mod show_message {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn notification<S: AsRef<str>>(typ: MessageType, message: S) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "window/showMessage",
+                "params": ShowMessageParams {
+                    typ,
+                    message: message.as_ref().into(),
+                },
+            })
+        }
+    }
+}
+
+pub mod workspace {
+    pub mod did_change_configuration {
+        use serde_json::{json, Value};
+
+        pub fn notification(settings: Value) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "workspace/didChangeConfiguration",
+                "params": {
+                    "settings": settings,
                 },
             })
         }
@@ -1,10 +1,10 @@
 use serde_json::Value;
-use tower_lsp::{ClientSocket, LspService};
+use tower_lsp::ClientSocket;
 use tower_test::mock::Spawn;
-use wasm_lsp_server::Server;
+use wasm_lsp_server::{Server, Service};
 
 pub async fn send(
-    service: &mut tower_test::mock::Spawn<LspService<Server>>,
+    service: &mut tower_test::mock::Spawn<Service>,
     request: &serde_json::Value,
 ) -> Result<Option<Value>, tower_lsp::ExitedError> {
     let request = serde_json::from_value(request.clone()).unwrap();
@@ -13,23 +13,30 @@ pub async fn send(
     Ok(response)
 }
 
-pub async fn spawn() -> anyhow::Result<(tower_test::mock::Spawn<LspService<Server>>, ClientSocket)> {
+pub async fn spawn() -> anyhow::Result<(tower_test::mock::Spawn<Service>, ClientSocket)> {
+    spawn_with(None).await
+}
+
+pub async fn spawn_with(
+    spawner: Option<wasm_lsp_server::core::Spawner>,
+) -> anyhow::Result<(tower_test::mock::Spawn<Service>, ClientSocket)> {
     #[rustfmt::skip]
     #[cfg(target_arch = "wasm32")]
     let languages = wasm_lsp_server::core::SessionLanguages {
-        wast: wasm_lsp_languages::language::wast().await?,
-        wat : wasm_lsp_languages::language::wat ().await?,
+        component: wasm_lsp_languages::language::component().await?,
+        wast     : wasm_lsp_languages::language::wast     ().await?,
+        wat      : wasm_lsp_languages::language::wat      ().await?,
+        wit      : wasm_lsp_languages::language::wit      ().await?,
     };
     #[rustfmt::skip]
     #[cfg(not(target_arch = "wasm32"))]
     let languages = wasm_lsp_server::core::SessionLanguages {
-        wast: wasm_lsp_languages::language::wast(),
-        wat : wasm_lsp_languages::language::wat (),
+        component: wasm_lsp_languages::language::component(),
+        wast     : wasm_lsp_languages::language::wast     (),
+        wat      : wasm_lsp_languages::language::wat      (),
+        wit      : wasm_lsp_languages::language::wit      (),
     };
-    let (service, socket) = LspService::new(move |client| {
-        let server = wasm_lsp_server::Server::new(languages, client);
-        server.unwrap()
-    });
+    let (service, socket) = Server::service(languages, spawner)?;
     Ok((Spawn::new(service), socket))
 }
 
@@ -684,10 +684,21 @@ mod util {
                 cmd.status()?;
             }
 
+            // Configure the path of the grammars maintained in `wasm-lsp-languages`.
+            let languages_path = [root_path, "crates", "languages", "grammars"]
+                .iter()
+                .collect::<PathBuf>();
+            let languages_path = languages_path.to_str().unwrap();
+
             // Iterate through the different grammar types.
-            for grammar in &["wast", "wat"] {
+            for (path, grammar) in &[
+                (tree_sitter_path, "wast"),
+                (tree_sitter_path, "wat"),
+                (languages_path, "component"),
+                (languages_path, "wit"),
+            ] {
                 // Configure the grammar directory path.
-                let grammar_path = [tree_sitter_path, grammar].iter().collect::<PathBuf>();
+                let grammar_path = [path, grammar].iter().collect::<PathBuf>();
                 let grammar_path = dunce::canonicalize(grammar_path)?;
                 let grammar_path = grammar_path.to_str().unwrap();
 